@@ -1,33 +1,45 @@
 //! Tauri commands for IPC
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::time::Instant;
 use tauri::{command, AppHandle};
 
+use data_x::scanner::ScanOptions;
+
 use crate::duplicates::{self, DuplicateScanConfig, DuplicateScanResult};
 use crate::scanner;
-use crate::ssh::{self, SSHConnection, SSHConnectionInput, SSHTestResult};
-use crate::types::{DiskInfo, FileNode, ScanResult};
+use crate::ssh::{self, SSHConfigHost, SSHConnection, SSHConnectionInput, SSHTestResult};
+use crate::types::{DiskInfo, ScanResult};
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 
 /// Scan a directory and return the file tree (with progress events)
+///
+/// Accepts the same options as the CLI (`--depth`, `--exclude`,
+/// `--no-cross-mount`, `--apparent-size`) and uses the same defaults,
+/// so both report identical sizes.
 #[command]
 pub async fn scan_directory(
     app: AppHandle,
     path: String,
     max_depth: Option<usize>,
+    exclude_patterns: Option<Vec<String>>,
+    cross_mount: Option<bool>,
+    apparent_size: Option<bool>,
 ) -> Result<ScanResult, String> {
-    let path_clone = path.clone();
+    let options = ScanOptions::new(PathBuf::from(&path))
+        .with_max_depth(max_depth)
+        .with_exclude_patterns(exclude_patterns.unwrap_or_default())
+        .with_cross_mount(cross_mount.unwrap_or(true))
+        .with_apparent_size(apparent_size.unwrap_or(false));
 
     // Run scan in a blocking task to not freeze the UI
     let result = tokio::task::spawn_blocking(move || {
-        let path = Path::new(&path_clone);
         let start = Instant::now();
 
-        let root = scanner::scan_directory_with_progress(path, max_depth, Some(&app), None)?;
+        let root = scanner::scan_directory_with_progress(options, Some(&app))?;
 
-        let total_files = count_files(&root);
+        let total_files = root.file_count;
         let total_size = root.size;
         let scan_time_ms = start.elapsed().as_millis() as u64;
 
@@ -44,14 +56,6 @@ pub async fn scan_directory(
     Ok(result)
 }
 
-fn count_files(node: &FileNode) -> u64 {
-    if node.is_dir {
-        node.children.iter().map(count_files).sum()
-    } else {
-        1
-    }
-}
-
 /// Get disk space information
 #[command]
 pub fn get_disk_info(path: String) -> Result<DiskInfo, String> {
@@ -126,13 +130,40 @@ pub async fn open_in_terminal(path: Option<String>) -> Result<(), String> {
 
     let tui_str = tui_path.to_string_lossy();
 
-    #[cfg(target_os = "macos")]
+    #[cfg(unix)]
     {
         let cmd_args = if let Some(ref p) = path {
             format!("'{}' --tui '{}'", tui_str.replace("'", "'\\''"), p.replace("'", "'\\''"))
         } else {
             format!("'{}' --help", tui_str.replace("'", "'\\''"))
         };
+        open_terminal_with(&cmd_args)?;
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let cmd = if let Some(p) = path {
+            format!("\"{}\" --tui \"{}\"", tui_str, p)
+        } else {
+            format!("\"{}\" --help", tui_str)
+        };
+
+        std::process::Command::new("cmd")
+            .args(["/c", "start", "cmd", "/k", &cmd])
+            .spawn()
+            .map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+}
+
+/// Run `cmd_args`, a POSIX shell command line, in a new terminal window
+#[cfg(unix)]
+fn open_terminal_with(cmd_args: &str) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    {
+        // Inside an AppleScript string literal
+        let cmd_args = cmd_args.replace('\\', "\\\\").replace('"', "\\\"");
 
         // Check if iTerm2 is installed
         let iterm_exists = Path::new("/Applications/iTerm.app").exists();
@@ -180,52 +211,31 @@ pub async fn open_in_terminal(path: Option<String>) -> Result<(), String> {
                 .spawn()
                 .map_err(|e| e.to_string())?;
         }
+        Ok(())
     }
 
-    #[cfg(target_os = "windows")]
+    #[cfg(not(target_os = "macos"))]
     {
-        let cmd = if let Some(p) = path {
-            format!("\"{}\" --tui \"{}\"", tui_str, p)
-        } else {
-            format!("\"{}\" --help", tui_str)
-        };
-
-        std::process::Command::new("cmd")
-            .args(["/c", "start", "cmd", "/k", &cmd])
-            .spawn()
-            .map_err(|e| e.to_string())?;
-    }
-
-    #[cfg(target_os = "linux")]
-    {
-        let cmd = if let Some(p) = path {
-            format!("'{}' --tui '{}'", tui_str, p)
-        } else {
-            format!("'{}' --help", tui_str)
-        };
-
         // Try common terminal emulators
         let terminals = ["gnome-terminal", "konsole", "xfce4-terminal", "xterm"];
         for term in terminals {
             let result = match term {
                 "gnome-terminal" => std::process::Command::new(term)
-                    .args(["--", "sh", "-c", &cmd])
+                    .args(["--", "sh", "-c", cmd_args])
                     .spawn(),
                 "konsole" => std::process::Command::new(term)
-                    .args(["-e", "sh", "-c", &cmd])
+                    .args(["-e", "sh", "-c", cmd_args])
                     .spawn(),
                 _ => std::process::Command::new(term)
-                    .args(["-e", &cmd])
+                    .args(["-e", cmd_args])
                     .spawn(),
             };
             if result.is_ok() {
                 return Ok(());
             }
         }
-        return Err("No terminal emulator found".to_string());
+        Err("No terminal emulator found".to_string())
     }
-
-    Ok(())
 }
 
 // =============================================================================
@@ -262,6 +272,12 @@ pub fn delete_ssh_connection(id: String) -> Result<(), String> {
     ssh::delete_connection(&id)
 }
 
+/// List the host aliases from ~/.ssh/config
+#[command]
+pub fn get_ssh_config_hosts() -> Vec<SSHConfigHost> {
+    ssh::get_config_hosts()
+}
+
 /// Test an SSH connection
 #[command]
 pub fn test_ssh_connection(connection: SSHConnectionInput) -> Result<SSHTestResult, String> {
@@ -275,6 +291,8 @@ pub fn test_ssh_connection(connection: SSHConnectionInput) -> Result<SSHTestResu
         auth_method: connection.auth_method,
         default_path: connection.default_path,
         timeout_secs: connection.timeout_secs.unwrap_or(30),
+        jump_hosts: connection.jump_hosts,
+        ssh_options: connection.ssh_options,
         created_at: 0,
         last_used_at: None,
     };
@@ -298,6 +316,36 @@ pub async fn scan_remote(
     .map_err(|e| e.to_string())?
 }
 
+/// Permanently delete a file or folder found by a remote scan of `root`
+#[command]
+pub async fn delete_remote(
+    connection_id: String,
+    root: String,
+    path: String,
+    is_dir: bool,
+) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || ssh::delete_remote_path(&connection_id, &root, &path, is_dir))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Open a terminal with an SSH shell in `path` on the connection's host
+#[command]
+pub async fn open_ssh_shell(connection_id: String, path: String) -> Result<(), String> {
+    let cmd_args = ssh::ssh_shell_command_line(&connection_id, &path)?;
+
+    #[cfg(unix)]
+    {
+        open_terminal_with(&cmd_args)
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = cmd_args;
+        Err("Opening an SSH shell needs a POSIX shell".to_string())
+    }
+}
+
 // =============================================================================
 // Duplicate Detection Commands
 // =============================================================================
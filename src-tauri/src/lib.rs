@@ -29,7 +29,10 @@ pub fn run() {
             commands::update_ssh_connection,
             commands::delete_ssh_connection,
             commands::test_ssh_connection,
+            commands::get_ssh_config_hosts,
             commands::scan_remote,
+            commands::delete_remote,
+            commands::open_ssh_shell,
             // Duplicate detection commands
             commands::find_duplicates,
             commands::delete_files,
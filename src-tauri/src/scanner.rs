@@ -1,14 +1,19 @@
 //! File system scanner with progress events
+//!
+//! Thin adapter over the core `data_x::scanner`, so the desktop app walks the
+//! tree exactly like `data-x --json` and only converts the result for the UI.
 
 use std::fs;
 use std::path::Path;
-use std::sync::atomic::{AtomicU64, AtomicBool, Ordering};
-use std::sync::Arc;
-use rayon::prelude::*;
-use walkdir::WalkDir;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc;
+use std::thread;
 use tauri::{AppHandle, Emitter};
 use serde::Serialize;
 
+use data_x::scanner::{self as core_scanner, ScanOptions};
+use data_x::tree::{FileTree, NodeId};
+
 use crate::types::FileNode;
 
 static NODE_ID_COUNTER: AtomicU64 = AtomicU64::new(1);
@@ -25,26 +30,18 @@ pub struct ScanProgress {
     pub bytes_scanned: u64,
 }
 
-/// Quick count of files in a directory (for progress bar)
-pub fn count_files_quick(path: &Path) -> u64 {
-    WalkDir::new(path)
-        .into_iter()
-        .filter_map(|e| e.ok())
-        .count() as u64
-}
-
 /// Scan a directory with progress events
 pub fn scan_directory_with_progress(
-    path: &Path,
-    max_depth: Option<usize>,
+    options: ScanOptions,
     app_handle: Option<&AppHandle>,
-    cancel_flag: Option<Arc<AtomicBool>>,
 ) -> Result<FileNode, String> {
+    let path = options.root_path.clone();
+
     if !path.exists() {
         return Err(format!("Path does not exist: {}", path.display()));
     }
 
-    let metadata = fs::metadata(path).map_err(|e| e.to_string())?;
+    let metadata = fs::metadata(&path).map_err(|e| e.to_string())?;
 
     if !metadata.is_dir() {
         // Single file
@@ -67,135 +64,50 @@ pub fn scan_directory_with_progress(
         });
     }
 
-    // Quick file count for progress
-    let total_files = if app_handle.is_some() {
-        count_files_quick(path)
-    } else {
-        0
-    };
-
-    // Progress counter
-    let files_scanned = Arc::new(AtomicU64::new(0));
-    let bytes_scanned = Arc::new(AtomicU64::new(0));
-
-    // Directory - scan recursively
-    scan_directory_recursive(
-        path,
-        0,
-        max_depth,
-        app_handle,
-        cancel_flag,
-        total_files,
-        &files_scanned,
-        &bytes_scanned,
-    )
-}
-
-fn scan_directory_recursive(
-    path: &Path,
-    depth: usize,
-    max_depth: Option<usize>,
-    app_handle: Option<&AppHandle>,
-    cancel_flag: Option<Arc<AtomicBool>>,
-    total_files: u64,
-    files_scanned: &Arc<AtomicU64>,
-    bytes_scanned: &Arc<AtomicU64>,
-) -> Result<FileNode, String> {
-    // Check for cancellation
-    if let Some(ref flag) = cancel_flag {
-        if flag.load(Ordering::Relaxed) {
-            return Err("Scan cancelled".to_string());
+    // Run the core scanner on its own thread and forward its progress
+    let (tx, rx) = mpsc::sync_channel(1000);
+    let scan_thread = thread::spawn(move || core_scanner::Scanner::new(options, tx).scan());
+
+    for progress in rx {
+        if let core_scanner::ScanProgress::Scanning {
+            path,
+            files_found,
+            estimated_total,
+            bytes_processed,
+        } = progress
+        {
+            if let Some(app) = app_handle {
+                let _ = app.emit("scan-progress", ScanProgress {
+                    files_scanned: files_found,
+                    total_files: estimated_total,
+                    current_path: path.display().to_string(),
+                    bytes_scanned: bytes_processed,
+                });
+            }
         }
     }
 
-    let name = path.file_name()
-        .map(|n| n.to_string_lossy().to_string())
-        .unwrap_or_else(|| path.display().to_string());
-
-    let is_hidden = name.starts_with('.');
-
-    // Check max depth
-    if let Some(max) = max_depth {
-        if depth >= max {
-            // Return directory with estimated size
-            let size = estimate_dir_size(path);
-            return Ok(FileNode {
-                id: next_id(),
-                name,
-                path: path.display().to_string(),
-                size,
-                is_dir: true,
-                is_hidden,
-                extension: None,
-                children: vec![],
-                file_count: 0,
-            });
-        }
-    }
+    let tree = scan_thread
+        .join()
+        .map_err(|_| "Scanner thread panicked".to_string())?
+        .map_err(|e| e.to_string())?;
 
-    // Read directory entries
-    let entries: Vec<_> = match fs::read_dir(path) {
-        Ok(entries) => entries.filter_map(|e| e.ok()).collect(),
-        Err(_) => {
-            // Permission denied or other error - return empty dir
-            return Ok(FileNode {
-                id: next_id(),
-                name,
-                path: path.display().to_string(),
-                size: 0,
-                is_dir: true,
-                is_hidden,
-                extension: None,
-                children: vec![],
-                file_count: 0,
-            });
-        }
-    };
+    let root = tree.root.ok_or_else(|| "Scan produced an empty tree".to_string())?;
+    Ok(tree_to_file_node(&tree, root))
+}
 
-    // Process entries (sequentially for better progress updates, parallel for speed)
-    let children: Vec<FileNode> = if depth < 2 {
-        // Sequential for top levels (better progress updates)
-        entries
-            .iter()
-            .filter_map(|entry| {
-                process_entry(
-                    entry,
-                    depth,
-                    max_depth,
-                    app_handle,
-                    cancel_flag.clone(),
-                    total_files,
-                    files_scanned,
-                    bytes_scanned,
-                )
-            })
-            .collect()
-    } else {
-        // Parallel for deeper levels
-        entries
-            .par_iter()
-            .filter_map(|entry| {
-                process_entry(
-                    entry,
-                    depth,
-                    max_depth,
-                    app_handle,
-                    cancel_flag.clone(),
-                    total_files,
-                    files_scanned,
-                    bytes_scanned,
-                )
-            })
-            .collect()
-    };
+/// Convert a core tree node (and its subtree) to the frontend representation.
+fn tree_to_file_node(tree: &FileTree, node_id: NodeId) -> FileNode {
+    let node = tree.get_node(node_id).expect("Node must exist");
 
-    // Calculate totals
-    let total_size: u64 = children.iter().map(|c| c.size).sum();
-    let file_count: u64 = children.iter().map(|c| c.file_count).sum();
+    let mut children: Vec<FileNode> = tree
+        .get_children(node_id)
+        .into_iter()
+        .map(|child_id| tree_to_file_node(tree, child_id))
+        .collect();
 
     // Sort children: directories first, then by size descending
-    let mut sorted_children = children;
-    sorted_children.sort_by(|a, b| {
+    children.sort_by(|a, b| {
         match (a.is_dir, b.is_dir) {
             (true, false) => std::cmp::Ordering::Less,
             (false, true) => std::cmp::Ordering::Greater,
@@ -203,92 +115,19 @@ fn scan_directory_recursive(
         }
     });
 
-    Ok(FileNode {
+    FileNode {
         id: next_id(),
-        name,
-        path: path.display().to_string(),
-        size: total_size,
-        is_dir: true,
-        is_hidden,
-        extension: None,
-        children: sorted_children,
-        file_count,
-    })
-}
-
-fn process_entry(
-    entry: &fs::DirEntry,
-    depth: usize,
-    max_depth: Option<usize>,
-    app_handle: Option<&AppHandle>,
-    cancel_flag: Option<Arc<AtomicBool>>,
-    total_files: u64,
-    files_scanned: &Arc<AtomicU64>,
-    bytes_scanned: &Arc<AtomicU64>,
-) -> Option<FileNode> {
-    let entry_path = entry.path();
-    let entry_name = entry.file_name().to_string_lossy().to_string();
-    let entry_hidden = entry_name.starts_with('.');
-
-    let metadata = entry.metadata().ok()?;
-    let size = metadata.len();
-
-    // Update progress
-    let scanned = files_scanned.fetch_add(1, Ordering::Relaxed) + 1;
-    bytes_scanned.fetch_add(size, Ordering::Relaxed);
-
-    // Emit progress event every 100 files
-    if scanned % 100 == 0 {
-        if let Some(app) = app_handle {
-            let _ = app.emit("scan-progress", ScanProgress {
-                files_scanned: scanned,
-                total_files,
-                current_path: entry_path.display().to_string(),
-                bytes_scanned: bytes_scanned.load(Ordering::Relaxed),
-            });
-        }
-    }
-
-    if metadata.is_dir() {
-        // Recursively scan subdirectory
-        scan_directory_recursive(
-            &entry_path,
-            depth + 1,
-            max_depth,
-            app_handle,
-            cancel_flag,
-            total_files,
-            files_scanned,
-            bytes_scanned,
-        ).ok()
-    } else {
-        // File
-        let extension = entry_path.extension().map(|e| e.to_string_lossy().to_string());
-        Some(FileNode {
-            id: next_id(),
-            name: entry_name,
-            path: entry_path.display().to_string(),
-            size,
-            is_dir: false,
-            is_hidden: entry_hidden,
-            extension,
-            children: vec![],
-            file_count: 1,
-        })
+        name: node.name.clone(),
+        path: node.path.display().to_string(),
+        size: node.size,
+        is_dir: node.is_dir,
+        is_hidden: node.is_hidden,
+        extension: node.extension.clone(),
+        children,
+        file_count: node.file_count,
     }
 }
 
-/// Quick size estimation for max_depth exceeded directories
-fn estimate_dir_size(path: &Path) -> u64 {
-    WalkDir::new(path)
-        .into_iter()
-        .filter_map(|e| e.ok())
-        .filter_map(|e| e.metadata().ok())
-        .filter(|m| m.is_file())
-        .map(|m| m.len())
-        .sum()
-}
-
 /// Get disk space information
 pub fn get_disk_space(path: &Path) -> Option<crate::types::DiskInfo> {
     use fs2::available_space;
@@ -312,3 +151,30 @@ pub fn get_disk_space(path: &Path) -> Option<crate::types::DiskInfo> {
         mount_point,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_honors_core_options() {
+        let dir = std::env::temp_dir().join(format!("data-x-tauri-scan-{}", std::process::id()));
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("big.bin"), vec![0u8; 4096]).unwrap();
+        fs::write(dir.join("skip.tmp"), vec![0u8; 8192]).unwrap();
+        fs::write(dir.join("sub/small.txt"), b"hello").unwrap();
+
+        let options = ScanOptions::new(dir.clone())
+            .with_exclude_patterns(vec!["*.tmp".to_string()])
+            .with_apparent_size(true);
+        let root = scan_directory_with_progress(options, None).unwrap();
+
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(root.size, 4096 + 5);
+        assert_eq!(root.file_count, 2);
+        assert!(root.children.iter().all(|c| c.name != "skip.tmp"));
+        // Directories come first
+        assert!(root.children[0].is_dir);
+    }
+}
@@ -8,6 +8,8 @@ use std::fs;
 use std::path::PathBuf;
 use uuid::Uuid;
 
+use data_x::ssh_config::SshConfig;
+
 /// SSH authentication method
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -48,6 +50,12 @@ pub struct SSHConnection {
     pub default_path: Option<String>,
     /// Connection timeout in seconds
     pub timeout_secs: u32,
+    /// Hosts to connect through (`ssh -J`)
+    #[serde(default)]
+    pub jump_hosts: Vec<String>,
+    /// Extra `ssh -o` options, e.g. `StrictHostKeyChecking=yes`
+    #[serde(default)]
+    pub ssh_options: Vec<String>,
     /// Creation timestamp
     pub created_at: i64,
     /// Last used timestamp
@@ -75,6 +83,47 @@ pub struct SSHConnectionInput {
     pub default_path: Option<String>,
     /// Timeout in seconds
     pub timeout_secs: Option<u32>,
+    /// Jump hosts
+    #[serde(default)]
+    pub jump_hosts: Vec<String>,
+    /// Extra ssh options
+    #[serde(default)]
+    pub ssh_options: Vec<String>,
+}
+
+/// A host alias from `~/.ssh/config` with what it resolves to
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SSHConfigHost {
+    /// Name after `Host`, usable as the connection host
+    pub alias: String,
+    pub host_name: Option<String>,
+    pub user: Option<String>,
+    pub port: Option<u16>,
+    pub identity_file: Option<String>,
+    pub proxy_jump: Option<String>,
+}
+
+/// Hosts defined in the user's `~/.ssh/config`
+pub fn get_config_hosts() -> Vec<SSHConfigHost> {
+    config_hosts(&SshConfig::load())
+}
+
+fn config_hosts(config: &SshConfig) -> Vec<SSHConfigHost> {
+    config
+        .aliases()
+        .into_iter()
+        .map(|alias| {
+            let resolved = config.resolve(&alias);
+            SSHConfigHost {
+                host_name: resolved.host_name().map(str::to_string),
+                user: resolved.user().map(str::to_string),
+                port: resolved.port(),
+                identity_file: resolved.identity_files().first().map(|path| path.to_string_lossy().into_owned()),
+                proxy_jump: resolved.proxy_jump().map(str::to_string),
+                alias,
+            }
+        })
+        .collect()
 }
 
 /// Get the connections file path
@@ -147,6 +196,8 @@ pub fn save_connection(input: SSHConnectionInput) -> Result<SSHConnection, Strin
         auth_method: input.auth_method.clone(),
         default_path: input.default_path,
         timeout_secs: input.timeout_secs.unwrap_or(30),
+        jump_hosts: input.jump_hosts,
+        ssh_options: input.ssh_options,
         created_at: now,
         last_used_at: None,
     };
@@ -190,6 +241,8 @@ pub fn update_connection(input: SSHConnectionInput) -> Result<SSHConnection, Str
         auth_method: input.auth_method.clone(),
         default_path: input.default_path,
         timeout_secs: input.timeout_secs.unwrap_or(30),
+        jump_hosts: input.jump_hosts,
+        ssh_options: input.ssh_options,
         created_at: old_connection.created_at,
         last_used_at: Some(now),
     };
@@ -253,4 +306,33 @@ mod tests {
         let json = serde_json::to_string(&password_auth).unwrap();
         assert!(json.contains("password"));
     }
+
+    #[test]
+    fn test_reads_connections_without_ssh_options() {
+        let json = r#"[{"id":"1","name":"db","host":"db","port":22,"username":"dba",
+            "auth_method":{"type":"agent"},"default_path":null,"timeout_secs":30,
+            "created_at":0,"last_used_at":null}]"#;
+        let connections: Vec<SSHConnection> = serde_json::from_str(json).unwrap();
+        assert!(connections[0].jump_hosts.is_empty());
+        assert!(connections[0].ssh_options.is_empty());
+    }
+
+    #[test]
+    fn test_config_hosts() {
+        let config = SshConfig::parse(
+            "Host db\n  HostName 10.0.0.5\n  Port 2222\n  ProxyJump bastion\nHost *\n  User ops\n",
+            std::path::Path::new("/nonexistent"),
+        );
+        assert_eq!(
+            config_hosts(&config),
+            [SSHConfigHost {
+                alias: "db".to_string(),
+                host_name: Some("10.0.0.5".to_string()),
+                user: Some("ops".to_string()),
+                port: Some(2222),
+                identity_file: None,
+                proxy_jump: Some("bastion".to_string()),
+            }]
+        );
+    }
 }
@@ -9,10 +9,12 @@ pub mod remote_scan;
 
 #[allow(unused_imports)]
 pub use connection_manager::{
-    delete_connection, get_all_connections, get_connection, save_connection, update_connection,
-    AuthMethod, SSHConnection, SSHConnectionInput,
+    delete_connection, get_all_connections, get_config_hosts, get_connection, save_connection,
+    update_connection, AuthMethod, SSHConfigHost, SSHConnection, SSHConnectionInput,
 };
 // Credentials are used internally by connection_manager and remote_scan
 #[allow(unused_imports)]
 pub use credentials::{delete_credential, get_credential, store_credential};
-pub use remote_scan::{scan_remote_directory, test_connection, SSHTestResult};
+pub use remote_scan::{
+    delete_remote_path, scan_remote_directory, ssh_shell_command_line, test_connection, SSHTestResult,
+};
@@ -4,10 +4,13 @@
 
 use serde::{Deserialize, Serialize};
 use std::io::{BufRead, BufReader};
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
 use std::process::{Command, Stdio};
 use tauri::{AppHandle, Emitter};
 
+use data_x::shell::{self, quote_path};
+use data_x::ssh_config::{sets_option, SshConfig};
+
 use super::connection_manager::{get_connection, mark_connection_used, AuthMethod, SSHConnection};
 use super::credentials::get_credential;
 use crate::types::{FileNode, ScanProgress, ScanResult};
@@ -25,14 +28,30 @@ pub struct SSHTestResult {
 
 /// Build SSH command arguments for a connection
 fn build_ssh_args(connection: &SSHConnection, _password: Option<&str>) -> Vec<String> {
+    build_ssh_args_with_config(connection, &SshConfig::load())
+}
+
+/// Build SSH command arguments, leaving settings made in `config` alone
+fn build_ssh_args_with_config(connection: &SSHConnection, config: &SshConfig) -> Vec<String> {
     let mut args = Vec::new();
+    let host_config = config.resolve(&connection.host);
 
     // Disable pseudo-terminal allocation (prevents hangs)
     args.push("-T".to_string());
 
-    // Disable strict host key checking for first connection
-    args.push("-o".to_string());
-    args.push("StrictHostKeyChecking=accept-new".to_string());
+    // The connection's own options come first: ssh keeps the first value
+    for option in &connection.ssh_options {
+        args.push("-o".to_string());
+        args.push(option.clone());
+    }
+
+    // Accept new host keys unless the user chose how to check them
+    if !sets_option(&connection.ssh_options, "StrictHostKeyChecking")
+        && host_config.get("StrictHostKeyChecking").is_none()
+    {
+        args.push("-o".to_string());
+        args.push("StrictHostKeyChecking=accept-new".to_string());
+    }
 
     // Set connection timeout
     args.push("-o".to_string());
@@ -53,7 +72,7 @@ fn build_ssh_args(connection: &SSHConnection, _password: Option<&str>) -> Vec<St
         args.push("PasswordAuthentication=no".to_string());
     }
 
-    // Port
+    // Port; 22 leaves a Port from ~/.ssh/config in effect
     if connection.port != 22 {
         args.push("-p".to_string());
         args.push(connection.port.to_string());
@@ -65,8 +84,20 @@ fn build_ssh_args(connection: &SSHConnection, _password: Option<&str>) -> Vec<St
         args.push(path.clone());
     }
 
-    // User@Host
-    args.push(format!("{}@{}", connection.username, connection.host));
+    // Jump hosts
+    if !connection.jump_hosts.is_empty() {
+        args.push("-J".to_string());
+        args.push(connection.jump_hosts.join(","));
+    }
+
+    // User@Host, after `--` so it can't read as an option. Without a
+    // username, the User from ~/.ssh/config applies.
+    args.push("--".to_string());
+    if connection.username.is_empty() {
+        args.push(connection.host.clone());
+    } else {
+        args.push(format!("{}@{}", connection.username, connection.host));
+    }
 
     args
 }
@@ -192,6 +223,71 @@ pub fn scan_remote_directory(
     Ok(result)
 }
 
+/// Whether `path` lies below the scanned `root`, and isn't the root itself
+fn is_below(root: &str, path: &str) -> bool {
+    let (root, path) = (Path::new(root), Path::new(path));
+    path != root && path.starts_with(root) && !path.components().any(|c| c == Component::ParentDir)
+}
+
+/// `path` quoted as a command operand; paths from the frontend are
+/// strings, so converting the quoted word back is exact
+fn quote_operand(path: &str) -> String {
+    quote_path(Path::new(path)).to_string_lossy().into_owned()
+}
+
+/// Remote command deleting `path`; without `-f` it fails for a path that
+/// isn't there
+fn delete_command(path: &str, is_dir: bool) -> String {
+    let command = if is_dir { "rm -r" } else { "rm" };
+    format!("{} {}", command, quote_operand(path))
+}
+
+/// Delete `path`, from a scan of `root`, on the connection's host
+///
+/// There is no trash over SSH: the file is gone for good. The frontend
+/// refuses *Move to Trash* for remote scans and offers this instead.
+pub fn delete_remote_path(connection_id: &str, root: &str, path: &str, is_dir: bool) -> Result<(), String> {
+    if !is_below(root, path) {
+        return Err(format!("{} is not below {}", path, root));
+    }
+
+    let connection =
+        get_connection(connection_id)?.ok_or_else(|| "Connection not found".to_string())?;
+    let use_sshpass = matches!(connection.auth_method, AuthMethod::Password);
+    let password = if use_sshpass {
+        get_credential(connection_id)?
+    } else {
+        None
+    };
+
+    let args = build_ssh_args(&connection, password.as_deref());
+    let output = build_ssh_command(use_sshpass, password.as_deref(), &args)
+        .arg(delete_command(path, is_dir))
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| format!("Failed to run ssh: {}", e))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let reason = stderr.lines().find(|line| !line.trim().is_empty()).unwrap_or("no output");
+        Err(format!("Delete on {} failed: {}", connection.host, reason))
+    }
+}
+
+/// Local shell command line opening a login shell in `dir` on the
+/// connection's host, for a terminal window
+pub fn ssh_shell_command_line(connection_id: &str, dir: &str) -> Result<String, String> {
+    let connection =
+        get_connection(connection_id)?.ok_or_else(|| "Connection not found".to_string())?;
+
+    let mut words = vec!["ssh".to_string(), "-t".to_string()];
+    words.extend(build_ssh_args(&connection, None));
+    words.push(format!("cd {}; exec \"${{SHELL:-/bin/sh}}\" -l", quote_operand(dir)));
+    Ok(shell::join(words))
+}
+
 /// Check if data-x is installed on the remote server
 fn check_remote_datax(use_sshpass: bool, password: Option<&str>, ssh_args: &[String]) -> Result<bool, String> {
     let mut cmd = build_ssh_command(use_sshpass, password, ssh_args);
@@ -210,7 +306,7 @@ fn check_remote_datax(use_sshpass: bool, password: Option<&str>, ssh_args: &[Str
 /// Scan using remote data-x installation (preferred, faster)
 fn scan_with_datax(use_sshpass: bool, password: Option<&str>, ssh_args: &[String], path: &str, _app: &AppHandle) -> Result<ScanResult, String> {
     let mut cmd = build_ssh_command(use_sshpass, password, ssh_args);
-    cmd.arg(format!("data-x --json '{}'", path));
+    cmd.arg(datax_command(path));
     cmd.stdout(Stdio::piped());
     cmd.stderr(Stdio::null());
 
@@ -242,30 +338,37 @@ fn scan_with_datax(use_sshpass: bool, password: Option<&str>, ssh_args: &[String
     })
 }
 
-/// Scan using find/stat commands (fallback)
-fn scan_with_find(use_sshpass: bool, password: Option<&str>, ssh_args: &[String], path: &str, app: &AppHandle) -> Result<ScanResult, String> {
-    let mut cmd = build_ssh_command(use_sshpass, password, ssh_args);
+/// Remote command for a data-x scan of `path`
+fn datax_command(path: &str) -> String {
+    format!("data-x --json {}", quote_operand(path))
+}
 
-    // Use a portable approach that works on both Linux and BSD/macOS:
-    // - Try GNU find -printf first (Linux)
-    // - Fall back to find with a shell script that works on BSD/macOS
-    // Format: path|type|size
-    // Limit depth to 4 levels for remote scans to avoid timeouts
-    // User can drill down to go deeper
-    let max_depth = 4;
-    let find_cmd = format!(
-        r#"if find '{}' -maxdepth 0 -printf '' 2>/dev/null; then
-            find '{}' -maxdepth {} -printf '%p|%y|%s\n' 2>/dev/null
+/// Remote command listing `path` as `path|type|size` lines
+///
+/// Uses a portable approach that works on both Linux and BSD/macOS:
+/// GNU find -printf first, then find with a shell loop over stat.
+fn find_command(path: &str, max_depth: usize) -> String {
+    let path = quote_operand(path);
+    format!(
+        r#"if find {path} -maxdepth 0 -printf '' 2>/dev/null; then
+            find {path} -maxdepth {max_depth} -printf '%p|%y|%s\n' 2>/dev/null
         else
-            find '{}' -maxdepth {} -exec sh -c 'for f; do
+            find {path} -maxdepth {max_depth} -exec sh -c 'for f; do
                 if [ -d "$f" ]; then t=d; else t=f; fi
                 s=$(stat -f%z "$f" 2>/dev/null || stat -c%s "$f" 2>/dev/null || echo 0)
                 printf "%s|%s|%s\n" "$f" "$t" "$s"
             done' _ {{}} +
-        fi"#,
-        path, path, max_depth, path, max_depth
-    );
-    cmd.arg(find_cmd);
+        fi"#
+    )
+}
+
+/// Scan using find/stat commands (fallback)
+fn scan_with_find(use_sshpass: bool, password: Option<&str>, ssh_args: &[String], path: &str, app: &AppHandle) -> Result<ScanResult, String> {
+    let mut cmd = build_ssh_command(use_sshpass, password, ssh_args);
+
+    // Limit depth to 4 levels for remote scans to avoid timeouts
+    // User can drill down to go deeper
+    cmd.arg(find_command(path, 4));
     cmd.stdout(Stdio::piped());
     cmd.stderr(Stdio::piped());
 
@@ -479,4 +582,167 @@ mod tests {
         assert_eq!(tree.path, "/home");
         assert!(tree.is_dir);
     }
+
+    /// Paths that break or escape naively quoted commands
+    const HOSTILE_NAMES: &[&str] = &[
+        "it's",
+        "'; touch pwned; '",
+        "$(touch pwned)",
+        "`touch pwned`",
+        "a\"b\\c;touch pwned",
+        "-rf",
+    ];
+
+    /// Scratch directory with an `ssh` stand-in that runs the remote
+    /// command locally, in `home` and with `bin` on the PATH
+    #[cfg(unix)]
+    fn fake_ssh(name: &str) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("data-x-tauri-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("home")).unwrap();
+        std::fs::create_dir_all(dir.join("bin")).unwrap();
+        std::fs::write(
+            dir.join("ssh"),
+            "#!/bin/sh\nfor command; do :; done\ncd \"${0%/ssh}/home\" && PATH=\"${0%/ssh}/bin:/usr/bin:/bin\" exec sh -c \"$command\"\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(dir.join("ssh"), std::fs::Permissions::from_mode(0o755)).unwrap();
+        dir
+    }
+
+    /// Run `command` through the stand-in with a hostile connection
+    #[cfg(unix)]
+    fn run_remote(dir: &Path, command: &str) -> String {
+        let connection = SSHConnection {
+            id: "test".to_string(),
+            name: "test".to_string(),
+            host: "files".to_string(),
+            port: 22,
+            username: "-oProxyCommand=touch pwned".to_string(),
+            auth_method: AuthMethod::Agent,
+            default_path: None,
+            timeout_secs: 5,
+            jump_hosts: Vec::new(),
+            ssh_options: Vec::new(),
+            created_at: 0,
+            last_used_at: None,
+        };
+        let output = Command::new(dir.join("ssh"))
+            .args(build_ssh_args(&connection, None))
+            .arg(command)
+            .output()
+            .unwrap();
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_find_command_quotes_hostile_paths() {
+        let dir = fake_ssh("find");
+        let home = dir.join("home");
+
+        for name in HOSTILE_NAMES {
+            std::fs::create_dir(home.join(name)).unwrap();
+            std::fs::write(home.join(name).join("file.txt"), b"12345").unwrap();
+
+            let absolute = home.join(name).to_string_lossy().into_owned();
+            let relative = data_x::shell::operand(Path::new(name)).to_string_lossy().into_owned();
+            for (path, listed) in [(absolute.clone(), absolute), (name.to_string(), relative)] {
+                let output = run_remote(&dir, &find_command(&path, 4));
+                let lines: Vec<&str> = output.lines().collect();
+                assert_eq!(lines.len(), 2, "{}: {:?}", path, lines);
+                assert!(lines[0].starts_with(&format!("{}|d|", listed)), "{}: {:?}", path, lines);
+                assert_eq!(lines[1], format!("{}/file.txt|f|5", listed), "{}", path);
+            }
+        }
+
+        assert!(!dir.join("home/pwned").exists() && !dir.join("pwned").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_datax_command_quotes_hostile_paths() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = fake_ssh("datax");
+        std::fs::write(dir.join("bin/data-x"), "#!/bin/sh\nprintf '%s\\n' \"$@\"\n").unwrap();
+        std::fs::set_permissions(dir.join("bin/data-x"), std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        for name in HOSTILE_NAMES {
+            let expected = if name.starts_with('-') { format!("./{}", name) } else { name.to_string() };
+            assert_eq!(run_remote(&dir, &datax_command(name)), format!("--json\n{}\n", expected));
+        }
+
+        assert!(!dir.join("home/pwned").exists() && !dir.join("pwned").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_ssh_args_respect_user_settings() {
+        let mut connection = SSHConnection {
+            id: "test".to_string(),
+            name: "db".to_string(),
+            host: "db".to_string(),
+            port: 22,
+            username: String::new(),
+            auth_method: AuthMethod::Agent,
+            default_path: None,
+            timeout_secs: 5,
+            jump_hosts: vec!["bastion".to_string(), "gw".to_string()],
+            ssh_options: Vec::new(),
+            created_at: 0,
+            last_used_at: None,
+        };
+        let config = SshConfig::parse("Host db\n  StrictHostKeyChecking yes\n", Path::new("/nonexistent"));
+
+        let args = build_ssh_args_with_config(&connection, &SshConfig::default());
+        assert!(args.contains(&"StrictHostKeyChecking=accept-new".to_string()));
+        assert_eq!(args[args.len() - 4..], ["-J", "bastion,gw", "--", "db"]);
+
+        // A host key policy from ~/.ssh/config or the connection is kept
+        let args = build_ssh_args_with_config(&connection, &config);
+        assert!(!args.iter().any(|arg| arg.starts_with("StrictHostKeyChecking")));
+
+        connection.ssh_options = vec!["StrictHostKeyChecking=no".to_string(), "ConnectTimeout=60".to_string()];
+        connection.username = "dba".to_string();
+        let args = build_ssh_args_with_config(&connection, &SshConfig::default());
+        assert_eq!(args[..5], ["-T", "-o", "StrictHostKeyChecking=no", "-o", "ConnectTimeout=60"]);
+        assert_eq!(args.iter().filter(|arg| arg.starts_with("StrictHostKeyChecking")).count(), 1);
+        assert_eq!(args.last().unwrap(), "dba@db");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_delete_command_quotes_hostile_paths() {
+        let dir = fake_ssh("delete");
+        let home = dir.join("home");
+
+        for name in HOSTILE_NAMES {
+            std::fs::create_dir(home.join(name)).unwrap();
+            std::fs::write(home.join(name).join("file.txt"), b"12345").unwrap();
+            std::fs::write(home.join(format!("{}.log", name)), b"12345").unwrap();
+
+            run_remote(&dir, &delete_command(&format!("{}.log", name), false));
+            run_remote(&dir, &delete_command(&home.join(name).to_string_lossy(), true));
+            assert!(!home.join(name).exists() && !home.join(format!("{}.log", name)).exists(), "{}", name);
+        }
+
+        assert_eq!(std::fs::read_dir(&home).unwrap().count(), 0);
+        assert!(!dir.join("pwned").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_is_below() {
+        assert!(is_below("/srv", "/srv/logs"));
+        assert!(is_below("/", "/srv"));
+        assert!(!is_below("/srv", "/srv"));
+        assert!(!is_below("/srv", "/srv/"));
+        assert!(!is_below("/srv", "/srvx/logs"));
+        assert!(!is_below("/srv", "/srv/../etc"));
+        assert!(!is_below("/srv", "/"));
+    }
 }
@@ -0,0 +1,238 @@
+//! File actions on the tree being browsed.
+//!
+//! A local scan's paths are on this machine. A remote scan's paths name
+//! files on its host, so its actions run there over ssh and never touch the
+//! local filesystem, where the same path may well exist.
+
+use std::ffi::OsString;
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use anyhow::{anyhow, bail, Result};
+
+use crate::remote::{first_line, SshTarget};
+use crate::shell::quote_path;
+
+/// Where file actions are carried out
+pub enum Actions {
+    /// On this machine
+    Local,
+    /// On the scanned host, over ssh
+    Remote(RemoteActions),
+}
+
+impl Actions {
+    pub fn is_remote(&self) -> bool {
+        matches!(self, Self::Remote(_))
+    }
+
+    /// Delete the file or directory at `path`.
+    pub fn delete(&self, path: &Path, is_dir: bool) -> Result<()> {
+        match self {
+            Self::Local if is_dir => Ok(std::fs::remove_dir_all(path)?),
+            Self::Local => Ok(std::fs::remove_file(path)?),
+            Self::Remote(remote) => remote.delete(path, is_dir),
+        }
+    }
+
+    /// Interactive shell in `dir`, to be run with the terminal handed over.
+    pub fn shell(&self, dir: &Path) -> Command {
+        match self {
+            Self::Local => {
+                let program = std::env::var_os("SHELL")
+                    .unwrap_or_else(|| if cfg!(windows) { "cmd".into() } else { "/bin/sh".into() });
+                let mut command = Command::new(program);
+                command.current_dir(dir);
+                command
+            }
+            Self::Remote(remote) => remote.shell(dir),
+        }
+    }
+
+    /// Show `dir` in the system file manager.
+    pub fn open_in_file_manager(&self, dir: &Path) -> Result<()> {
+        match self {
+            Self::Local => Ok(open::that(dir)?),
+            Self::Remote(remote) => bail!("{} is on {}, press S for a shell there", dir.display(), remote.target.host),
+        }
+    }
+
+    /// `path` as it would be typed to reach it from here.
+    pub fn display_path(&self, path: &Path) -> String {
+        match self {
+            Self::Local => path.to_string_lossy().into_owned(),
+            Self::Remote(remote) => SshTarget {
+                path: path.to_path_buf(),
+                ..remote.target.clone()
+            }
+            .display(),
+        }
+    }
+}
+
+/// Actions on a remote scan's host
+pub struct RemoteActions {
+    target: SshTarget,
+    ssh_program: PathBuf,
+}
+
+impl RemoteActions {
+    pub fn new(target: SshTarget) -> Self {
+        Self {
+            target,
+            ssh_program: PathBuf::from("ssh"),
+        }
+    }
+
+    /// Run `program` instead of `ssh`.
+    #[cfg(test)]
+    pub fn with_ssh_program(mut self, program: impl Into<PathBuf>) -> Self {
+        self.ssh_program = program.into();
+        self
+    }
+
+    /// The scanned host and root
+    pub fn target(&self) -> &SshTarget {
+        &self.target
+    }
+
+    /// Refuse paths outside the scanned root, and the root itself
+    fn check_path(&self, path: &Path) -> Result<()> {
+        let inside = path.starts_with(&self.target.path) && path != self.target.path;
+        if !inside || path.components().any(|c| c == Component::ParentDir) {
+            bail!("{} is not below {}", path.display(), self.target.path.display());
+        }
+        Ok(())
+    }
+
+    /// Delete `path` on the host.
+    ///
+    /// Without `-f`, `rm` fails for a path that isn't there, so the caller
+    /// never takes a node out of the tree whose file is still on the host.
+    /// There is no trash over SSH; the file is gone for good.
+    pub fn delete(&self, path: &Path, is_dir: bool) -> Result<()> {
+        self.check_path(path)?;
+        let mut command = OsString::from(if is_dir { "rm -r " } else { "rm " });
+        command.push(quote_path(path));
+        let output = Command::new(&self.ssh_program)
+            .args(self.target.ssh_args())
+            .arg(command)
+            .stdin(Stdio::null())
+            .output()?;
+        if output.status.success() {
+            return Ok(());
+        }
+
+        let errors = String::from_utf8_lossy(&output.stderr);
+        // ssh itself exits with 255 when it can't connect
+        Err(match output.status.code() {
+            Some(255) => anyhow!("ssh to {} failed: {}", self.target.host, first_line(&errors)),
+            _ => anyhow!("{}", first_line(&errors)),
+        })
+    }
+
+    /// Login shell on the host, started in `dir`.
+    ///
+    /// A directory that is gone by now leaves the shell in the home
+    /// directory, after `cd` says why.
+    pub fn shell(&self, dir: &Path) -> Command {
+        let mut remote_command = OsString::from("cd ");
+        remote_command.push(quote_path(dir));
+        remote_command.push("; exec \"${SHELL:-/bin/sh}\" -l");
+
+        let mut command = Command::new(&self.ssh_program);
+        command.arg("-t").args(self.target.ssh_args()).arg(remote_command);
+        command
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An `ssh` stand-in that runs the remote command with sh, locally
+    #[cfg(unix)]
+    fn local_ssh(dir: &Path) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+
+        let ssh = dir.join("ssh");
+        std::fs::write(&ssh, "#!/bin/sh\nfor command; do :; done\nexec sh -c \"$command\"\n").unwrap();
+        std::fs::set_permissions(&ssh, std::fs::Permissions::from_mode(0o755)).unwrap();
+        ssh
+    }
+
+    fn remote(root: &Path, ssh: &Path) -> Actions {
+        let target = SshTarget::parse(&format!("admin@files:{}", root.display())).unwrap();
+        Actions::Remote(RemoteActions::new(target).with_ssh_program(ssh))
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_remote_delete_runs_over_ssh() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = dir.path().join("srv");
+        std::fs::create_dir_all(root.join("-rf it's/deep")).unwrap();
+        std::fs::write(root.join("-rf it's/deep/a.log"), b"log").unwrap();
+        std::fs::write(root.join("b $(touch pwned).log"), b"log").unwrap();
+        let actions = remote(&root, &local_ssh(dir.path()));
+
+        actions.delete(&root.join("-rf it's"), true).unwrap();
+        actions.delete(&root.join("b $(touch pwned).log"), false).unwrap();
+        assert_eq!(std::fs::read_dir(&root).unwrap().count(), 0);
+        assert!(!dir.path().join("pwned").exists());
+
+        let error = actions.delete(&root.join("missing"), true).unwrap_err();
+        assert!(error.to_string().contains("missing"), "{}", error);
+        std::fs::create_dir(root.join("dir")).unwrap();
+        let error = actions.delete(&root.join("dir"), false).unwrap_err();
+        assert!(error.to_string().contains("dir"), "{}", error);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_remote_delete_of_non_utf8_name() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = dir.path().join("srv");
+        std::fs::create_dir(&root).unwrap();
+        let path = root.join(OsStr::from_bytes(b"caf\xe9.log"));
+        std::fs::write(&path, b"log").unwrap();
+        std::fs::write(root.join("caf\u{fffd}.log"), b"log").unwrap();
+        let actions = remote(&root, &local_ssh(dir.path()));
+
+        actions.delete(&path, false).unwrap();
+        assert!(!path.exists());
+        assert!(root.join("caf\u{fffd}.log").exists());
+        assert!(actions.delete(&path, false).is_err());
+    }
+
+    #[test]
+    fn test_remote_delete_stays_below_root() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = dir.path().join("srv");
+        std::fs::create_dir_all(root.join("keep")).unwrap();
+        std::fs::write(dir.path().join("outside"), b"").unwrap();
+
+        // Refused before ssh runs, so a missing program doesn't matter
+        let actions = remote(&root, &dir.path().join("no-ssh"));
+        for path in [root.clone(), dir.path().join("outside"), root.join("../outside"), PathBuf::from("/")] {
+            assert!(actions.delete(&path, true).is_err(), "{}", path.display());
+        }
+        assert!(actions.open_in_file_manager(&root).is_err());
+        assert!(root.join("keep").exists() && dir.path().join("outside").exists());
+    }
+
+    #[test]
+    fn test_remote_shell_command() {
+        let target = SshTarget::parse("admin@files:/srv").unwrap();
+        let actions = Actions::Remote(RemoteActions::new(target));
+
+        let command = actions.shell(Path::new("/srv/it's"));
+        let args: Vec<_> = command.get_args().map(|arg| arg.to_string_lossy().into_owned()).collect();
+        assert_eq!(command.get_program(), "ssh");
+        assert_eq!(args, ["-t", "--", "admin@files", r#"cd '/srv/it'\''s'; exec "${SHELL:-/bin/sh}" -l"#]);
+        assert_eq!(actions.display_path(Path::new("/srv/a b")), "admin@files:/srv/a b");
+    }
+}
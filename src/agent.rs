@@ -0,0 +1,420 @@
+//! `data-x agent --stdio`: file listings, scans, stats and deletes served
+//! over one ssh connection, so a remote session only fetches what it shows.
+//!
+//! Requests and replies are JSON objects, one per line, matched by `id`:
+//!
+//! ```text
+//! {"id":1,"op":"hello"}                  {"id":1,"hello":{"protocol":1,"version":"0.4.1"}}
+//! {"id":2,"op":"list","path":"/srv"}     {"id":2,"node":{"path":"/srv",...,"children":[...]}}
+//! {"id":3,"op":"delete","path":"/srv/x"} {"id":3,"deleted":"/srv/x"}
+//! {"id":4,"op":"stat","path":"/nope"}    {"id":4,"error":"/nope: No such file or directory (os error 2)"}
+//! ```
+//!
+//! Nodes are those of `--json`. A listing sizes each entry by its whole
+//! subtree, so the first request below a root scans all of it before it is
+//! answered: browsing this way saves transfer, not disk reads. The agent
+//! keeps the last tree it scanned, so listing anything below it needs no
+//! second trip over the disk; scan progress goes to stderr as
+//! `--progress-json` lines.
+//!
+//! The TUI only lists through [`AgentClient`]; deletes in a remote session
+//! run `rm` over ssh like those without the agent.
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, ChildStdout};
+use std::sync::mpsc;
+use std::thread;
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::export::{node_to_export, tree_to_export_node, write_progress_json, ExportNode};
+use crate::remote::first_line;
+use crate::scanner::{ScanOptions, Scanner};
+use crate::tree::{FileTree, NodeId};
+
+/// Version of the request/reply protocol, bumped on incompatible changes
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// What a client asks of the agent
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum Request {
+    /// Agree on the protocol; the first request of a session
+    Hello,
+    /// A directory with its entries, each sized by its whole subtree
+    List { path: PathBuf },
+    /// A directory with everything below it
+    Scan { path: PathBuf },
+    /// One entry, without its children
+    Stat { path: PathBuf },
+    /// Remove a file, or a directory with its contents
+    Delete { path: PathBuf },
+}
+
+/// The agent's answer to a request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Reply {
+    Hello { protocol: u32, version: String },
+    Node(ExportNode),
+    Deleted(PathBuf),
+    Error(String),
+}
+
+/// A request or reply with the id pairing them
+#[derive(Debug, Serialize, Deserialize)]
+struct Message<T> {
+    id: u64,
+    #[serde(flatten)]
+    body: T,
+}
+
+/// Serves requests against the local filesystem.
+pub struct Agent {
+    /// Options every scan runs with, whatever its root
+    options: ScanOptions,
+    /// Write scan progress to stderr
+    progress: bool,
+    /// Last tree scanned, with its nodes by path
+    tree: FileTree,
+    index: HashMap<PathBuf, NodeId>,
+}
+
+impl Agent {
+    pub fn new(options: ScanOptions) -> Self {
+        Self {
+            options,
+            progress: false,
+            tree: FileTree::new(),
+            index: HashMap::new(),
+        }
+    }
+
+    /// Report scan progress on stderr as JSON lines.
+    pub fn with_progress(mut self, progress: bool) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// Answer requests read from `reader` on `writer` until the client
+    /// hangs up.
+    pub fn serve(&mut self, reader: impl BufRead, mut writer: impl Write) -> io::Result<()> {
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let reply = match serde_json::from_str::<Message<Request>>(&line) {
+                Ok(request) => Message {
+                    id: request.id,
+                    body: self.handle(request.body),
+                },
+                // Without an id to answer to, 0 stands for "your last line"
+                Err(err) => Message {
+                    id: serde_json::from_str::<serde_json::Value>(&line)
+                        .ok()
+                        .and_then(|value| value["id"].as_u64())
+                        .unwrap_or(0),
+                    body: Reply::Error(format!("bad request: {}", err)),
+                },
+            };
+            serde_json::to_writer(&mut writer, &reply).map_err(io::Error::other)?;
+            writer.write_all(b"\n")?;
+            writer.flush()?;
+        }
+        Ok(())
+    }
+
+    fn handle(&mut self, request: Request) -> Reply {
+        let result = match request {
+            Request::Hello => {
+                return Reply::Hello {
+                    protocol: PROTOCOL_VERSION,
+                    version: env!("CARGO_PKG_VERSION").to_string(),
+                }
+            }
+            Request::List { path } => self.locate(&path).map(|id| Reply::Node(self.export(id, Some(1)))),
+            Request::Scan { path } => self.locate(&path).map(|id| Reply::Node(self.export(id, None))),
+            Request::Stat { path } => self.locate(&path).map(|id| Reply::Node(self.export(id, Some(0)))),
+            Request::Delete { path } => self.delete(&path).map(|_| Reply::Deleted(path)),
+        };
+        result.unwrap_or_else(|err| Reply::Error(format!("{:#}", err)))
+    }
+
+    /// Node of `path` in the last tree, scanning it (or, for a file, its
+    /// directory) in full when the tree doesn't reach it.
+    fn locate(&mut self, path: &Path) -> Result<NodeId> {
+        if let Some(&id) = self.index.get(path) {
+            return Ok(id);
+        }
+
+        let root = match std::fs::symlink_metadata(path).with_context(|| path.display().to_string())? {
+            metadata if metadata.is_dir() => path,
+            _ => path.parent().ok_or_else(|| anyhow!("{} has no directory", path.display()))?,
+        };
+        let options = ScanOptions {
+            root_path: root.to_path_buf(),
+            ..self.options.clone()
+        };
+        let (tx, rx) = mpsc::sync_channel(1000);
+        let writer = self.progress.then(io::stderr);
+        let progress = thread::spawn(move || write_progress_json(rx, writer));
+        let scanned = Scanner::new(options, tx).scan();
+        let _ = progress.join();
+
+        self.tree = scanned?;
+        self.index = match self.tree.root {
+            Some(root) => root
+                .descendants(&self.tree.arena)
+                .filter_map(|id| Some((self.tree.get_node(id)?.path.clone(), id)))
+                .collect(),
+            None => HashMap::new(),
+        };
+        self.index
+            .get(path)
+            .copied()
+            .ok_or_else(|| anyhow!("{} is excluded from scans", path.display()))
+    }
+
+    /// `id` as exported, with `depth` levels of children (all if None)
+    fn export(&self, id: NodeId, depth: Option<usize>) -> ExportNode {
+        match depth {
+            None => tree_to_export_node(&self.tree, id),
+            Some(depth) => {
+                let node = self.tree.get_node(id).expect("Node must exist");
+                let children = match depth {
+                    0 => Vec::new(),
+                    _ => self
+                        .tree
+                        .get_children(id)
+                        .into_iter()
+                        .map(|child| self.export(child, Some(depth - 1)))
+                        .collect(),
+                };
+                ExportNode {
+                    children,
+                    ..node_to_export(node)
+                }
+            }
+        }
+    }
+
+    /// Remove `path` from disk, and from the last tree if it's there.
+    fn delete(&mut self, path: &Path) -> Result<()> {
+        let removed = match std::fs::symlink_metadata(path) {
+            Ok(metadata) if metadata.is_dir() => std::fs::remove_dir_all(path),
+            Ok(_) => std::fs::remove_file(path),
+            Err(err) => Err(err),
+        };
+        removed.with_context(|| path.display().to_string())?;
+
+        if let Some(id) = self.index.get(path).copied() {
+            for removed in id.descendants(&self.tree.arena).collect::<Vec<_>>() {
+                if let Some(node) = self.tree.get_node(removed) {
+                    self.index.remove(&node.path);
+                }
+            }
+            if self.tree.root == Some(id) {
+                self.tree = FileTree::new();
+            } else {
+                self.tree.remove_node(id);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Something a browsing session has to tell the app
+#[derive(Debug)]
+pub enum AgentEvent {
+    /// The host's agent answered: the scan to come is only the top level,
+    /// the rest is listed as asked for
+    Connected,
+    /// Entries of a directory that was asked for
+    Listed { path: PathBuf, result: Result<ExportNode, String> },
+}
+
+/// Client end of an agent started over ssh.
+pub struct AgentClient {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    stdout: BufReader<ChildStdout>,
+    /// The agent's stderr less progress records, once it exits
+    errors: Option<thread::JoinHandle<String>>,
+    next_id: u64,
+}
+
+impl AgentClient {
+    /// Talk to the agent `child` runs, once it has answered a greeting.
+    ///
+    /// A host without data-x, or with one that predates the agent, fails
+    /// here with what it printed.
+    pub fn start(mut child: Child, errors: thread::JoinHandle<String>) -> Result<Self> {
+        let stdin = child.stdin.take().ok_or_else(|| anyhow!("Failed to capture stdin"))?;
+        let stdout = child.stdout.take().ok_or_else(|| anyhow!("Failed to capture stdout"))?;
+        let mut client = Self {
+            child,
+            stdin: Some(stdin),
+            stdout: BufReader::new(stdout),
+            errors: Some(errors),
+            next_id: 1,
+        };
+
+        match client.request(Request::Hello) {
+            Ok(Reply::Hello { protocol, .. }) if protocol == PROTOCOL_VERSION => Ok(client),
+            Ok(Reply::Hello { protocol, .. }) => {
+                Err(anyhow!("agent speaks protocol {}, not {}", protocol, PROTOCOL_VERSION))
+            }
+            Ok(reply) => Err(anyhow!("unexpected greeting {:?}", reply)),
+            Err(err) => {
+                let errors = client.close();
+                let reason = if errors.trim().is_empty() { err.to_string() } else { first_line(&errors).to_string() };
+                Err(anyhow!("no agent: {}", reason))
+            }
+        }
+    }
+
+    /// Send `request` and wait for its reply; an error reply becomes an error.
+    fn request(&mut self, request: Request) -> Result<Reply> {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let stdin = self.stdin.as_mut().ok_or_else(|| anyhow!("agent connection closed"))?;
+        serde_json::to_writer(&mut *stdin, &Message { id, body: request })?;
+        stdin.write_all(b"\n")?;
+        stdin.flush()?;
+
+        let mut line = String::new();
+        if self.stdout.read_line(&mut line)? == 0 {
+            return Err(anyhow!("agent connection closed"));
+        }
+        let reply: Message<Reply> = serde_json::from_str(&line)?;
+        if reply.id != id {
+            return Err(anyhow!("agent answered request {} instead of {}", reply.id, id));
+        }
+        match reply.body {
+            Reply::Error(error) => Err(anyhow!(error)),
+            body => Ok(body),
+        }
+    }
+
+    /// `path` with its entries, sized by their whole subtrees.
+    ///
+    /// The first listing below the scan root waits for the agent to scan
+    /// all of it.
+    pub fn list(&mut self, path: &Path) -> Result<ExportNode> {
+        match self.request(Request::List { path: path.to_path_buf() })? {
+            Reply::Node(node) => Ok(node),
+            reply => Err(anyhow!("unexpected reply {:?}", reply)),
+        }
+    }
+
+    /// Hang up and wait for the agent to go, returning its stderr.
+    fn close(&mut self) -> String {
+        // The agent exits at the end of its input; ssh may linger
+        drop(self.stdin.take());
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        self.errors.take().and_then(|errors| errors.join().ok()).unwrap_or_default()
+    }
+}
+
+impl Drop for AgentClient {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Replies of `agent` to `requests`
+    fn serve(agent: &mut Agent, requests: &[&str]) -> Vec<Message<Reply>> {
+        let mut output = Vec::new();
+        agent.serve(io::Cursor::new(requests.join("\n")), &mut output).unwrap();
+        output
+            .split(|&b| b == b'\n')
+            .filter(|line| !line.is_empty())
+            .map(|line| serde_json::from_slice(line).unwrap())
+            .collect()
+    }
+
+    fn node(reply: &Message<Reply>) -> &ExportNode {
+        match reply.body {
+            Reply::Node(ref node) => node,
+            ref other => panic!("expected a node, got {:?}", other),
+        }
+    }
+
+    fn names(node: &ExportNode) -> Vec<&str> {
+        let mut names: Vec<&str> = node.children.iter().map(|child| child.name.as_str()).collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_agent_requests() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        std::fs::create_dir_all(root.join("logs/old")).unwrap();
+        std::fs::write(root.join("logs/old/app.log"), vec![0u8; 3000]).unwrap();
+        std::fs::write(root.join("logs/app.log"), vec![0u8; 2000]).unwrap();
+        std::fs::write(root.join("notes.txt"), vec![0u8; 1000]).unwrap();
+
+        let request = |id: u64, op: &str, path: &Path| {
+            serde_json::json!({ "id": id, "op": op, "path": path }).to_string()
+        };
+        let requests = [
+            r#"{"id":1,"op":"hello"}"#.to_string(),
+            request(2, "list", &root),
+            request(3, "list", &root.join("logs")),
+            request(4, "stat", &root.join("logs/old/app.log")),
+            request(5, "scan", &root),
+            request(6, "delete", &root.join("logs/old")),
+            request(7, "scan", &root),
+            request(8, "stat", &root.join("missing")),
+            "not json".to_string(),
+        ];
+        let requests: Vec<&str> = requests.iter().map(String::as_str).collect();
+        let mut agent = Agent::new(ScanOptions::new(PathBuf::new()));
+        let replies = serve(&mut agent, &requests);
+
+        let ids: Vec<u64> = replies.iter().map(|reply| reply.id).collect();
+        assert_eq!(ids, [1, 2, 3, 4, 5, 6, 7, 8, 0]);
+        assert!(matches!(replies[0].body, Reply::Hello { protocol: PROTOCOL_VERSION, .. }));
+
+        // A listing is one level, sized by everything below it
+        let listed = node(&replies[1]);
+        assert_eq!(listed.size, 6000);
+        assert_eq!(names(listed), ["logs", "notes.txt"]);
+        assert!(listed.children.iter().all(|child| child.children.is_empty()));
+        let logs = listed.children.iter().find(|child| child.name == "logs").unwrap();
+        assert_eq!((logs.size, logs.file_count), (5000, 2));
+        assert_eq!(names(node(&replies[2])), ["app.log", "old"]);
+
+        let stat = node(&replies[3]);
+        assert_eq!((stat.name.as_str(), stat.size, stat.is_dir), ("app.log", 3000, false));
+
+        let scanned = node(&replies[4]);
+        let logs = scanned.children.iter().find(|child| child.name == "logs").unwrap();
+        let old = logs.children.iter().find(|child| child.name == "old").unwrap();
+        assert_eq!(names(old), ["app.log"]);
+
+        // Deleting takes the directory off disk and out of the agent's tree
+        assert_eq!(replies[5].body, Reply::Deleted(root.join("logs/old")));
+        assert!(!root.join("logs/old").exists());
+        assert_eq!(node(&replies[6]).size, 3000);
+
+        match (&replies[7].body, &replies[8].body) {
+            (Reply::Error(missing), Reply::Error(garbled)) => {
+                assert!(missing.contains("missing"), "{}", missing);
+                assert!(garbled.starts_with("bad request"), "{}", garbled);
+            }
+            other => panic!("expected errors, got {:?}", other),
+        }
+    }
+}
@@ -1,4 +1,5 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::io::Read;
 use std::path::PathBuf;
 use std::sync::mpsc::{self, Receiver, TryRecvError};
 use std::thread;
@@ -6,7 +7,13 @@ use std::time::{Duration, Instant};
 
 use indextree::NodeId;
 
+use crate::actions::{Actions, RemoteActions};
+use crate::agent::AgentEvent;
+use crate::cache::CacheReader;
+use crate::export::{export_to_node, ExportNode};
+use crate::history::{HistoryStore, SizeHistory};
 use crate::remote::{RemoteScanner, SshTarget};
+use crate::ssh_config::SshConfig;
 use crate::scanner::{get_disk_space, DiskSpaceInfo, ScanOptions, ScanProgress, Scanner};
 use crate::tree::FileTree;
 use crate::ui::{ColorScheme, Command, ConfirmAction, FileCategory, InputMode, SortBy, TreemapRect, ViewMode};
@@ -57,10 +64,18 @@ pub struct App {
     // Stats panel visibility toggle
     pub show_stats: bool,
 
+    // Directory sizes from earlier scans (sparklines and growth column)
+    pub history: Option<SizeHistory>,
+    history_receiver: Option<Receiver<SizeHistory>>,
+
     // Scan state
     pub scan_state: ScanState,
     pub scan_progress: ScanProgressInfo,
     progress_receiver: Option<Receiver<ScanProgress>>,
+    /// The rest of a cached tree is still being read
+    pub loading_cache: bool,
+    /// Unix time of the cached scan shown, None once a scan replaces it
+    pub cached_at: Option<u64>,
 
     // Animation
     pub spinner_frame: usize,
@@ -69,6 +84,30 @@ pub struct App {
     // Flags
     pub should_quit: bool,
     pub needs_refresh: bool,
+    /// Browsing a saved scan (--load): deleting and rescanning are disabled
+    pub read_only: bool,
+    /// Carries out deletes and shells, here or on the scanned host
+    pub actions: Actions,
+    /// Outcome of the last action, shown in the status bar until the next key
+    pub message: Option<String>,
+    /// Shell to run with the terminal handed over, taken by the main loop
+    pub pending_shell: Option<std::process::Command>,
+    /// Options of the scan shown, reused by rescans
+    pub scan_options: Option<ScanOptions>,
+    /// Nodes were excluded or included since the tree was loaded or saved
+    pub exclusions_changed: bool,
+    /// Directories of a remote tree are fetched from the host's agent as
+    /// they are expanded; not all of the tree has been seen yet
+    pub partial_tree: bool,
+    lazy: Option<LazyRemote>,
+}
+
+/// Connection to a remote agent listing directories on request
+struct LazyRemote {
+    requests: mpsc::Sender<PathBuf>,
+    events: Receiver<AgentEvent>,
+    /// Paths asked for, so each is listed once
+    requested: HashSet<PathBuf>,
 }
 
 #[derive(Clone, PartialEq)]
@@ -106,6 +145,10 @@ pub struct ScanProgressInfo {
     pub analyzing_start: Option<std::time::Instant>,
     pub items_per_second: f64,
     pub bytes_per_second: f64,
+    /// Remote scan output received over the connection
+    pub bytes_received: u64,
+    /// The same output once decompressed
+    pub bytes_decoded: u64,
 }
 
 impl ScanProgressInfo {
@@ -163,6 +206,14 @@ impl ScanProgressInfo {
             "-- files/s".to_string()
         }
     }
+
+    /// How much smaller remote output was on the wire, if it was compressed
+    pub fn compression_ratio(&self) -> Option<f64> {
+        if self.bytes_received == 0 || self.bytes_decoded <= self.bytes_received {
+            return None;
+        }
+        Some(self.bytes_decoded as f64 / self.bytes_received as f64)
+    }
 }
 
 impl App {
@@ -194,18 +245,192 @@ impl App {
             disk_info: None,
             file_type_stats: None,
             show_stats: false,
+            scan_options: None,
+            exclusions_changed: false,
+            partial_tree: false,
+            lazy: None,
+            history: None,
+            history_receiver: None,
             scan_state: ScanState::Idle,
             scan_progress: ScanProgressInfo::default(),
             progress_receiver: None,
+            loading_cache: false,
+            cached_at: None,
             spinner_frame: 0,
             last_spinner_update: Instant::now(),
             should_quit: false,
             needs_refresh: true,
+            read_only: false,
+            actions: Actions::Local,
+            message: None,
+            pending_shell: None,
+        }
+    }
+
+    /// Browse a previously exported tree instead of scanning.
+    ///
+    /// The tree may come from another machine, so nothing in it is touched
+    /// on disk: the app becomes read-only until a new scan is started.
+    pub fn load_scan(&mut self, tree: FileTree) {
+        let root = tree.root.and_then(|root| tree.get_node(root).map(|node| (root, node)));
+        if let Some((_, node)) = root {
+            self.root_path = node.path.clone();
+            self.scan_progress.total_files = node.file_count;
+            self.scan_progress.total_size = node.size;
+        }
+        let root = root.map(|(root, _)| root);
+
+        self.scan_progress.phase = ScanPhase::Complete;
+        self.scan_state = ScanState::Complete;
+        self.progress_receiver = None;
+        self.loading_cache = false;
+        self.cached_at = None;
+        self.read_only = true;
+        self.scan_options = None;
+        self.exclusions_changed = false;
+        self.partial_tree = false;
+        self.lazy = None;
+        self.disk_info = None;
+        self.history = None;
+        self.history_receiver = None;
+        self.file_type_stats = Some(crate::ui::AggregatedStats::from_tree(&tree));
+        self.tree = Some(tree);
+
+        self.treemap_root = None;
+        self.selected_index = 0;
+        self.scroll_offset = 0;
+        self.expanded_nodes.clear();
+        if let Some(root) = root {
+            self.expanded_nodes.insert(root);
+        }
+        self.needs_refresh = true;
+        self.refresh_visible_nodes();
+    }
+
+    /// Show a cached scan whose first block has been read.
+    ///
+    /// The top levels are browsable immediately while the remaining blocks
+    /// are read in a background thread; the full tree then replaces the
+    /// partial one without invalidating expanded or selected nodes.
+    ///
+    /// A remote tree keeps the host in the root shown, and its actions.
+    pub fn load_cached_tree<R: Read + Send + 'static>(&mut self, reader: CacheReader<R>) {
+        let header = reader.header().clone();
+        if !self.actions.is_remote() {
+            self.root_path = header.root_path.clone();
+        }
+        self.scan_options = header.options.clone();
+        self.disk_info = header.disk_info;
+        self.cached_at = Some(header.scan_time);
+        self.show_cached_tree(reader.snapshot(), reader.is_complete());
+        self.scan_progress.total_files = header.total_files;
+        self.scan_progress.total_size = header.total_size;
+
+        if reader.is_complete() {
+            return;
+        }
+
+        let (tx, rx) = mpsc::sync_channel(1);
+        self.progress_receiver = Some(rx);
+        self.loading_cache = true;
+
+        thread::spawn(move || {
+            // A damaged file just drops the sender; update() reports it
+            if let Ok(tree) = reader.finish() {
+                let _ = tx.send(ScanProgress::Completed {
+                    total_files: header.total_files,
+                    total_size: header.total_size,
+                    tree,
+                });
+            }
+        });
+    }
+
+    /// Show a complete tree taken from the cache, such as a subtree of a
+    /// cached ancestor scan made with `options` at `scan_time`.
+    pub fn load_cached_subtree(&mut self, tree: FileTree, options: &ScanOptions, scan_time: u64) {
+        if let Some(root) = tree.root.and_then(|root| tree.get_node(root)) {
+            if !self.actions.is_remote() {
+                self.root_path = root.path.clone();
+            }
+            self.scan_progress.total_files = root.file_count;
+            self.scan_progress.total_size = root.size;
+        }
+        self.scan_options = Some(options.clone());
+        self.disk_info = None;
+        self.cached_at = Some(scan_time);
+        self.show_cached_tree(tree, true);
+    }
+
+    /// Show a cached tree, scanned here or, for remote actions, on their
+    /// host. Nothing about a remote tree is looked up on this machine.
+    fn show_cached_tree(&mut self, tree: FileTree, complete: bool) {
+        let root = tree.root;
+        let remote = self.actions.is_remote();
+        self.scan_progress.phase = ScanPhase::Complete;
+        self.scan_state = ScanState::Complete;
+        self.exclusions_changed = false;
+        self.partial_tree = false;
+        self.lazy = None;
+        if complete {
+            self.file_type_stats = Some(crate::ui::AggregatedStats::from_tree(&tree));
+            if self.disk_info.is_none() && !remote {
+                self.disk_info = get_disk_space(&self.root_path);
+            }
         }
+        self.tree = Some(tree);
+        if let Some(root) = root {
+            self.expanded_nodes.insert(root);
+        }
+        self.needs_refresh = true;
+        self.refresh_visible_nodes();
+        if !remote {
+            self.load_history();
+        }
+    }
+
+    /// Scan `target` from now on: deletes and shells run on its host.
+    ///
+    /// Set before a cached tree of the host is loaded, or a scan started.
+    pub fn set_remote(&mut self, target: SshTarget) {
+        self.actions = Actions::Remote(RemoteActions::new(target));
+    }
+
+    /// Options of the scan shown, for the current root; None when they
+    /// aren't known, e.g. for an old cache file.
+    pub fn history_options(&self) -> Option<ScanOptions> {
+        self.scan_options.as_ref().map(|options| ScanOptions {
+            root_path: self.root_path.clone(),
+            ..options.clone()
+        })
+    }
+
+    /// Read the snapshots of the current root made with the same options
+    /// in a background thread; `history` is filled in by `update()` once
+    /// they are loaded.
+    pub fn load_history(&mut self) {
+        let Some(options) = self.history_options() else {
+            self.history = None;
+            self.history_receiver = None;
+            return;
+        };
+        let (tx, rx) = mpsc::sync_channel(1);
+        self.history_receiver = Some(rx);
+
+        thread::spawn(move || {
+            let _ = tx.send(HistoryStore::new().size_history(&options));
+        });
     }
 
     /// Start scanning in background thread
     pub fn start_scan(&mut self, options: ScanOptions) {
+        self.read_only = false;
+        self.actions = Actions::Local;
+        self.exclusions_changed = false;
+        self.partial_tree = false;
+        self.lazy = None;
+        self.scan_options = Some(options.clone());
+        self.loading_cache = false;
         self.scan_state = ScanState::Scanning;
         self.scan_progress = ScanProgressInfo::default();
 
@@ -223,21 +448,46 @@ impl App {
         });
 
         self.root_path = root_path;
+        self.load_history();
     }
 
     /// Start remote scanning via SSH in background thread
-    pub fn start_remote_scan(&mut self, target: SshTarget) {
+    ///
+    /// `options` (exclusions, depth, ...) default to those of a local scan.
+    /// With `data-x agent` on the host only the top level is fetched at
+    /// first, and each directory when it is expanded.
+    pub fn start_remote_scan(&mut self, target: SshTarget, options: Option<ScanOptions>) {
+        self.read_only = false;
+        self.set_remote(target.clone());
+        self.exclusions_changed = false;
+        self.partial_tree = false;
+        self.scan_options = options.clone();
+        self.loading_cache = false;
+        self.disk_info = None;
         self.scan_state = ScanState::Scanning;
         self.scan_progress = ScanProgressInfo::default();
         self.scan_progress.phase = ScanPhase::Analyzing;
         self.scan_progress.start_time = Some(Instant::now());
+        self.history = None;
+        self.history_receiver = None;
 
         let (tx, rx) = mpsc::sync_channel(1000);
         self.progress_receiver = Some(rx);
+        let (request_tx, request_rx) = mpsc::channel();
+        let (event_tx, event_rx) = mpsc::channel();
+        self.lazy = Some(LazyRemote {
+            requests: request_tx,
+            events: event_rx,
+            requested: HashSet::new(),
+        });
 
         thread::spawn(move || {
-            let scanner = RemoteScanner::new(target, tx);
-            let result = scanner.scan();
+            let mut scanner = RemoteScanner::new(target, tx);
+            if let Some(options) = options {
+                scanner = scanner.with_options(options);
+            }
+            // Lists directories until the session is replaced
+            let result = scanner.browse(request_rx, event_tx);
 
             // Result is sent via channel as Completed or Error
             drop(result);
@@ -252,6 +502,18 @@ impl App {
             self.last_spinner_update = Instant::now();
         }
 
+        if let Some(ref receiver) = self.history_receiver {
+            match receiver.try_recv() {
+                Ok(history) => {
+                    self.history = (!history.is_empty()).then_some(history);
+                    self.history_receiver = None;
+                    self.needs_refresh = true;
+                }
+                Err(TryRecvError::Empty) => {}
+                Err(TryRecvError::Disconnected) => self.history_receiver = None,
+            }
+        }
+
         // Collect progress messages first to avoid borrow issues
         let mut messages: Vec<ScanProgress> = Vec::new();
         let mut receiver_disconnected = false;
@@ -316,6 +578,10 @@ impl App {
                     // Handle incremental node discovery for streaming display
                     self.handle_node_discovered(node, parent_path);
                 }
+                ScanProgress::Transfer { received, decoded } => {
+                    self.scan_progress.bytes_received = received;
+                    self.scan_progress.bytes_decoded = decoded;
+                }
                 ScanProgress::Building { .. } => {
                     // Building phase - just update the phase indicator
                     self.scan_progress.phase = ScanPhase::Building;
@@ -328,13 +594,25 @@ impl App {
                     self.scan_progress.phase = ScanPhase::Complete;
                     self.scan_progress.total_files = total_files;
                     self.scan_progress.total_size = total_size;
-                    self.tree = Some(tree);
+                    let previous = self.tree.replace(tree);
+                    if !self.loading_cache {
+                        // A rescan: keep the view of the tree it replaces
+                        if let Some(previous) = previous {
+                            self.carry_over_view(&previous);
+                        }
+                        self.cached_at = None;
+                    }
                     self.scan_state = ScanState::Complete;
                     self.progress_receiver = None;
                     self.needs_refresh = true;
 
-                    // Get disk space info for the scanned path
-                    self.disk_info = get_disk_space(&self.root_path);
+                    // Get disk space info for the scanned path; a cached
+                    // scan keeps the usage recorded with it, and a remote
+                    // path isn't on this machine's disks
+                    if (!self.loading_cache || self.disk_info.is_none()) && !self.actions.is_remote() {
+                        self.disk_info = get_disk_space(&self.root_path);
+                    }
+                    self.loading_cache = false;
 
                     // Calculate file type statistics
                     if let Some(ref tree) = self.tree {
@@ -361,12 +639,75 @@ impl App {
             self.progress_receiver = None;
             if self.scan_state == ScanState::Scanning {
                 self.scan_state = ScanState::Error("Scanner disconnected".to_string());
+            } else if self.loading_cache {
+                self.loading_cache = false;
+                self.scan_state = ScanState::Error("Cache file damaged".to_string());
+            }
+        }
+
+        // Directories listed by a remote agent, after the progress carrying
+        // the tree they belong to
+        let mut events = Vec::new();
+        let mut agent_disconnected = false;
+        if let Some(ref lazy) = self.lazy {
+            loop {
+                match lazy.events.try_recv() {
+                    Ok(event) => events.push(event),
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => {
+                        agent_disconnected = true;
+                        break;
+                    }
+                }
+            }
+        }
+        for event in events {
+            match event {
+                AgentEvent::Connected => self.partial_tree = true,
+                AgentEvent::Listed { path, result: Ok(listing) } => self.add_listing(&path, &listing),
+                AgentEvent::Listed { path, result: Err(error) } => {
+                    self.message = Some(format!("Listing {} failed: {}", path.display(), error));
+                }
+            }
+        }
+        if agent_disconnected {
+            self.lazy = None;
+        }
+    }
+
+    /// Add the entries an agent listed under a directory that had none.
+    fn add_listing(&mut self, path: &PathBuf, listing: &ExportNode) {
+        let Some(ref mut tree) = self.tree else { return };
+        let Some(id) = tree.find_by_path(path) else { return };
+        if tree.get_children(id).is_empty() {
+            // Sizes were listed with the directory itself, so its own and
+            // its ancestors' stay as they are
+            for child in &listing.children {
+                tree.add_child(id, export_to_node(child));
+            }
+            self.file_type_stats = Some(crate::ui::AggregatedStats::from_tree(tree));
+            self.refresh_visible_nodes();
+        }
+    }
+
+    /// Ask the agent for expanded directories whose entries haven't been
+    /// fetched yet.
+    fn request_unlisted(&mut self) {
+        let (Some(lazy), Some(tree)) = (self.lazy.as_mut(), self.tree.as_ref()) else { return };
+        if !self.partial_tree {
+            return;
+        }
+        for &id in self.expanded_nodes.iter().chain(&self.treemap_root) {
+            let Some(node) = tree.get_node(id) else { continue };
+            if node.is_dir && id.children(&tree.arena).next().is_none() && lazy.requested.insert(node.path.clone()) {
+                let _ = lazy.requests.send(node.path.clone());
             }
         }
     }
 
     /// Handle a command from input
     pub fn handle_command(&mut self, cmd: Command) {
+        self.message = None;
         match cmd {
             Command::Quit => {
                 if matches!(self.input_mode, InputMode::Normal) {
@@ -471,7 +812,9 @@ impl App {
                 self.refresh_visible_nodes();
             }
             Command::Delete => {
-                self.input_mode = InputMode::Confirm(ConfirmAction::Delete);
+                if !self.read_only {
+                    self.input_mode = InputMode::Confirm(ConfirmAction::Delete);
+                }
             }
             Command::Confirm => {
                 if let InputMode::Confirm(ref action) = self.input_mode {
@@ -489,22 +832,20 @@ impl App {
             Command::Cancel => {
                 self.input_mode = InputMode::Normal;
             }
-            Command::Rescan => {
-                let options = ScanOptions {
-                    root_path: self.root_path.clone(),
-                    max_depth: None,
-                    exclude_patterns: vec![],
-                    cross_mount: true,
-                    apparent_size: false,
-                };
-                self.start_scan(options);
-            }
+            // A loaded scan has nothing on this machine to rescan
+            Command::Rescan if self.read_only => {}
+            Command::Rescan => self.start_background_refresh(),
             Command::CopyPath => {
                 self.copy_selected_path();
             }
             Command::OpenInFM => {
                 self.open_selected_in_fm();
             }
+            Command::OpenShell => {
+                if let Some(dir) = self.selected_dir() {
+                    self.pending_shell = Some(self.actions.shell(&dir));
+                }
+            }
             Command::Export => {
                 self.export_json();
             }
@@ -598,8 +939,8 @@ impl App {
                         self.visible_node_ids.clear();
 
                         // Update display path to show remote target
-                        self.root_path = PathBuf::from(target.display());
-                        self.start_remote_scan(target);
+                        self.root_path = PathBuf::from(target.describe(&SshConfig::load()));
+                        self.start_remote_scan(target, None);
                     }
                     // If invalid SSH path, stay in input mode
                 } else {
@@ -701,6 +1042,7 @@ impl App {
         }
 
         self.needs_refresh = false;
+        self.request_unlisted();
     }
 
     fn collect_visible_nodes(&mut self, tree: &FileTree, node_id: NodeId, _depth: usize) {
@@ -792,32 +1134,58 @@ impl App {
 
     /// Delete selected file/directory
     fn delete_selected(&mut self) {
-        if let Some(node_id) = self.get_selected_node_id() {
-            if let Some(ref tree) = self.tree {
-                if let Some(node) = tree.get_node(node_id) {
-                    let path = node.path.clone();
-                    let is_dir = node.is_dir;
+        if self.read_only {
+            return;
+        }
+        let Some(node_id) = self.get_selected_node_id() else { return };
+        let Some(node) = self.tree.as_ref().and_then(|tree| tree.get_node(node_id)) else { return };
+        let path = node.path.clone();
 
-                    let result = if is_dir {
-                        std::fs::remove_dir_all(&path)
-                    } else {
-                        std::fs::remove_file(&path)
-                    };
-
-                    match result {
-                        Ok(_) => {
-                            // Trigger rescan of parent
-                            self.needs_refresh = true;
-                            // For now, just rescan entirely
-                            self.handle_command(Command::Rescan);
-                        }
-                        Err(e) => {
-                            eprintln!("Delete failed: {}", e);
-                        }
-                    }
-                }
+        if let Err(e) = self.actions.delete(&path, node.is_dir) {
+            self.message = Some(format!("Delete failed: {}", e));
+            return;
+        }
+        self.message = Some(format!("Deleted {}", self.actions.display_path(&path)));
+
+        if self.actions.is_remote() {
+            // Rescanning the host takes a while and nothing else changed
+            self.remove_deleted_node(node_id);
+        } else {
+            // Trigger rescan of parent
+            self.needs_refresh = true;
+            // For now, just rescan entirely
+            self.handle_command(Command::Rescan);
+        }
+    }
+
+    /// Take a deleted node out of the tree and the view
+    fn remove_deleted_node(&mut self, node_id: NodeId) {
+        let Some(ref mut tree) = self.tree else { return };
+
+        // Leave a treemap zoomed into the deleted directory
+        if let Some(treemap_root) = self.treemap_root {
+            if treemap_root.ancestors(&tree.arena).any(|id| id == node_id) {
+                self.treemap_root = tree.get_parent(node_id);
             }
         }
+
+        tree.remove_node(node_id);
+        self.expanded_nodes.retain(|id| !id.is_removed(&tree.arena));
+        self.hovered_node = None;
+        self.scan_progress.total_files = tree.total_file_count();
+        self.scan_progress.total_size = tree.total_size();
+        self.file_type_stats = Some(crate::ui::AggregatedStats::from_tree(tree));
+        self.refresh_visible_nodes();
+    }
+
+    /// Selected directory, or the directory holding the selected file
+    fn selected_dir(&self) -> Option<PathBuf> {
+        let node = self.get_selected_node_id().and_then(|id| self.tree.as_ref()?.get_node(id))?;
+        if node.is_dir {
+            Some(node.path.clone())
+        } else {
+            Some(node.path.parent().unwrap_or(&node.path).to_path_buf())
+        }
     }
 
     /// Copy selected path to clipboard
@@ -825,7 +1193,7 @@ impl App {
         if let Some(node_id) = self.get_selected_node_id() {
             if let Some(ref tree) = self.tree {
                 if let Some(node) = tree.get_node(node_id) {
-                    let path_str = node.path.to_string_lossy().to_string();
+                    let path_str = self.actions.display_path(&node.path);
 
                     match arboard::Clipboard::new() {
                         Ok(mut clipboard) => {
@@ -843,20 +1211,10 @@ impl App {
     }
 
     /// Open selected item in file manager
-    fn open_selected_in_fm(&self) {
-        if let Some(node_id) = self.get_selected_node_id() {
-            if let Some(ref tree) = self.tree {
-                if let Some(node) = tree.get_node(node_id) {
-                    let path = if node.is_dir {
-                        &node.path
-                    } else {
-                        node.path.parent().unwrap_or(&node.path)
-                    };
-
-                    if let Err(e) = open::that(path) {
-                        eprintln!("Failed to open: {}", e);
-                    }
-                }
+    fn open_selected_in_fm(&mut self) {
+        if let Some(dir) = self.selected_dir() {
+            if let Err(e) = self.actions.open_in_file_manager(&dir) {
+                self.message = Some(format!("Failed to open: {}", e));
             }
         }
     }
@@ -866,7 +1224,7 @@ impl App {
         if let Some(ref tree) = self.tree {
             use crate::export::{export_json, ExportOptions};
 
-            let options = ExportOptions { top_n: None };
+            let options = ExportOptions::default();
             let mut stdout = std::io::stdout();
 
             if let Err(e) = export_json(tree, &options, &mut stdout) {
@@ -883,6 +1241,7 @@ impl App {
                     node.excluded = !node.excluded;
                 }
                 tree.calculate_sizes();
+                self.exclusions_changed = true;
                 self.needs_refresh = true;
             }
         }
@@ -1133,20 +1492,294 @@ impl App {
         }
     }
 
-    /// Start a background refresh to check for filesystem changes.
-    /// This runs a lightweight scan in the background while showing cached data.
-    #[allow(dead_code)]
-    pub fn start_background_refresh(&mut self, options: ScanOptions) {
-        // Mark that we're doing a background refresh (not a full rescan)
-        // For now, we just start a normal scan in the background
-        // The UI will continue showing cached data until the new scan completes
-
-        // Don't start background refresh if already scanning
-        if self.scan_state == ScanState::Scanning {
+    /// Rescan the root shown, on its host for a remote scan.
+    ///
+    /// The tree shown stays browsable until the new one replaces it. A
+    /// remote scan sends no nodes before it completes, so a cached remote
+    /// tree stays as it was in the meantime.
+    pub fn start_background_refresh(&mut self) {
+        if let Actions::Remote(ref remote) = self.actions {
+            let target = remote.target().clone();
+            self.start_remote_scan(target, self.scan_options.clone());
             return;
         }
 
-        // Start the scan - it will update the tree when complete
+        let options = match self.scan_options {
+            Some(ref options) => ScanOptions {
+                root_path: self.root_path.clone(),
+                ..options.clone()
+            },
+            None => ScanOptions {
+                root_path: self.root_path.clone(),
+                max_depth: None,
+                exclude_patterns: vec![],
+                cross_mount: true,
+                apparent_size: false,
+            },
+        };
         self.start_scan(options);
     }
+
+    /// Expand, select and zoom into the same paths in the current tree as
+    /// in `previous`, where they still exist.
+    fn carry_over_view(&mut self, previous: &FileTree) {
+        let path_of = |id: NodeId| previous.get_node(id).map(|node| node.path.clone());
+        let expanded: HashSet<PathBuf> = self.expanded_nodes.iter().filter_map(|&id| path_of(id)).collect();
+        let treemap_root = self.treemap_root.and_then(path_of);
+        let selected = self.get_selected_node_id().and_then(path_of);
+
+        let mut wanted = expanded.clone();
+        wanted.extend(treemap_root.iter().chain(&selected).cloned());
+
+        let Some(ref tree) = self.tree else { return };
+        let Some(root) = tree.root else { return };
+        let mut ids = HashMap::new();
+        for id in root.descendants(&tree.arena) {
+            let Some(node) = tree.get_node(id) else { continue };
+            if wanted.contains(&node.path) {
+                ids.insert(node.path.clone(), id);
+            }
+        }
+
+        self.expanded_nodes = expanded.iter().filter_map(|path| ids.get(path).copied()).collect();
+        self.treemap_root = treemap_root.and_then(|path| ids.get(&path).copied());
+        self.hovered_node = None;
+        self.refresh_visible_nodes();
+        if let Some(id) = selected.and_then(|path| ids.get(&path).copied()) {
+            if let Some(index) = self.visible_node_ids.iter().position(|&visible| visible == id) {
+                self.selected_index = index;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::FileNode;
+
+    #[test]
+    fn test_loaded_scan_is_read_only() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().join("scan");
+        std::fs::create_dir(&dir).unwrap();
+        let file_path = dir.join("keep.txt");
+        std::fs::write(&file_path, b"data").unwrap();
+
+        let mut tree = FileTree::with_root(dir.clone());
+        let root = tree.root.unwrap();
+        tree.add_child(root, FileNode::new(file_path.clone(), false).with_size(4));
+        tree.calculate_sizes();
+
+        let mut app = App::new(PathBuf::from("."), ColorScheme::default());
+        app.load_scan(tree);
+
+        assert!(app.read_only);
+        assert!(app.scan_state == ScanState::Complete);
+        assert_eq!(app.root_path, dir);
+        assert_eq!(app.scan_progress.total_size, 4);
+        assert_eq!(app.visible_node_ids.len(), 2);
+
+        // Select the file and try to delete it
+        app.handle_command(Command::MoveDown);
+        app.handle_command(Command::Delete);
+        assert!(matches!(app.input_mode, InputMode::Normal));
+        app.input_mode = InputMode::Confirm(ConfirmAction::Delete);
+        app.handle_command(Command::Confirm);
+        assert!(file_path.exists());
+
+        // Nothing to rescan either
+        app.handle_command(Command::Rescan);
+        assert!(app.scan_state == ScanState::Complete);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_remote_delete_stays_on_host() {
+        use std::os::unix::fs::PermissionsExt;
+
+        // The same paths exist here, and must survive
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().join("srv");
+        std::fs::create_dir(&dir).unwrap();
+        let big = dir.join("big.log");
+        std::fs::write(&big, b"data").unwrap();
+
+        let ssh = temp.path().join("ssh");
+        std::fs::write(&ssh, "#!/bin/sh\nfor command; do :; done\nprintf '%s' \"$command\" > \"$0.command\"\n").unwrap();
+        std::fs::set_permissions(&ssh, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let mut tree = FileTree::with_root(dir.clone());
+        let root = tree.root.unwrap();
+        tree.add_child(root, FileNode::new(big.clone(), false).with_size(5000));
+        tree.add_child(root, FileNode::new(dir.join("small.log"), false).with_size(10));
+        tree.calculate_sizes();
+
+        let target = SshTarget::parse(&format!("admin@files:{}", dir.display())).unwrap();
+        let mut app = App::new(PathBuf::from(target.display()), ColorScheme::default());
+        app.actions = Actions::Remote(RemoteActions::new(target).with_ssh_program(&ssh));
+        app.tree = Some(tree);
+        app.expanded_nodes.insert(root);
+        app.refresh_visible_nodes();
+
+        // Largest first: the root, then big.log
+        app.handle_command(Command::MoveDown);
+        app.handle_command(Command::Delete);
+        assert!(matches!(app.input_mode, InputMode::Confirm(ConfirmAction::Delete)));
+        app.handle_command(Command::Confirm);
+
+        let command = std::fs::read_to_string(temp.path().join("ssh.command")).unwrap();
+        assert_eq!(command, format!("rm '{}'", big.display()));
+        assert!(big.exists());
+        let tree = app.tree.as_ref().unwrap();
+        assert!(tree.find_by_path(&big).is_none());
+        assert_eq!((tree.total_size(), app.scan_progress.total_size), (10, 10));
+        assert_eq!(app.visible_node_ids.len(), 2);
+        assert!(app.message.as_deref().unwrap().starts_with("Deleted admin@files:"));
+
+        app.handle_command(Command::OpenInFM);
+        assert!(app.message.as_deref().unwrap().starts_with("Failed to open"));
+        app.handle_command(Command::OpenShell);
+        assert_eq!(app.pending_shell.take().unwrap().get_program(), ssh.as_os_str());
+    }
+
+    #[test]
+    fn test_remote_scan_has_no_local_disk_info() {
+        // The host's root also exists here
+        let temp = tempfile::tempdir().unwrap();
+        let target = SshTarget::parse(&format!("admin@files:{}", temp.path().display())).unwrap();
+        let mut app = App::new(temp.path().to_path_buf(), ColorScheme::default());
+        app.set_remote(target);
+
+        let (tx, rx) = mpsc::sync_channel(10);
+        app.progress_receiver = Some(rx);
+        app.scan_state = ScanState::Scanning;
+        let tree = FileTree::with_root(temp.path().to_path_buf());
+        tx.send(ScanProgress::Completed { total_files: 0, total_size: 0, tree }).unwrap();
+        app.update();
+
+        assert!(app.scan_state == ScanState::Complete);
+        assert_eq!(app.disk_info, None);
+    }
+
+    #[test]
+    fn test_remote_agent_lists_expanded_dirs() {
+        // The top level of /srv, as an agent lists it
+        let mut tree = create_srv_tree(&["logs", "data"]);
+        let root = tree.root.unwrap();
+        for dir in tree.get_children(root) {
+            let files: Vec<NodeId> = dir.children(&tree.arena).collect();
+            files.into_iter().for_each(|file| file.remove_subtree(&mut tree.arena));
+        }
+
+        let mut app = App::new(PathBuf::from("files:/srv"), ColorScheme::default());
+        let (tx, rx) = mpsc::sync_channel(10);
+        let (request_tx, request_rx) = mpsc::channel();
+        let (event_tx, event_rx) = mpsc::channel();
+        app.progress_receiver = Some(rx);
+        app.lazy = Some(LazyRemote { requests: request_tx, events: event_rx, requested: HashSet::new() });
+        app.scan_state = ScanState::Scanning;
+        event_tx.send(AgentEvent::Connected).unwrap();
+        tx.send(ScanProgress::Completed { total_files: 2, total_size: 3000, tree }).unwrap();
+        app.update();
+        assert!(app.partial_tree);
+        assert_eq!(app.visible_node_ids.len(), 3);
+        assert!(request_rx.try_recv().is_err());
+
+        // Expanding a directory asks for it once
+        let logs_path = PathBuf::from("/srv/logs");
+        let logs = app.tree.as_ref().unwrap().find_by_path(&logs_path).unwrap();
+        app.expanded_nodes.insert(logs);
+        app.refresh_visible_nodes();
+        app.refresh_visible_nodes();
+        assert_eq!(request_rx.try_iter().collect::<Vec<_>>(), std::slice::from_ref(&logs_path));
+
+        let listing = ExportNode {
+            children: vec![crate::export::node_to_export(
+                &FileNode::new(logs_path.join("data.bin"), false).with_size(1000),
+            )],
+            ..crate::export::node_to_export(&FileNode::new(logs_path.clone(), true).with_size(1000))
+        };
+        event_tx.send(AgentEvent::Listed { path: logs_path.clone(), result: Ok(listing) }).unwrap();
+        event_tx.send(AgentEvent::Listed { path: PathBuf::from("/srv/data"), result: Err("denied".into()) }).unwrap();
+        app.update();
+
+        let tree = app.tree.as_ref().unwrap();
+        assert_eq!(tree.get_children(logs).len(), 1);
+        assert_eq!(tree.total_size(), 3000);
+        assert_eq!(app.visible_node_ids.len(), 4);
+        assert_eq!(app.message.as_deref(), Some("Listing /srv/data failed: denied"));
+
+        // A session that ends leaves the tree as far as it was listed
+        drop(event_tx);
+        app.update();
+        assert!(app.lazy.is_none() && app.partial_tree);
+    }
+
+    fn create_srv_tree(dirs: &[&str]) -> FileTree {
+        let mut tree = FileTree::with_root(PathBuf::from("/srv"));
+        let root = tree.root.unwrap();
+        for (i, name) in dirs.iter().enumerate() {
+            let dir = tree.add_child(root, FileNode::new(PathBuf::from("/srv").join(name), true));
+            let file = PathBuf::from(format!("/srv/{}/data.bin", name));
+            tree.add_child(dir, FileNode::new(file, false).with_size(1000 * (i as u64 + 1)));
+        }
+        tree.calculate_sizes();
+        tree
+    }
+
+    #[test]
+    fn test_cached_remote_tree_and_refresh() {
+        use crate::cache::{header_for, CacheHeader, CacheManager};
+        use std::path::Path;
+
+        let temp = tempfile::tempdir().unwrap();
+        let cache = CacheManager::with_cache_dir(temp.path().to_path_buf()).with_host("admin@files:22");
+        let tree = create_srv_tree(&["logs", "data"]);
+        let header = header_for(&tree, Path::new("/srv")).with_scan(Some(&ScanOptions::new(PathBuf::from("/srv"))), None);
+        let scan_time = header.scan_time - 3 * 3600;
+        cache.save(&tree, &CacheHeader { scan_time, ..header }).unwrap();
+
+        let target = SshTarget::parse("admin@files:/srv").unwrap();
+        let mut app = App::new(PathBuf::from(target.display()), ColorScheme::default());
+        app.set_remote(target);
+        let mut reader = cache.open(Path::new("/srv")).unwrap();
+        reader.read_block().unwrap();
+        app.load_cached_tree(reader);
+
+        // Shown with its host, and nothing about it taken from this machine
+        assert!(app.actions.is_remote());
+        assert_eq!(app.root_path, PathBuf::from("admin@files:/srv"));
+        assert_eq!(app.cached_at, Some(scan_time));
+        assert_eq!(crate::cache::age_string(scan_time), "3h ago");
+        assert_eq!(app.disk_info, None);
+        assert!(app.history_receiver.is_none());
+
+        // Expand logs and select its file
+        assert_eq!(app.visible_node_ids.len(), 3);
+        let tree = app.tree.as_ref().unwrap();
+        let logs = tree.find_by_path(&PathBuf::from("/srv/logs")).unwrap();
+        let file = tree.find_by_path(&PathBuf::from("/srv/logs/data.bin")).unwrap();
+        app.expanded_nodes.insert(logs);
+        app.refresh_visible_nodes();
+        app.selected_index = app.visible_node_ids.iter().position(|&id| id == file).unwrap();
+        let selected = |app: &App| {
+            let tree = app.tree.as_ref().unwrap();
+            tree.get_node(app.get_selected_node_id().unwrap()).unwrap().path.clone()
+        };
+        assert_eq!(selected(&app), PathBuf::from("/srv/logs/data.bin"));
+
+        // The refreshed tree, with new node ids, keeps the view
+        let (tx, rx) = mpsc::sync_channel(1);
+        app.progress_receiver = Some(rx);
+        app.scan_state = ScanState::Scanning;
+        let refreshed = create_srv_tree(&["new", "data", "logs"]);
+        tx.send(ScanProgress::Completed { total_files: 3, total_size: 6000, tree: refreshed }).unwrap();
+        app.update();
+
+        assert_eq!(app.cached_at, None);
+        assert_eq!(app.scan_progress.total_size, 6000);
+        assert_eq!(selected(&app), PathBuf::from("/srv/logs/data.bin"));
+        assert_eq!(app.visible_node_ids.len(), 5);
+    }
 }
@@ -0,0 +1,849 @@
+//! Binary cache file format (version 4).
+//!
+//! ```text
+//! file    := magic version:u32le header block*
+//! header  := root_path:bytes scan_time:varint total_size:varint
+//!            total_files:varint node_count:varint options disk host
+//! options := 0 | 1 max_depth:varint (0 = none, else depth + 1)
+//!            patterns:varint (len:varint bytes)* options_flags:u8
+//! disk    := 0 | 1 total:varint used:varint free:varint
+//! host    := 0 | 1 location:bytes
+//! block   := compressed_len:u32le deflate(names nodes)
+//! names   := count:varint (len:varint bytes)*     appended to the name table
+//! nodes   := count:varint node*
+//! node    := parent:varint (0 = root, else index + 1) name:varint flags:u8
+//!            size:varint file_count:varint [mtime_secs:zigzag mtime_nsec:varint]
+//!            [symlink_target:bytes]
+//! ```
+//!
+//! Nodes are written breadth-first, so every parent precedes its children
+//! and the first block holds the top levels of the tree. Paths are rebuilt
+//! from the parent chain; each distinct file name is stored once.
+//!
+//! Version 2 files, which lack the scan options, disk info, exclusions and
+//! symlink targets, and version 3 files, which lack the host, are still
+//! read.
+
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::ffi::OsString;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+
+use crate::export::{from_epoch, to_epoch};
+use crate::scanner::{DiskSpaceInfo, ScanOptions};
+use crate::tree::{FileNode, FileTree, NodeId};
+
+/// Identifies a data-x cache file
+pub const MAGIC: &[u8; 8] = b"DATAXC\r\n";
+/// Version of the binary layout
+pub const VERSION: u32 = 4;
+/// Oldest version that can still be read
+const MIN_VERSION: u32 = 2;
+
+/// Depth of the nodes in the first block, loaded before the rest
+const TOP_LEVEL_DEPTH: usize = 2;
+/// Nodes per block after the first
+const BLOCK_NODES: usize = 1 << 16;
+
+const FLAG_DIR: u8 = 1;
+const FLAG_HIDDEN: u8 = 2;
+const FLAG_SYMLINK: u8 = 4;
+const FLAG_MODIFIED: u8 = 8;
+const FLAG_EXCLUDED: u8 = 16;
+const FLAG_SYMLINK_TARGET: u8 = 32;
+
+const OPTION_CROSS_MOUNT: u8 = 1;
+const OPTION_APPARENT_SIZE: u8 = 2;
+
+/// Scan-wide data stored ahead of the nodes
+#[derive(Debug, Clone, PartialEq)]
+pub struct CacheHeader {
+    pub root_path: PathBuf,
+    /// Unix time of the scan
+    pub scan_time: u64,
+    pub total_size: u64,
+    pub total_files: u64,
+    pub node_count: u64,
+    /// Options of the scan, None if unknown (version 2 files)
+    pub options: Option<ScanOptions>,
+    /// Usage of the scanned filesystem at scan time
+    pub disk_info: Option<DiskSpaceInfo>,
+    /// Where the tree was scanned, e.g. `admin@files:22`; None for this
+    /// machine
+    pub host: Option<String>,
+}
+
+impl CacheHeader {
+    /// Record the options and filesystem usage of the scan.
+    pub fn with_scan(mut self, options: Option<&ScanOptions>, disk_info: Option<DiskSpaceInfo>) -> Self {
+        self.options = options.map(|options| ScanOptions {
+            root_path: self.root_path.clone(),
+            ..options.clone()
+        });
+        self.disk_info = disk_info;
+        self
+    }
+}
+
+fn invalid_data(msg: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, msg.to_string())
+}
+
+fn write_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+fn read_varint(input: &mut &[u8]) -> Result<u64, std::io::Error> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = input.split_first().ok_or_else(|| invalid_data("truncated varint"))?;
+        *input = rest;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(invalid_data("varint too long"))
+}
+
+fn write_bytes(buf: &mut Vec<u8>, bytes: &[u8]) {
+    write_varint(buf, bytes.len() as u64);
+    buf.extend_from_slice(bytes);
+}
+
+fn read_bytes<'a>(input: &mut &'a [u8]) -> Result<&'a [u8], std::io::Error> {
+    let len = read_varint(input)? as usize;
+    if input.len() < len {
+        return Err(invalid_data("truncated string"));
+    }
+    let (bytes, rest) = input.split_at(len);
+    *input = rest;
+    Ok(bytes)
+}
+
+fn read_u8(input: &mut &[u8]) -> Result<u8, std::io::Error> {
+    let (&byte, rest) = input.split_first().ok_or_else(|| invalid_data("truncated node"))?;
+    *input = rest;
+    Ok(byte)
+}
+
+#[cfg(unix)]
+fn os_to_bytes(s: &std::ffi::OsStr) -> Vec<u8> {
+    use std::os::unix::ffi::OsStrExt;
+    s.as_bytes().to_vec()
+}
+
+#[cfg(not(unix))]
+fn os_to_bytes(s: &std::ffi::OsStr) -> Vec<u8> {
+    s.to_string_lossy().into_owned().into_bytes()
+}
+
+#[cfg(unix)]
+fn os_from_bytes(bytes: &[u8]) -> OsString {
+    use std::os::unix::ffi::OsStrExt;
+    std::ffi::OsStr::from_bytes(bytes).to_os_string()
+}
+
+#[cfg(not(unix))]
+fn os_from_bytes(bytes: &[u8]) -> OsString {
+    OsString::from(String::from_utf8_lossy(bytes).into_owned())
+}
+
+/// Write one compressed block
+fn write_block(writer: &mut impl Write, payload: &[u8]) -> Result<(), std::io::Error> {
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::fast());
+    encoder.write_all(payload)?;
+    let compressed = encoder.finish()?;
+
+    let len = u32::try_from(compressed.len()).map_err(|_| invalid_data("cache block too large"))?;
+    writer.write_all(&len.to_le_bytes())?;
+    writer.write_all(&compressed)
+}
+
+/// Write a FileTree in the binary cache format
+pub fn write_cache(tree: &FileTree, header: &CacheHeader, writer: &mut impl Write) -> Result<(), std::io::Error> {
+    writer.write_all(MAGIC)?;
+    writer.write_all(&VERSION.to_le_bytes())?;
+
+    let mut buf = Vec::new();
+    write_bytes(&mut buf, &os_to_bytes(header.root_path.as_os_str()));
+    write_varint(&mut buf, header.scan_time);
+    write_varint(&mut buf, header.total_size);
+    write_varint(&mut buf, header.total_files);
+    write_varint(&mut buf, header.node_count);
+    match header.options {
+        Some(ref options) => {
+            buf.push(1);
+            write_varint(&mut buf, options.max_depth.map(|depth| depth as u64 + 1).unwrap_or(0));
+            write_varint(&mut buf, options.exclude_patterns.len() as u64);
+            for pattern in &options.exclude_patterns {
+                write_bytes(&mut buf, pattern.as_bytes());
+            }
+            let mut flags = 0;
+            if options.cross_mount {
+                flags |= OPTION_CROSS_MOUNT;
+            }
+            if options.apparent_size {
+                flags |= OPTION_APPARENT_SIZE;
+            }
+            buf.push(flags);
+        }
+        None => buf.push(0),
+    }
+    match header.disk_info {
+        Some(disk) => {
+            buf.push(1);
+            write_varint(&mut buf, disk.total);
+            write_varint(&mut buf, disk.used);
+            write_varint(&mut buf, disk.free);
+        }
+        None => buf.push(0),
+    }
+    match header.host {
+        Some(ref host) => {
+            buf.push(1);
+            write_bytes(&mut buf, host.as_bytes());
+        }
+        None => buf.push(0),
+    }
+    writer.write_all(&buf)?;
+
+    let Some(root) = tree.root else { return Ok(()) };
+
+    // Breadth-first: (node, parent index, depth)
+    let mut order: Vec<(NodeId, Option<usize>, usize)> = Vec::new();
+    let mut queue = VecDeque::from([(root, None, 0usize)]);
+    while let Some((node_id, parent, depth)) = queue.pop_front() {
+        let index = order.len();
+        order.push((node_id, parent, depth));
+        for child_id in tree.get_children(node_id) {
+            queue.push_back((child_id, Some(index), depth + 1));
+        }
+    }
+
+    let mut name_ids: HashMap<Vec<u8>, u64> = HashMap::new();
+    let mut start = 0;
+    while start < order.len() {
+        let end = if start == 0 {
+            order.iter().position(|&(_, _, depth)| depth > TOP_LEVEL_DEPTH).unwrap_or(order.len())
+        } else {
+            (start + BLOCK_NODES).min(order.len())
+        };
+
+        let mut new_names: Vec<Vec<u8>> = Vec::new();
+        let mut nodes = Vec::new();
+        write_varint(&mut nodes, (end - start) as u64);
+
+        for &(node_id, parent, _) in &order[start..end] {
+            let node = tree.get_node(node_id).expect("Node must exist");
+
+            // The root keeps its full path in the header
+            let name = match parent {
+                None => Vec::new(),
+                Some(_) => node
+                    .path
+                    .file_name()
+                    .map(os_to_bytes)
+                    .unwrap_or_else(|| node.name.clone().into_bytes()),
+            };
+            let next_id = name_ids.len() as u64;
+            let name_id = *name_ids.entry(name.clone()).or_insert_with(|| {
+                new_names.push(name);
+                next_id
+            });
+
+            let mut flags = 0;
+            if node.is_dir {
+                flags |= FLAG_DIR;
+            }
+            if node.is_hidden {
+                flags |= FLAG_HIDDEN;
+            }
+            if node.is_symlink {
+                flags |= FLAG_SYMLINK;
+            }
+            if node.modified.is_some() {
+                flags |= FLAG_MODIFIED;
+            }
+            if node.excluded {
+                flags |= FLAG_EXCLUDED;
+            }
+            if node.symlink_target.is_some() {
+                flags |= FLAG_SYMLINK_TARGET;
+            }
+
+            write_varint(&mut nodes, parent.map(|p| p as u64 + 1).unwrap_or(0));
+            write_varint(&mut nodes, name_id);
+            nodes.push(flags);
+            write_varint(&mut nodes, node.size);
+            write_varint(&mut nodes, node.file_count);
+            if let Some(modified) = node.modified {
+                let (secs, nanos) = to_epoch(modified);
+                write_varint(&mut nodes, ((secs << 1) ^ (secs >> 63)) as u64);
+                write_varint(&mut nodes, u64::from(nanos));
+            }
+            if let Some(ref target) = node.symlink_target {
+                write_bytes(&mut nodes, &os_to_bytes(target.as_os_str()));
+            }
+        }
+
+        let mut payload = Vec::new();
+        write_varint(&mut payload, new_names.len() as u64);
+        for name in &new_names {
+            write_bytes(&mut payload, name);
+        }
+        payload.extend_from_slice(&nodes);
+        write_block(writer, &payload)?;
+
+        start = end;
+    }
+
+    Ok(())
+}
+
+/// Read the magic and version of a cache file
+///
+/// # Returns
+/// * `Ok(Some(version))` for a data-x binary cache
+/// * `Ok(None)` if the file is not one (e.g. a v1 JSON cache)
+pub fn read_version(input: &mut impl Read) -> Result<Option<u32>, std::io::Error> {
+    let mut magic = [0u8; 8];
+    if input.read_exact(&mut magic).is_err() || &magic != MAGIC {
+        return Ok(None);
+    }
+    let mut version = [0u8; 4];
+    input.read_exact(&mut version)?;
+    Ok(Some(u32::from_le_bytes(version)))
+}
+
+/// Reads a cache file block by block, building the tree as it goes.
+pub struct CacheReader<R> {
+    input: R,
+    header: CacheHeader,
+    names: Vec<OsString>,
+    ids: Vec<NodeId>,
+    tree: FileTree,
+}
+
+impl<R: Read> CacheReader<R> {
+    /// Read the file header. Fails on anything but a version 2 to 4 cache.
+    pub fn new(mut input: R) -> Result<Self, std::io::Error> {
+        let version = match read_version(&mut input)? {
+            Some(version) if (MIN_VERSION..=VERSION).contains(&version) => version,
+            Some(version) => return Err(invalid_data(&format!("unsupported cache version {}", version))),
+            None => return Err(invalid_data("not a data-x cache file")),
+        };
+
+        // The header is small but unframed; read it a varint at a time
+        let root_path = PathBuf::from(os_from_bytes(&Self::read_raw_bytes(&mut input)?));
+        let scan_time = Self::read_raw_varint(&mut input)?;
+        let total_size = Self::read_raw_varint(&mut input)?;
+        let total_files = Self::read_raw_varint(&mut input)?;
+        let node_count = Self::read_raw_varint(&mut input)?;
+        let options = if version >= 3 && Self::read_raw_u8(&mut input)? == 1 {
+            let max_depth = Self::read_raw_varint(&mut input)?.checked_sub(1).map(|depth| depth as usize);
+            let pattern_count = Self::read_raw_varint(&mut input)?;
+            let exclude_patterns = (0..pattern_count)
+                .map(|_| {
+                    let bytes = Self::read_raw_bytes(&mut input)?;
+                    String::from_utf8(bytes).map_err(|_| invalid_data("invalid exclude pattern"))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let flags = Self::read_raw_u8(&mut input)?;
+            Some(ScanOptions {
+                root_path: root_path.clone(),
+                max_depth,
+                exclude_patterns,
+                cross_mount: flags & OPTION_CROSS_MOUNT != 0,
+                apparent_size: flags & OPTION_APPARENT_SIZE != 0,
+            })
+        } else {
+            None
+        };
+        let disk_info = if version >= 3 && Self::read_raw_u8(&mut input)? == 1 {
+            Some(DiskSpaceInfo {
+                total: Self::read_raw_varint(&mut input)?,
+                used: Self::read_raw_varint(&mut input)?,
+                free: Self::read_raw_varint(&mut input)?,
+            })
+        } else {
+            None
+        };
+        let host = if version >= 4 && Self::read_raw_u8(&mut input)? == 1 {
+            let bytes = Self::read_raw_bytes(&mut input)?;
+            Some(String::from_utf8(bytes).map_err(|_| invalid_data("invalid host"))?)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            input,
+            header: CacheHeader {
+                root_path,
+                scan_time,
+                total_size,
+                total_files,
+                node_count,
+                options,
+                disk_info,
+                host,
+            },
+            names: Vec::new(),
+            ids: Vec::new(),
+            tree: FileTree::new(),
+        })
+    }
+
+    fn read_raw_u8(input: &mut R) -> Result<u8, std::io::Error> {
+        let mut byte = [0u8; 1];
+        input.read_exact(&mut byte)?;
+        Ok(byte[0])
+    }
+
+    fn read_raw_varint(input: &mut R) -> Result<u64, std::io::Error> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let mut byte = [0u8; 1];
+            input.read_exact(&mut byte)?;
+            value |= u64::from(byte[0] & 0x7f) << shift;
+            if byte[0] & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(invalid_data("varint too long"))
+    }
+
+    fn read_raw_bytes(input: &mut R) -> Result<Vec<u8>, std::io::Error> {
+        let len = Self::read_raw_varint(input)?;
+        let mut bytes = Vec::new();
+        input.by_ref().take(len).read_to_end(&mut bytes)?;
+        if bytes.len() as u64 != len {
+            return Err(invalid_data("truncated header"));
+        }
+        Ok(bytes)
+    }
+
+    pub fn header(&self) -> &CacheHeader {
+        &self.header
+    }
+
+    /// The tree read so far
+    pub fn tree(&self) -> &FileTree {
+        &self.tree
+    }
+
+    /// Copy of the tree read so far with the same node ids, which stay
+    /// valid in the tree returned by `finish`
+    pub fn snapshot(&self) -> FileTree {
+        FileTree {
+            arena: self.tree.arena.clone(),
+            root: self.tree.root,
+        }
+    }
+
+    /// Whether every node has been read
+    pub fn is_complete(&self) -> bool {
+        self.ids.len() as u64 >= self.header.node_count
+    }
+
+    /// Read the next block into the tree
+    ///
+    /// # Returns
+    /// * `Ok(true)` if a block was read
+    /// * `Ok(false)` once all nodes have been read
+    pub fn read_block(&mut self) -> Result<bool, std::io::Error> {
+        if self.is_complete() {
+            return Ok(false);
+        }
+
+        let mut len = [0u8; 4];
+        self.input.read_exact(&mut len)?;
+        let len = u64::from(u32::from_le_bytes(len));
+
+        let mut payload = Vec::new();
+        DeflateDecoder::new(self.input.by_ref().take(len)).read_to_end(&mut payload)?;
+        let mut input = payload.as_slice();
+
+        let name_count = read_varint(&mut input)?;
+        for _ in 0..name_count {
+            let name = read_bytes(&mut input)?;
+            self.names.push(os_from_bytes(name));
+        }
+
+        let node_count = read_varint(&mut input)?;
+        for _ in 0..node_count {
+            self.read_node(&mut input)?;
+        }
+
+        if self.ids.len() as u64 > self.header.node_count {
+            return Err(invalid_data("more nodes than the header announced"));
+        }
+        Ok(true)
+    }
+
+    fn read_node(&mut self, input: &mut &[u8]) -> Result<(), std::io::Error> {
+        let parent = read_varint(input)?;
+        let name_id = read_varint(input)? as usize;
+        let flags = read_u8(input)?;
+        let size = read_varint(input)?;
+        let file_count = read_varint(input)?;
+        let modified = if flags & FLAG_MODIFIED != 0 {
+            let zigzag = read_varint(input)?;
+            let secs = ((zigzag >> 1) as i64) ^ -((zigzag & 1) as i64);
+            let nanos = read_varint(input)?;
+            if nanos >= 1_000_000_000 {
+                return Err(invalid_data("invalid mtime"));
+            }
+            Some(from_epoch(secs, nanos as u32))
+        } else {
+            None
+        };
+
+        let symlink_target = if flags & FLAG_SYMLINK_TARGET != 0 {
+            Some(PathBuf::from(os_from_bytes(read_bytes(input)?)))
+        } else {
+            None
+        };
+
+        let name = self.names.get(name_id).ok_or_else(|| invalid_data("unknown name id"))?;
+        let is_dir = flags & FLAG_DIR != 0;
+
+        let node_id = if parent == 0 {
+            if !self.ids.is_empty() {
+                return Err(invalid_data("more than one root node"));
+            }
+            self.tree = FileTree::with_root(self.header.root_path.clone());
+            let root_id = self.tree.root.expect("Tree was created with a root");
+            if let Some(root) = self.tree.get_node_mut(root_id) {
+                *root = FileNode::new(self.header.root_path.clone(), is_dir);
+            }
+            root_id
+        } else {
+            let parent_id = *self
+                .ids
+                .get(parent as usize - 1)
+                .ok_or_else(|| invalid_data("node refers to an unknown parent"))?;
+            let parent_path = &self.tree.get_node(parent_id).expect("Node must exist").path;
+            let node = FileNode::new(parent_path.join(name), is_dir);
+            self.tree.add_child(parent_id, node)
+        };
+
+        let node = self.tree.get_node_mut(node_id).expect("Node must exist");
+        node.size = size;
+        node.file_count = file_count;
+        node.modified = modified;
+        node.is_hidden = flags & FLAG_HIDDEN != 0;
+        node.is_symlink = flags & FLAG_SYMLINK != 0;
+        node.symlink_target = symlink_target;
+        node.excluded = flags & FLAG_EXCLUDED != 0;
+
+        self.ids.push(node_id);
+        Ok(())
+    }
+
+    /// Read all remaining blocks and return the complete tree
+    pub fn finish(mut self) -> Result<FileTree, std::io::Error> {
+        while self.read_block()? {}
+        Ok(self.tree)
+    }
+}
+
+/// Header for a tree about to be cached
+pub fn header_for(tree: &FileTree, root_path: &Path) -> CacheHeader {
+    let root = tree.root.and_then(|r| tree.get_node(r));
+    CacheHeader {
+        root_path: root_path.to_path_buf(),
+        scan_time: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs(),
+        total_size: root.map(|n| n.size).unwrap_or(0),
+        total_files: root.map(|n| n.file_count).unwrap_or(0),
+        // Removed nodes stay in the arena, so count the live ones
+        node_count: tree.root.map(|r| r.descendants(&tree.arena).count()).unwrap_or(0) as u64,
+        options: None,
+        disk_info: None,
+        host: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use std::time::Duration;
+
+    fn create_test_tree() -> FileTree {
+        let mut tree = FileTree::with_root(PathBuf::from("/data"));
+        let root = tree.root.unwrap();
+
+        let a = tree.add_child(root, FileNode::new(PathBuf::from("/data/a"), true));
+        let b = tree.add_child(a, FileNode::new(PathBuf::from("/data/a/b"), true));
+        let c = tree.add_child(b, FileNode::new(PathBuf::from("/data/a/b/c"), true));
+        tree.add_child(
+            c,
+            FileNode::new(PathBuf::from("/data/a/b/c/deep.TXT"), false)
+                .with_size(10)
+                .with_modified(UNIX_EPOCH + Duration::new(1_700_000_000, 5)),
+        );
+        tree.add_child(
+            root,
+            FileNode::new(PathBuf::from("/data/.hidden"), false)
+                .with_size(3)
+                .with_modified(UNIX_EPOCH - Duration::new(10, 250)),
+        );
+        // The same name in two places is stored once
+        let excluded = tree.add_child(a, FileNode::new(PathBuf::from("/data/a/.hidden"), false).with_size(4));
+        tree.get_node_mut(excluded).unwrap().excluded = true;
+        tree.add_child(root, FileNode::new(PathBuf::from("/data/link"), false).with_symlink(PathBuf::from("a")));
+
+        tree.calculate_sizes();
+        tree
+    }
+
+    fn encode(tree: &FileTree) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        write_cache(tree, &header_for(tree, Path::new("/data")), &mut buffer).unwrap();
+        buffer
+    }
+
+    /// Every node with all its fields and its depth, in tree order
+    fn summary(tree: &FileTree) -> Vec<String> {
+        let Some(root) = tree.root else { return Vec::new() };
+        root.descendants(&tree.arena)
+            .map(|id| format!("{} {:?}", id.ancestors(&tree.arena).count(), tree.get_node(id).unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn test_varint_round_trip() {
+        for value in [0, 1, 127, 128, 300, u64::from(u32::MAX), u64::MAX] {
+            let mut buf = Vec::new();
+            write_varint(&mut buf, value);
+            let mut input = buf.as_slice();
+            assert_eq!(read_varint(&mut input).unwrap(), value);
+            assert!(input.is_empty());
+        }
+        assert!(read_varint(&mut [0x80u8].as_slice()).is_err());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let tree = create_test_tree();
+        let bytes = encode(&tree);
+
+        let reader = CacheReader::new(bytes.as_slice()).unwrap();
+        assert_eq!(reader.header().node_count, 8);
+        assert_eq!(reader.header().total_size, 13);
+
+        let loaded = reader.finish().unwrap();
+        assert_eq!(summary(&loaded), summary(&tree));
+    }
+
+    #[test]
+    fn test_header_round_trip() {
+        let tree = create_test_tree();
+        let options = ScanOptions::new(PathBuf::from("/elsewhere"))
+            .with_max_depth(Some(4))
+            .with_exclude_patterns(vec!["node_modules".to_string(), "*.tmp".to_string()]);
+        let disk = DiskSpaceInfo { total: 1 << 40, used: 1 << 39, free: 12345 };
+        let header = CacheHeader {
+            host: Some("admin@files:2222".to_string()),
+            ..header_for(&tree, Path::new("/data")).with_scan(Some(&options), Some(disk))
+        };
+        assert_eq!(header.options.as_ref().unwrap().root_path, PathBuf::from("/data"));
+
+        let mut bytes = Vec::new();
+        write_cache(&tree, &header, &mut bytes).unwrap();
+        let reader = CacheReader::new(bytes.as_slice()).unwrap();
+        assert_eq!(reader.header(), &header);
+        assert_eq!(summary(&reader.finish().unwrap()), summary(&tree));
+    }
+
+    #[test]
+    fn test_reads_version_2() {
+        let mut tree = create_test_tree();
+        let link = tree.find_by_path(&PathBuf::from("/data/link")).unwrap();
+        tree.get_node_mut(link).unwrap().symlink_target = None;
+        let excluded = tree.find_by_path(&PathBuf::from("/data/a/.hidden")).unwrap();
+        tree.get_node_mut(excluded).unwrap().excluded = false;
+
+        // Version 2 had the same nodes, minus the header's options, disk
+        // and host bytes
+        let header = header_for(&tree, Path::new("/data"));
+        let bytes = with_version(&tree, &header, 2, 3);
+        let reader = CacheReader::new(bytes.as_slice()).unwrap();
+        assert_eq!(reader.header(), &header);
+        assert_eq!(summary(&reader.finish().unwrap()), summary(&tree));
+    }
+
+    #[test]
+    fn test_reads_version_3() {
+        let tree = create_test_tree();
+        let header = header_for(&tree, Path::new("/data"));
+        let bytes = with_version(&tree, &header, 3, 1);
+        let reader = CacheReader::new(bytes.as_slice()).unwrap();
+        assert_eq!(reader.header(), &header);
+        assert_eq!(summary(&reader.finish().unwrap()), summary(&tree));
+    }
+
+    /// `tree` encoded as an older `version`, which ends its header
+    /// `dropped` fields before the current one. Those must all be empty.
+    fn with_version(tree: &FileTree, header: &CacheHeader, version: u32, dropped: usize) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        write_cache(tree, header, &mut bytes).unwrap();
+        let mut fields = Vec::new();
+        write_bytes(&mut fields, &os_to_bytes(header.root_path.as_os_str()));
+        for value in [header.scan_time, header.total_size, header.total_files, header.node_count] {
+            write_varint(&mut fields, value);
+        }
+        let end = MAGIC.len() + 4 + fields.len() + 3;
+        assert_eq!(&bytes[end - 3..end], [0, 0, 0]);
+        bytes.drain(end - dropped..end);
+        bytes[8..12].copy_from_slice(&version.to_le_bytes());
+        bytes
+    }
+
+    #[test]
+    fn test_top_levels_load_first() {
+        let tree = create_test_tree();
+        let bytes = encode(&tree);
+
+        let mut reader = CacheReader::new(bytes.as_slice()).unwrap();
+        assert!(reader.read_block().unwrap());
+
+        // Depth 0-2 only: /data, a, .hidden, link, a/b, a/.hidden
+        assert_eq!(reader.tree().node_count(), 6);
+        assert!(!reader.is_complete());
+        let b = reader.tree().find_by_path(&PathBuf::from("/data/a/b")).unwrap();
+        assert_eq!(reader.tree().get_node(b).unwrap().size, 10);
+        let snapshot = reader.snapshot();
+
+        assert!(reader.read_block().unwrap());
+        assert!(reader.is_complete());
+        assert!(!reader.read_block().unwrap());
+        assert_eq!(reader.tree().node_count(), 8);
+
+        // Ids handed out for the top levels still point at the same nodes
+        let full = reader.finish().unwrap();
+        assert_eq!(full.get_node(b).unwrap().path, snapshot.get_node(b).unwrap().path);
+        assert_eq!(full.get_children(b).len(), 1);
+    }
+
+    #[test]
+    fn test_smaller_than_json() {
+        let mut tree = FileTree::with_root(PathBuf::from("/big"));
+        let root = tree.root.unwrap();
+        for i in 0..200 {
+            let dir = tree.add_child(root, FileNode::new(PathBuf::from(format!("/big/pkg{}", i)), true));
+            for name in ["index.js", "package.json", "README.md"] {
+                tree.add_child(dir, FileNode::new(PathBuf::from(format!("/big/pkg{}/{}", i, name)), false).with_size(100));
+            }
+        }
+        tree.calculate_sizes();
+
+        let binary = encode(&tree);
+        let json = serde_json::to_vec(&crate::export::tree_to_export_node(&tree, root)).unwrap();
+        assert!(binary.len() * 10 < json.len(), "{} vs {}", binary.len(), json.len());
+    }
+
+    #[test]
+    fn test_rejects_bad_input() {
+        assert!(CacheReader::new(&b"{\"version\": 1}"[..]).is_err());
+
+        let mut bytes = encode(&create_test_tree());
+        bytes[8] = 9;
+        let err = CacheReader::new(bytes.as_slice()).err().unwrap();
+        assert!(err.to_string().contains("version 9"));
+
+        let bytes = encode(&create_test_tree());
+        let truncated = &bytes[..bytes.len() - 5];
+        assert!(CacheReader::new(truncated).unwrap().finish().is_err());
+    }
+
+    #[test]
+    fn test_empty_tree() {
+        let bytes = encode(&FileTree::new());
+        let loaded = CacheReader::new(bytes.as_slice()).unwrap().finish().unwrap();
+        assert!(loaded.root.is_none());
+    }
+
+    /// One generated node: parent pick among earlier directories, name and state
+    type NodeSpec = (usize, String, bool, u64, Option<(i64, u32)>, Option<String>, bool);
+
+    fn node_spec() -> impl Strategy<Value = NodeSpec> {
+        (
+            any::<usize>(),
+            "\\.?[a-zA-Z0-9_ é-][a-zA-Z0-9._ é-]{0,10}",
+            any::<bool>(),
+            any::<u64>(),
+            proptest::option::of((-4_000_000_000i64..4_000_000_000, 0u32..1_000_000_000)),
+            proptest::option::of("[a-z./]{1,12}"),
+            any::<bool>(),
+        )
+    }
+
+    /// Build a tree the way the scanner does: nodes under a root, sizes summed
+    fn build_tree(root_mtime: Option<(i64, u32)>, specs: &[NodeSpec]) -> FileTree {
+        let mut tree = FileTree::with_root(PathBuf::from("/root dir"));
+        let root = tree.root.unwrap();
+        if let Some((secs, nanos)) = root_mtime {
+            tree.get_node_mut(root).unwrap().modified = Some(from_epoch(secs, nanos));
+        }
+
+        let mut dirs = vec![root];
+        for (pick, name, is_dir, size, mtime, target, excluded) in specs {
+            let parent = dirs[pick % dirs.len()];
+            let path = tree.get_node(parent).unwrap().path.join(name);
+            let mut node = FileNode::new(path, *is_dir);
+            if !is_dir {
+                node = node.with_size(size >> 8);
+                if let Some(target) = target {
+                    node = node.with_symlink(PathBuf::from(target));
+                }
+            }
+            if let Some((secs, nanos)) = mtime {
+                node = node.with_modified(from_epoch(*secs, *nanos));
+            }
+            node.excluded = *excluded;
+            let id = tree.add_child(parent, node);
+            if *is_dir {
+                dirs.push(id);
+            }
+        }
+        tree.calculate_sizes();
+        tree
+    }
+
+    proptest! {
+        #[test]
+        fn prop_round_trip_is_lossless(
+            root_mtime in proptest::option::of((-4_000_000_000i64..4_000_000_000, 0u32..1_000_000_000)),
+            specs in proptest::collection::vec(node_spec(), 0..80),
+        ) {
+            let tree = build_tree(root_mtime, &specs);
+            let header = header_for(&tree, Path::new("/root dir"));
+            let mut bytes = Vec::new();
+            write_cache(&tree, &header, &mut bytes).unwrap();
+
+            let mut reader = CacheReader::new(bytes.as_slice()).unwrap();
+            prop_assert_eq!(reader.header(), &header);
+            // The partial tree of the first block is a prefix of the full one
+            reader.read_block().unwrap();
+            let first = summary(&reader.snapshot());
+            let loaded = reader.finish().unwrap();
+            prop_assert_eq!(summary(&loaded), summary(&tree));
+            prop_assert!(first.iter().all(|line| summary(&tree).contains(line)));
+        }
+    }
+}
@@ -0,0 +1,119 @@
+//! Version 1 cache files (JSON), read only to migrate them.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{Duration, UNIX_EPOCH};
+
+use serde::Deserialize;
+
+use crate::tree::{FileNode, FileTree};
+
+const LEGACY_VERSION: u32 = 1;
+
+/// Cache entry for a scanned directory.
+#[derive(Debug, Clone, Deserialize)]
+struct CacheEntry {
+    /// Root path that was scanned
+    root_path: PathBuf,
+    /// When the scan was performed
+    scan_time: u64,
+    /// Version of the cache format
+    version: u32,
+    /// Serialized file tree nodes
+    nodes: Vec<CachedNode>,
+    /// Root node index
+    root_index: Option<usize>,
+}
+
+/// Node as stored in a v1 cache. Unused fields are ignored.
+#[derive(Debug, Clone, Deserialize)]
+struct CachedNode {
+    path: PathBuf,
+    size: u64,
+    is_dir: bool,
+    is_hidden: bool,
+    is_symlink: bool,
+    file_count: u64,
+    modified: Option<u64>,
+    extension: Option<String>,
+    children_indices: Vec<usize>,
+}
+
+/// Convert a CacheEntry back to a FileTree.
+fn cache_entry_to_tree(entry: &CacheEntry) -> Option<FileTree> {
+    if entry.nodes.is_empty() {
+        return None;
+    }
+
+    let root_index = entry.root_index?;
+    let root_cached = entry.nodes.get(root_index)?;
+
+    let mut tree = FileTree::with_root(root_cached.path.clone());
+    let tree_root_id = tree.root?;
+
+    // Update root node
+    if let Some(root_node) = tree.get_node_mut(tree_root_id) {
+        root_node.size = root_cached.size;
+        root_node.file_count = root_cached.file_count;
+        root_node.is_hidden = root_cached.is_hidden;
+        if let Some(mod_secs) = root_cached.modified {
+            root_node.modified = Some(UNIX_EPOCH + Duration::from_secs(mod_secs));
+        }
+    }
+
+    // Build a map of cache index to tree NodeId
+    let mut index_to_node_id: HashMap<usize, indextree::NodeId> = HashMap::new();
+    index_to_node_id.insert(root_index, tree_root_id);
+
+    // Track visited indices to prevent cycles from corrupted cache
+    let mut visited: std::collections::HashSet<usize> = std::collections::HashSet::new();
+    visited.insert(root_index);
+
+    // Add all other nodes (breadth-first to ensure parents exist)
+    let mut queue: Vec<(usize, indextree::NodeId)> = vec![(root_index, tree_root_id)];
+
+    while let Some((cache_idx, parent_node_id)) = queue.pop() {
+        let cached = &entry.nodes[cache_idx];
+
+        for &child_idx in &cached.children_indices {
+            // Skip if already visited (prevents cycles)
+            if visited.contains(&child_idx) {
+                continue;
+            }
+            visited.insert(child_idx);
+
+            if let Some(child_cached) = entry.nodes.get(child_idx) {
+                let mut child_node = FileNode::new(child_cached.path.clone(), child_cached.is_dir);
+                child_node.size = child_cached.size;
+                child_node.file_count = child_cached.file_count;
+                child_node.is_hidden = child_cached.is_hidden;
+                child_node.is_symlink = child_cached.is_symlink;
+                child_node.extension = child_cached.extension.clone();
+
+                if let Some(mod_secs) = child_cached.modified {
+                    child_node.modified = Some(UNIX_EPOCH + Duration::from_secs(mod_secs));
+                }
+
+                let child_node_id = tree.add_child(parent_node_id, child_node);
+                index_to_node_id.insert(child_idx, child_node_id);
+                queue.push((child_idx, child_node_id));
+            }
+        }
+    }
+
+    Some(tree)
+}
+
+/// Parse a v1 cache file
+///
+/// # Returns
+/// * `Some((root_path, tree, scan_time))` for a valid v1 cache
+/// * `None` otherwise
+pub fn read_legacy(content: &str) -> Option<(PathBuf, FileTree, u64)> {
+    let entry: CacheEntry = serde_json::from_str(content).ok()?;
+    if entry.version != LEGACY_VERSION {
+        return None;
+    }
+    let tree = cache_entry_to_tree(&entry)?;
+    Some((entry.root_path, tree, entry.scan_time))
+}
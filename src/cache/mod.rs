@@ -0,0 +1,989 @@
+//! Cache system for Data-X scan results.
+//!
+//! Saves scan results to disk and provides background update checking
+//! to avoid full rescans on startup.
+
+mod binary;
+mod legacy;
+
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::scanner::ScanOptions;
+use crate::tree::FileTree;
+
+pub use binary::{header_for, CacheHeader, CacheReader};
+
+/// Cache manager for loading and saving scan results.
+pub struct CacheManager {
+    cache_dir: PathBuf,
+    max_age: Duration,
+    /// Host whose scans are loaded and saved, None for this machine
+    host: Option<String>,
+}
+
+/// Caches older than this are ignored unless configured otherwise.
+pub const DEFAULT_MAX_AGE: Duration = Duration::from_secs(86400 * 7); // 7 days
+
+const CACHE_EXTENSION: &str = "dxc";
+const LEGACY_EXTENSION: &str = "json";
+
+impl CacheManager {
+    /// Create a new cache manager.
+    pub fn new() -> Self {
+        Self::with_cache_dir(Self::get_cache_dir())
+    }
+
+    /// Create a cache manager that keeps its files in `cache_dir`.
+    pub fn with_cache_dir(cache_dir: PathBuf) -> Self {
+        Self {
+            cache_dir,
+            max_age: DEFAULT_MAX_AGE,
+            host: None,
+        }
+    }
+
+    /// Treat caches older than `max_age` as stale.
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    /// Load and save scans of `host` (e.g. `admin@files:22`) rather than
+    /// of this machine. The same path on two hosts gets two caches.
+    pub fn with_host(mut self, host: impl Into<String>) -> Self {
+        self.host = Some(host.into());
+        self
+    }
+
+    /// The same cache, for scans of `host` (None for this machine).
+    pub fn for_host(&self, host: Option<String>) -> Self {
+        Self {
+            cache_dir: self.cache_dir.clone(),
+            max_age: self.max_age,
+            host,
+        }
+    }
+
+    pub fn cache_dir(&self) -> &Path {
+        &self.cache_dir
+    }
+
+    /// Get the cache directory path.
+    fn get_cache_dir() -> PathBuf {
+        // Try XDG cache dir first, then fallback to ~/.cache
+        if let Ok(xdg_cache) = std::env::var("XDG_CACHE_HOME") {
+            PathBuf::from(xdg_cache).join("data-x")
+        } else if let Ok(home) = std::env::var("HOME") {
+            PathBuf::from(home).join(".cache").join("data-x")
+        } else {
+            PathBuf::from("/tmp").join("data-x-cache")
+        }
+    }
+
+    /// Generate a cache filename for a given path.
+    ///
+    /// Slot 0 is `scan_<hash>.dxc`; paths whose hash collides with one
+    /// already cached get `scan_<hash>-1.dxc`, `scan_<hash>-2.dxc`, ...
+    fn cache_filename(&self, path: &Path, slot: usize) -> PathBuf {
+        let hash = self.key_hash(path);
+        match slot {
+            0 => self.cache_dir.join(format!("scan_{:016x}.{}", hash, CACHE_EXTENSION)),
+            _ => self.cache_dir.join(format!("scan_{:016x}-{}.{}", hash, slot, CACHE_EXTENSION)),
+        }
+    }
+
+    /// Filename the same path had in the v1 JSON format.
+    fn legacy_filename(&self, path: &Path) -> PathBuf {
+        self.cache_dir
+            .join(format!("scan_{:016x}.{}", Self::path_hash(path), LEGACY_EXTENSION))
+    }
+
+    /// Hash of a path, used to name its cache files.
+    pub fn path_hash(path: &Path) -> u64 {
+        Self::simple_hash(&path.to_string_lossy())
+    }
+
+    /// Hash naming the cache files of `path` on this manager's host.
+    ///
+    /// Local paths hash as before hosts were cached, so their files keep
+    /// their names.
+    fn key_hash(&self, path: &Path) -> u64 {
+        match self.host {
+            Some(ref host) => Self::simple_hash(&format!("{}:{}", host, path.to_string_lossy())),
+            None => Self::path_hash(path),
+        }
+    }
+
+    /// Simple hash function for path strings.
+    fn simple_hash(s: &str) -> u64 {
+        let mut hash: u64 = 5381;
+        for byte in s.bytes() {
+            hash = hash.wrapping_mul(33).wrapping_add(byte as u64);
+        }
+        hash
+    }
+
+    /// Cache files in the cache directory with the given extension.
+    fn cache_files(&self, extension: &str) -> Vec<PathBuf> {
+        let Ok(entries) = fs::read_dir(&self.cache_dir) else { return Vec::new() };
+        entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| {
+                path.extension().is_some_and(|ext| ext == extension)
+                    && path
+                        .file_name()
+                        .is_some_and(|name| name.to_string_lossy().starts_with("scan_"))
+            })
+            .collect()
+    }
+
+    /// Open the cache file that belongs to `path`.
+    ///
+    /// The hash only narrows down the candidates; the host and root path
+    /// stored in each file decide, so two paths never share a cache.
+    fn find(&self, path: &Path) -> Option<(PathBuf, CacheReader<BufReader<File>>)> {
+        let prefix = format!("scan_{:016x}", self.key_hash(path));
+
+        // Slots can have gaps after removals, so look at every candidate
+        self.cache_files(CACHE_EXTENSION)
+            .into_iter()
+            .filter(|file| {
+                file.file_stem()
+                    .map(|stem| stem.to_string_lossy())
+                    .is_some_and(|stem| stem == prefix || stem.starts_with(&format!("{}-", prefix)))
+            })
+            .find_map(|file| {
+                let reader = open_cache_file(&file)?;
+                let header = reader.header();
+                (header.root_path == path && header.host == self.host).then_some((file, reader))
+            })
+    }
+
+    /// File a new cache for `path` goes to: its current one, or the first
+    /// free slot.
+    fn file_for(&self, path: &Path) -> PathBuf {
+        if let Some((file, _)) = self.find(path) {
+            return file;
+        }
+        (0..)
+            .map(|slot| self.cache_filename(path, slot))
+            .find(|file| !file.exists())
+            .expect("Some slot is free")
+    }
+
+    fn is_fresh(&self, header: &CacheHeader) -> bool {
+        age_secs(header.scan_time) < self.max_age.as_secs()
+    }
+
+    /// Whether a fresh cache matches a scan with `options`
+    fn is_usable(&self, header: &CacheHeader, options: &ScanOptions) -> bool {
+        let depth = options
+            .root_path
+            .strip_prefix(&header.root_path)
+            .map(|relative| relative.components().count())
+            .unwrap_or(0);
+        self.is_fresh(header) && options_cover(header.options.as_ref(), options, depth)
+    }
+
+    /// Convert a v1 JSON cache file to the binary format.
+    ///
+    /// The JSON file is removed either way: after a successful conversion,
+    /// or because it can't be read and would never be used again.
+    fn migrate_file(&self, legacy_file: &Path) {
+        // v1 only cached local scans
+        if self.host.is_some() {
+            return;
+        }
+        let Ok(content) = fs::read_to_string(legacy_file) else { return };
+
+        if let Some((root_path, tree, scan_time)) = legacy::read_legacy(&content) {
+            if self.find(&root_path).is_none() {
+                let header = CacheHeader {
+                    scan_time,
+                    ..header_for(&tree, &root_path)
+                };
+                if self.write(&tree, &header).is_err() {
+                    return;
+                }
+            }
+        }
+        let _ = fs::remove_file(legacy_file);
+    }
+
+    /// Check if a valid cache exists for a scan with `options`.
+    ///
+    /// Only the path's own cache counts; see `load` for ancestors. A cache
+    /// made with other scan options (exclusions, depth, ...) isn't valid.
+    pub fn has_valid_cache(&self, options: &ScanOptions) -> bool {
+        self.open(&options.root_path)
+            .is_some_and(|reader| self.is_usable(reader.header(), options))
+    }
+
+    /// Open a cached scan result for reading.
+    ///
+    /// Only the header is read; the tree is loaded block by block through
+    /// the returned reader, top levels first.
+    pub fn open(&self, path: &Path) -> Option<CacheReader<BufReader<File>>> {
+        self.migrate_file(&self.legacy_filename(path));
+        self.find(path).map(|(_, reader)| reader)
+    }
+
+    /// Load a fresh cached scan matching `options`.
+    ///
+    /// Without a cache of its own, the subtree is extracted from the nearest
+    /// ancestor directory with a fresh cache, e.g. `/home/alice` from a scan
+    /// of `/home`.
+    ///
+    /// The header returned is that of the cache the tree was taken from.
+    pub fn load(&self, options: &ScanOptions) -> Option<(FileTree, CacheHeader)> {
+        let path = options.root_path.as_path();
+        if let Some(reader) = self.open(path).filter(|reader| self.is_usable(reader.header(), options)) {
+            let header = reader.header().clone();
+            return reader.finish().ok().map(|tree| (tree, header));
+        }
+
+        let path_buf = path.to_path_buf();
+        for ancestor in path.ancestors().skip(1) {
+            let Some(reader) = self.open(ancestor).filter(|reader| self.is_usable(reader.header(), options)) else {
+                continue;
+            };
+            let header = reader.header().clone();
+            let Ok(tree) = reader.finish() else { continue };
+
+            // The ancestor scan may not have gone this deep
+            let Some(node_id) = tree.find_by_path(&path_buf) else { continue };
+            let node = tree.get_node(node_id)?;
+            if !node.is_dir || node.excluded {
+                return None;
+            }
+            return tree.subtree(node_id).map(|tree| (tree, header));
+        }
+        None
+    }
+
+    /// Save a scan result to cache.
+    ///
+    /// The scan also replaces the same directory in cached scans of its
+    /// ancestors that were made with the same options. Those keep their
+    /// scan time and disk info, since the rest of their tree is no fresher
+    /// than before.
+    pub fn save(&self, tree: &FileTree, header: &CacheHeader) -> Result<(), std::io::Error> {
+        self.write(tree, header)?;
+
+        let root_path = header.root_path.as_path();
+        let Some(ref options) = header.options else { return Ok(()) };
+        for (depth, ancestor) in root_path.ancestors().enumerate().skip(1) {
+            let Some(reader) = self.open(ancestor) else { continue };
+            if !options_cover(reader.header().options.as_ref(), options, depth) {
+                continue;
+            }
+            let ancestor_header = reader.header().clone();
+            let Ok(mut ancestor_tree) = reader.finish() else { continue };
+            let Some(node_id) = ancestor_tree.find_by_path(&header.root_path) else { continue };
+
+            ancestor_tree.replace_subtree(node_id, tree);
+            let header = CacheHeader {
+                scan_time: ancestor_header.scan_time,
+                options: ancestor_header.options,
+                disk_info: ancestor_header.disk_info,
+                ..header_for(&ancestor_tree, ancestor)
+            };
+            self.write(&ancestor_tree, &header)?;
+        }
+        Ok(())
+    }
+
+    /// Write the cache file for `header.root_path` on this manager's host.
+    fn write(&self, tree: &FileTree, header: &CacheHeader) -> Result<(), std::io::Error> {
+        // Ensure cache directory exists
+        fs::create_dir_all(&self.cache_dir)?;
+
+        let header = CacheHeader {
+            host: self.host.clone(),
+            ..header.clone()
+        };
+        write_cache_file(&self.file_for(&header.root_path), tree, &header)
+    }
+
+    /// Clear all cached scans, including unfinished writes.
+    ///
+    /// Only scan caches are removed: the scan history kept in the same
+    /// directory stays.
+    pub fn clear_all(&self) -> Result<(), std::io::Error> {
+        for extension in [CACHE_EXTENSION, LEGACY_EXTENSION, "tmp"] {
+            for file in self.cache_files(extension) {
+                fs::remove_file(file)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Clear cache for a specific path.
+    ///
+    /// # Returns
+    /// * `Ok(true)` if a cache file was removed
+    /// * `Ok(false)` if there was none
+    pub fn clear(&self, path: &Path) -> Result<bool, std::io::Error> {
+        self.migrate_file(&self.legacy_filename(path));
+        match self.find(path).map(|(file, _)| file) {
+            Some(cache_file) => fs::remove_file(cache_file).map(|_| true),
+            None => Ok(false),
+        }
+    }
+
+    /// Get cache info for display.
+    pub fn get_cache_info(&self, path: &Path) -> Option<CacheInfo> {
+        self.migrate_file(&self.legacy_filename(path));
+        let cache_file = self.find(path).map(|(file, _)| file)?;
+        Self::read_info(&cache_file)
+    }
+
+    /// Read the header of one cache file.
+    fn read_info(cache_file: &Path) -> Option<CacheInfo> {
+        let file = File::open(cache_file).ok()?;
+        let size = file.metadata().ok()?.len();
+        let header = CacheReader::new(BufReader::new(file)).ok()?.header().clone();
+
+        Some(CacheInfo {
+            file_path: cache_file.to_path_buf(),
+            size,
+            age_secs: age_secs(header.scan_time),
+            root_path: header.root_path,
+            host: header.host,
+            node_count: header.node_count,
+            total_size: header.total_size,
+            total_files: header.total_files,
+            options: header.options,
+        })
+    }
+
+    /// Every readable cache, newest first.
+    ///
+    /// v1 JSON caches are converted on the way.
+    pub fn list(&self) -> Vec<CacheInfo> {
+        for legacy_file in self.cache_files(LEGACY_EXTENSION) {
+            self.migrate_file(&legacy_file);
+        }
+
+        let mut infos: Vec<CacheInfo> = self
+            .cache_files(CACHE_EXTENSION)
+            .iter()
+            .filter_map(|file| Self::read_info(file))
+            .collect();
+        infos.sort_by_key(|info| info.age_secs);
+        infos
+    }
+
+    /// Whether a cache is too old to be used.
+    pub fn is_stale(&self, info: &CacheInfo) -> bool {
+        info.age_secs >= self.max_age.as_secs()
+    }
+
+    /// Remove caches older than `older_than`, then the oldest ones until the
+    /// rest fit in `max_total_size` bytes. Unreadable cache files are always
+    /// removed.
+    ///
+    /// # Returns
+    /// The caches that were removed
+    pub fn prune(&self, older_than: Option<Duration>, max_total_size: Option<u64>) -> Result<Vec<CacheInfo>, std::io::Error> {
+        let infos = self.list();
+
+        // Anything list() couldn't read is damaged or from an unknown version
+        for file in self.cache_files(CACHE_EXTENSION) {
+            if !infos.iter().any(|info| info.file_path == file) {
+                fs::remove_file(&file)?;
+            }
+        }
+
+        let mut removed = Vec::new();
+        let mut total = 0u64;
+        let mut full = false;
+        for info in infos {
+            let too_old = older_than.is_some_and(|max| info.age_secs >= max.as_secs());
+            // Once the budget is used up, every older cache goes too
+            full = full || (!too_old && max_total_size.is_some_and(|max| total + info.size > max));
+            if too_old || full {
+                fs::remove_file(&info.file_path)?;
+                removed.push(info);
+            } else {
+                total += info.size;
+            }
+        }
+        Ok(removed)
+    }
+}
+
+/// Write `tree` to `cache_file`, replacing it only once complete.
+pub fn write_cache_file(cache_file: &Path, tree: &FileTree, header: &CacheHeader) -> Result<(), std::io::Error> {
+    let temp_file = cache_file.with_extension("dxc.tmp");
+
+    let result = File::create(&temp_file).and_then(|file| {
+        let mut writer = BufWriter::new(file);
+        binary::write_cache(tree, header, &mut writer)?;
+        writer.flush()
+    });
+
+    match result {
+        Ok(()) => fs::rename(&temp_file, cache_file),
+        Err(e) => {
+            let _ = fs::remove_file(&temp_file);
+            Err(e)
+        }
+    }
+}
+
+/// Open a cache file, reading only its header.
+pub fn open_cache_file(cache_file: &Path) -> Option<CacheReader<BufReader<File>>> {
+    let file = File::open(cache_file).ok()?;
+    CacheReader::new(BufReader::new(file)).ok()
+}
+
+/// Whether a scan made with `cached` options, `depth` levels above the
+/// root of `wanted`, has exactly the nodes a scan with `wanted` would find.
+pub fn options_cover(cached: Option<&ScanOptions>, wanted: &ScanOptions, depth: usize) -> bool {
+    cached.is_some_and(|cached| {
+        cached.exclude_patterns == wanted.exclude_patterns
+            && (depth == 0 || cached.exclude_patterns.iter().all(|pattern| excludes_alike_at_any_root(pattern)))
+            && cached.cross_mount == wanted.cross_mount
+            && cached.apparent_size == wanted.apparent_size
+            && cached.max_depth.map(|max_depth| max_depth.saturating_sub(depth)) == wanted.max_depth
+    })
+}
+
+/// Whether `pattern` excludes the same entries below a directory whether
+/// the scan starts there or at an ancestor.
+///
+/// Patterns match paths relative to the scan root, which are longer from
+/// an ancestor: `alice/tmp` matches `/home/alice/tmp` in a scan of `/home`
+/// only. Without a `/` and with a single run of text around the `*`s
+/// (`tmp`, `*.log`, `build*`), whatever matches a relative path also
+/// matches an entry name, which is the same from any root.
+fn excludes_alike_at_any_root(pattern: &str) -> bool {
+    !pattern.contains('/') && pattern.split('*').filter(|text| !text.is_empty()).count() <= 1
+}
+
+/// Seconds since `scan_time` (Unix time)
+fn age_secs(scan_time: u64) -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+        .saturating_sub(scan_time)
+}
+
+/// Information about a cache entry.
+#[derive(Debug)]
+pub struct CacheInfo {
+    pub file_path: PathBuf,
+    /// Size of the cache file
+    pub size: u64,
+    /// Time since the cached scan was made
+    pub age_secs: u64,
+    pub root_path: PathBuf,
+    /// Host the scan was made on, None for this machine
+    pub host: Option<String>,
+    pub node_count: u64,
+    /// Size of the scanned tree
+    pub total_size: u64,
+    pub total_files: u64,
+    /// Options of the cached scan, None if not recorded
+    pub options: Option<ScanOptions>,
+}
+
+impl CacheInfo {
+    /// The scanned root, prefixed with its host for remote scans.
+    pub fn location(&self) -> String {
+        match self.host {
+            Some(ref host) => format!("{}:{}", host, self.root_path.display()),
+            None => self.root_path.display().to_string(),
+        }
+    }
+
+    /// Format the age as a human-readable string.
+    pub fn age_string(&self) -> String {
+        format_age(self.age_secs)
+    }
+}
+
+/// Seconds since `scan_time` (Unix time), e.g. "3h ago"
+pub fn age_string(scan_time: u64) -> String {
+    format_age(age_secs(scan_time))
+}
+
+fn format_age(secs: u64) -> String {
+    if secs < 60 {
+        format!("{}s ago", secs)
+    } else if secs < 3600 {
+        format!("{}m ago", secs / 60)
+    } else if secs < 86400 {
+        format!("{}h ago", secs / 3600)
+    } else {
+        format!("{}d ago", secs / 86400)
+    }
+}
+
+impl Default for CacheManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::{get_disk_space, Scanner};
+    use crate::tree::FileNode;
+    use proptest::prelude::*;
+    use tempfile::TempDir;
+
+    /// Header of a scan of `root` with default options
+    fn scan_header(tree: &FileTree, root: &Path) -> CacheHeader {
+        header_for(tree, root).with_scan(Some(&ScanOptions::new(root.to_path_buf())), None)
+    }
+
+    fn save(manager: &CacheManager, tree: &FileTree, root: &Path) {
+        manager.save(tree, &scan_header(tree, root)).unwrap();
+    }
+
+    fn options(root: &str) -> ScanOptions {
+        ScanOptions::new(PathBuf::from(root))
+    }
+
+    fn create_test_tree() -> FileTree {
+        let mut tree = FileTree::with_root(PathBuf::from("/srv"));
+        let root = tree.root.unwrap();
+        let logs = tree.add_child(root, FileNode::new(PathBuf::from("/srv/logs"), true));
+        tree.add_child(logs, FileNode::new(PathBuf::from("/srv/logs/app.log"), false).with_size(500));
+        tree.calculate_sizes();
+        tree
+    }
+
+    #[test]
+    fn test_cache_filename_hash() {
+        let manager = CacheManager::new();
+        let path1 = PathBuf::from("/home/user/test");
+        let path2 = PathBuf::from("/home/user/test2");
+
+        let file1 = manager.cache_filename(&path1, 0);
+        let file2 = manager.cache_filename(&path2, 0);
+
+        assert_ne!(file1, file2);
+    }
+
+    #[test]
+    fn test_simple_hash() {
+        let hash1 = CacheManager::simple_hash("/test/path");
+        let hash2 = CacheManager::simple_hash("/test/path");
+        let hash3 = CacheManager::simple_hash("/test/other");
+
+        assert_eq!(hash1, hash2);
+        assert_ne!(hash1, hash3);
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = TempDir::new().unwrap();
+        let manager = CacheManager::with_cache_dir(dir.path().to_path_buf());
+        let root = PathBuf::from("/srv");
+
+        assert!(!manager.has_valid_cache(&options("/srv")));
+        save(&manager, &create_test_tree(), &root);
+        assert!(manager.has_valid_cache(&options("/srv")));
+        assert!(!manager.cache_filename(&root, 0).with_extension("dxc.tmp").exists());
+
+        let tree = manager.load(&options("/srv")).unwrap().0;
+        assert_eq!(tree.node_count(), 3);
+        assert_eq!(tree.get_node(tree.root.unwrap()).unwrap().size, 500);
+
+        // A file for another path that happens to collide is not used
+        assert!(manager.load(&options("/other")).is_none());
+    }
+
+    #[test]
+    fn test_other_scan_options_miss() {
+        let dir = TempDir::new().unwrap();
+        let manager = CacheManager::with_cache_dir(dir.path().to_path_buf());
+        let tree = create_test_tree();
+        let header = header_for(&tree, Path::new("/srv"));
+
+        // Unknown options, as in a version 2 cache
+        manager.save(&tree, &header).unwrap();
+        assert!(!manager.has_valid_cache(&options("/srv")));
+
+        let scanned = options("/srv").with_exclude_patterns(vec!["*.log".to_string()]);
+        manager.save(&tree, &header.clone().with_scan(Some(&scanned), None)).unwrap();
+        assert!(manager.has_valid_cache(&scanned));
+        assert!(!manager.has_valid_cache(&options("/srv")));
+        assert!(manager.load(&options("/srv/logs").with_exclude_patterns(vec!["*.log".to_string()])).is_some());
+        assert!(manager.load(&options("/srv/logs")).is_none());
+
+        // Depth limits count from the cached root
+        let shallow = options("/srv").with_max_depth(Some(2));
+        manager.save(&tree, &header.with_scan(Some(&shallow), None)).unwrap();
+        assert!(manager.load(&options("/srv/logs").with_max_depth(Some(1))).is_some());
+        assert!(manager.load(&options("/srv/logs")).is_none());
+    }
+
+    #[test]
+    fn test_migrates_v1_json() {
+        let dir = TempDir::new().unwrap();
+        let manager = CacheManager::with_cache_dir(dir.path().to_path_buf());
+        let root = PathBuf::from("/srv");
+
+        let legacy = serde_json::json!({
+            "root_path": "/srv",
+            "scan_time": 1_700_000_000u64,
+            "version": 1,
+            "root_index": 0,
+            "total_size": 500,
+            "total_files": 1,
+            "nodes": [
+                {"path": "/srv", "name": "srv", "size": 500, "is_dir": true, "is_hidden": false,
+                 "is_symlink": false, "file_count": 1, "modified": null, "extension": null,
+                 "parent_index": null, "children_indices": [1]},
+                {"path": "/srv/app.log", "name": "app.log", "size": 500, "is_dir": false, "is_hidden": false,
+                 "is_symlink": false, "file_count": 1, "modified": 1_700_000_000u64, "extension": "log",
+                 "parent_index": 0, "children_indices": []}
+            ]
+        });
+        fs::write(manager.legacy_filename(&root), legacy.to_string()).unwrap();
+
+        let reader = manager.open(&root).unwrap();
+        // The original scan time is kept, so an old v1 cache stays old
+        assert_eq!(reader.header().scan_time, 1_700_000_000);
+        assert!(!manager.has_valid_cache(&options("/srv")));
+
+        let tree = reader.finish().unwrap();
+        let log = tree.find_by_path(&PathBuf::from("/srv/app.log")).unwrap();
+        assert_eq!(tree.get_node(log).unwrap().size, 500);
+        assert!(!manager.legacy_filename(&root).exists());
+        assert!(manager.cache_filename(&root, 0).exists());
+    }
+
+    #[test]
+    fn test_discards_unreadable_v1_json() {
+        let dir = TempDir::new().unwrap();
+        let manager = CacheManager::with_cache_dir(dir.path().to_path_buf());
+        let root = PathBuf::from("/srv");
+
+        fs::write(manager.legacy_filename(&root), "{\"version\": 1, \"nodes\": [").unwrap();
+        assert!(manager.open(&root).is_none());
+        assert!(!manager.legacy_filename(&root).exists());
+    }
+
+    #[test]
+    fn test_list_and_prune() {
+        let dir = TempDir::new().unwrap();
+        let manager = CacheManager::with_cache_dir(dir.path().to_path_buf()).with_max_age(Duration::from_secs(3600));
+        let tree = create_test_tree();
+
+        // Three scans of the same size, 0s, 2h and 10d old
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        for (root, age) in [("/a", 0), ("/b", 7200), ("/c", 864000)] {
+            let header = CacheHeader {
+                scan_time: now - age,
+                ..scan_header(&tree, Path::new(root))
+            };
+            manager.write(&tree, &header).unwrap();
+        }
+        fs::write(dir.path().join("scan_0000000000000000.dxc"), b"damaged").unwrap();
+
+        let infos = manager.list();
+        let roots: Vec<_> = infos.iter().map(|info| info.root_path.clone()).collect();
+        assert_eq!(roots, [PathBuf::from("/a"), PathBuf::from("/b"), PathBuf::from("/c")]);
+        assert_eq!(infos[0].node_count, 3);
+        assert!(!manager.is_stale(&infos[0]));
+        assert!(manager.is_stale(&infos[1]));
+        assert!(manager.has_valid_cache(&options("/a")));
+        assert!(!manager.has_valid_cache(&options("/b")));
+
+        let removed = manager.prune(Some(Duration::from_secs(86400)), None).unwrap();
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].root_path, PathBuf::from("/c"));
+        assert!(!dir.path().join("scan_0000000000000000.dxc").exists());
+
+        // Room for one cache: the newest stays
+        let removed = manager.prune(None, Some(infos[0].size)).unwrap();
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].root_path, PathBuf::from("/b"));
+        assert!(manager.get_cache_info(Path::new("/a")).is_some());
+    }
+
+    #[test]
+    fn test_clear_reports_removal() {
+        let dir = TempDir::new().unwrap();
+        let manager = CacheManager::with_cache_dir(dir.path().to_path_buf());
+        let root = PathBuf::from("/srv");
+
+        save(&manager, &create_test_tree(), &root);
+        assert!(manager.clear(&root).unwrap());
+        assert!(!manager.clear(&root).unwrap());
+        assert!(manager.get_cache_info(&root).is_none());
+    }
+
+    #[test]
+    fn test_clear_all_keeps_history() {
+        let dir = TempDir::new().unwrap();
+        let manager = CacheManager::with_cache_dir(dir.path().to_path_buf());
+        save(&manager, &create_test_tree(), Path::new("/srv"));
+        save(&manager, &create_home_tree(), Path::new("/home"));
+        fs::write(dir.path().join("scan_0000000000000001.dxc.tmp"), b"partial").unwrap();
+        let snapshot = dir.path().join("history/0000000000000001/1700000000.dxc");
+        fs::create_dir_all(snapshot.parent().unwrap()).unwrap();
+        fs::write(&snapshot, b"snapshot").unwrap();
+
+        manager.clear_all().unwrap();
+        assert!(manager.list().is_empty());
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+        assert!(snapshot.exists());
+    }
+
+    fn create_home_tree() -> FileTree {
+        let mut tree = FileTree::with_root(PathBuf::from("/home"));
+        let root = tree.root.unwrap();
+        let alice = tree.add_child(root, FileNode::new(PathBuf::from("/home/alice"), true));
+        tree.add_child(alice, FileNode::new(PathBuf::from("/home/alice/old.iso"), false).with_size(4000));
+        let bob = tree.add_child(root, FileNode::new(PathBuf::from("/home/bob"), true));
+        tree.add_child(bob, FileNode::new(PathBuf::from("/home/bob/notes.txt"), false).with_size(50));
+        tree.calculate_sizes();
+        tree
+    }
+
+    #[test]
+    fn test_colliding_paths_get_own_files() {
+        let dir = TempDir::new().unwrap();
+        let manager = CacheManager::with_cache_dir(dir.path().to_path_buf());
+        // 'a' * 33 + 'B' == 'b' * 33 + '!'
+        let first = PathBuf::from("/x/aB");
+        let second = PathBuf::from("/x/b!");
+        assert_eq!(CacheManager::path_hash(&first), CacheManager::path_hash(&second));
+
+        save(&manager, &FileTree::with_root(first.clone()), &first);
+        save(&manager, &FileTree::with_root(second.clone()), &second);
+        assert_eq!(manager.list().len(), 2);
+        assert!(manager.cache_filename(&first, 1).exists());
+
+        // Saving again reuses the path's own file
+        save(&manager, &FileTree::with_root(second.clone()), &second);
+        assert_eq!(manager.list().len(), 2);
+
+        assert!(manager.clear(&first).unwrap());
+        let tree = manager.load(&options("/x/b!")).unwrap().0;
+        assert_eq!(tree.get_node(tree.root.unwrap()).unwrap().path, second);
+        assert!(manager.load(&options("/x/aB")).is_none());
+    }
+
+    #[test]
+    fn test_hosts_get_own_caches() {
+        let dir = TempDir::new().unwrap();
+        let local = CacheManager::with_cache_dir(dir.path().to_path_buf());
+        let files = CacheManager::with_cache_dir(dir.path().to_path_buf()).with_host("admin@files:22");
+        let other_port = CacheManager::with_cache_dir(dir.path().to_path_buf()).with_host("admin@files:2222");
+        let root = PathBuf::from("/srv");
+
+        save(&files, &create_test_tree(), &root);
+        assert!(files.has_valid_cache(&options("/srv")));
+        assert!(!local.has_valid_cache(&options("/srv")));
+        assert!(other_port.load(&options("/srv")).is_none());
+
+        // Subtrees come from the same host's ancestors only
+        assert!(files.load(&options("/srv/logs")).is_some());
+        assert!(local.load(&options("/srv/logs")).is_none());
+
+        save(&local, &FileTree::with_root(root.clone()), &root);
+        let mut locations: Vec<_> = local.list().iter().map(CacheInfo::location).collect();
+        locations.sort();
+        assert_eq!(locations, ["/srv", "admin@files:22:/srv"]);
+        assert_eq!(files.open(&root).unwrap().header().host.as_deref(), Some("admin@files:22"));
+
+        assert!(files.clear(&root).unwrap());
+        assert!(!files.clear(&root).unwrap());
+        assert!(local.has_valid_cache(&options("/srv")));
+
+        // Switching hosts mid-session keeps the caches apart too
+        let switched = local.for_host(Some("admin@files:22".to_string()));
+        save(&switched, &create_test_tree(), &root);
+        assert_eq!(switched.open(&root).unwrap().header().host.as_deref(), Some("admin@files:22"));
+        assert_eq!(local.open(&root).unwrap().header().node_count, 1);
+        assert_eq!(switched.for_host(None).open(&root).unwrap().header().node_count, 1);
+    }
+
+    #[test]
+    fn test_load_subtree_from_ancestor() {
+        let dir = TempDir::new().unwrap();
+        let manager = CacheManager::with_cache_dir(dir.path().to_path_buf());
+        save(&manager, &create_home_tree(), Path::new("/home"));
+
+        let (tree, header) = manager.load(&options("/home/alice")).unwrap();
+        assert_eq!(header.root_path, PathBuf::from("/home"));
+        let root = tree.get_node(tree.root.unwrap()).unwrap();
+        assert_eq!(root.path, PathBuf::from("/home/alice"));
+        assert_eq!(root.size, 4000);
+        assert_eq!(tree.node_count(), 2);
+
+        // Only the directory's own cache counts as valid for lazy loading
+        assert!(!manager.has_valid_cache(&options("/home/alice")));
+        assert!(manager.load(&options("/home/carol")).is_none());
+        assert!(manager.load(&options("/home/bob/notes.txt")).is_none());
+    }
+
+    #[test]
+    fn test_subtree_scan_merges_into_ancestor() {
+        let dir = TempDir::new().unwrap();
+        let manager = CacheManager::with_cache_dir(dir.path().to_path_buf());
+        let home = create_home_tree();
+        let header = CacheHeader {
+            scan_time: 1_000,
+            ..scan_header(&home, Path::new("/home"))
+        };
+        manager.write(&home, &header).unwrap();
+
+        let mut alice = FileTree::with_root(PathBuf::from("/home/alice"));
+        let alice_root = alice.root.unwrap();
+        alice.add_child(alice_root, FileNode::new(PathBuf::from("/home/alice/new.txt"), false).with_size(10));
+        alice.calculate_sizes();
+        save(&manager, &alice, Path::new("/home/alice"));
+
+        let info = manager.get_cache_info(Path::new("/home")).unwrap();
+        assert_eq!(info.total_size, 60);
+        assert_eq!(info.node_count, 5);
+
+        let reader = manager.open(Path::new("/home")).unwrap();
+        assert_eq!(reader.header().scan_time, 1_000);
+        let merged = reader.finish().unwrap();
+        assert!(merged.find_by_path(&PathBuf::from("/home/alice/old.iso")).is_none());
+        assert!(merged.find_by_path(&PathBuf::from("/home/alice/new.txt")).is_some());
+        assert!(manager.get_cache_info(Path::new("/home/alice")).is_some());
+    }
+
+    #[test]
+    fn test_path_excludes_stay_with_their_root() {
+        let dir = TempDir::new().unwrap();
+        let manager = CacheManager::with_cache_dir(dir.path().to_path_buf());
+        let home = create_home_tree();
+        let with_excludes = |root: &str, patterns: &[&str]| ScanOptions {
+            exclude_patterns: patterns.iter().map(|p| p.to_string()).collect(),
+            ..options(root)
+        };
+        let save_home = |patterns: &[&str]| {
+            let header = header_for(&home, Path::new("/home")).with_scan(Some(&with_excludes("/home", patterns)), None);
+            manager.save(&home, &header).unwrap();
+        };
+
+        // `alice/tmp` matches below /home, but not in a scan of /home/alice
+        for patterns in [&["alice/tmp"][..], &["a*p"], &["*.iso", "al*/x"]] {
+            save_home(patterns);
+            assert!(manager.load(&with_excludes("/home", patterns)).is_some(), "{:?}", patterns);
+            assert!(manager.load(&with_excludes("/home/alice", patterns)).is_none(), "{:?}", patterns);
+        }
+
+        // Names match alike from any root
+        for patterns in [&["tmp"][..], &["*.iso"], &["build*", "*cache*"]] {
+            save_home(patterns);
+            assert!(manager.load(&with_excludes("/home/alice", patterns)).is_some(), "{:?}", patterns);
+        }
+
+        // Nor does a subtree scan with a path exclude merge into the ancestor
+        save_home(&["alice/tmp"]);
+        let mut alice = FileTree::with_root(PathBuf::from("/home/alice"));
+        let alice_root = alice.root.unwrap();
+        alice.add_child(alice_root, FileNode::new(PathBuf::from("/home/alice/tmp"), true));
+        let header = header_for(&alice, Path::new("/home/alice"))
+            .with_scan(Some(&with_excludes("/home/alice", &["alice/tmp"])), None);
+        manager.save(&alice, &header).unwrap();
+        let merged = manager.open(Path::new("/home")).unwrap().finish().unwrap();
+        assert!(merged.find_by_path(&PathBuf::from("/home/alice/tmp")).is_none());
+        assert!(merged.find_by_path(&PathBuf::from("/home/alice/old.iso")).is_some());
+    }
+
+    /// What to create in a generated directory tree
+    #[derive(Debug, Clone)]
+    enum Entry {
+        Dir,
+        File { len: usize, mtime_nanos: u32 },
+        /// Broken when the target doesn't exist
+        Symlink { target: String },
+    }
+
+    fn entry() -> impl Strategy<Value = (usize, String, Entry)> {
+        let entry = prop_oneof![
+            Just(Entry::Dir),
+            (0usize..5000, 0u32..1_000_000_000).prop_map(|(len, mtime_nanos)| Entry::File { len, mtime_nanos }),
+            "[a-z]{1,6}".prop_map(|target| Entry::Symlink { target }),
+        ];
+        (any::<usize>(), "\\.?[a-z0-9_-]{1,8}(\\.[a-z]{1,3})?", entry)
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(24))]
+
+        #[test]
+        fn prop_scan_save_load_is_lossless(
+            entries in proptest::collection::vec(entry(), 1..30),
+            exclude in proptest::collection::vec(any::<usize>(), 0..3),
+            apparent_size in any::<bool>(),
+        ) {
+            let dir = TempDir::new().unwrap();
+            let root = dir.path().join("scan");
+            fs::create_dir(&root).unwrap();
+
+            let mut dirs = vec![root.clone()];
+            for (pick, name, entry) in &entries {
+                let path = dirs[pick % dirs.len()].join(name);
+                if path.symlink_metadata().is_ok() {
+                    continue;
+                }
+                match entry {
+                    Entry::Dir => {
+                        fs::create_dir(&path).unwrap();
+                        dirs.push(path);
+                    }
+                    Entry::File { len, mtime_nanos } => {
+                        let file = File::create(&path).unwrap();
+                        file.set_len(*len as u64).unwrap();
+                        file.set_modified(UNIX_EPOCH + Duration::new(1_700_000_000, *mtime_nanos)).unwrap();
+                    }
+                    #[cfg(unix)]
+                    Entry::Symlink { target } => std::os::unix::fs::symlink(target, &path).unwrap(),
+                    #[cfg(not(unix))]
+                    Entry::Symlink { .. } => {}
+                }
+            }
+
+            let options = ScanOptions {
+                apparent_size,
+                ..ScanOptions::new(root.clone())
+            };
+            let (tx, _rx) = std::sync::mpsc::sync_channel(100_000);
+            let mut tree = Scanner::new(options.clone(), tx).scan().unwrap();
+
+            // Exclusions made in the TUI are part of the saved state
+            let ids: Vec<_> = tree.root.unwrap().descendants(&tree.arena).collect();
+            for pick in exclude {
+                tree.get_node_mut(ids[pick % ids.len()]).unwrap().excluded = true;
+            }
+            tree.calculate_sizes();
+
+            let manager = CacheManager::with_cache_dir(dir.path().join("cache"));
+            let header = header_for(&tree, &root).with_scan(Some(&options), get_disk_space(&root));
+            manager.save(&tree, &header).unwrap();
+
+            prop_assert!(manager.has_valid_cache(&options));
+            let reader = manager.open(&root).unwrap();
+            prop_assert_eq!(reader.header(), &header);
+            let loaded = manager.load(&options).unwrap().0;
+            prop_assert_eq!(tree_summary(&loaded), tree_summary(&tree));
+        }
+    }
+
+    /// Every node with all its fields and its depth, in tree order
+    fn tree_summary(tree: &FileTree) -> Vec<String> {
+        let root = tree.root.unwrap();
+        root.descendants(&tree.arena)
+            .map(|id| format!("{} {:?}", id.ancestors(&tree.arena).count(), tree.get_node(id).unwrap()))
+            .collect()
+    }
+}
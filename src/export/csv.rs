@@ -0,0 +1,313 @@
+use std::fmt;
+use std::io::Write;
+use std::str::FromStr;
+use std::time::UNIX_EPOCH;
+
+use crate::tree::{FileNode, FileTree, NodeId};
+
+use super::ExportOptions;
+
+/// A column that can be selected for CSV/TSV output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    Path,
+    Size,
+    Files,
+    Mtime,
+    Ext,
+    Depth,
+    IsDir,
+}
+
+impl Column {
+    /// Default column set, in output order
+    pub const ALL: [Column; 7] = [
+        Column::Path,
+        Column::Size,
+        Column::Files,
+        Column::Mtime,
+        Column::Ext,
+        Column::Depth,
+        Column::IsDir,
+    ];
+
+    /// Header name of the column (same as accepted by `--columns`)
+    pub fn name(&self) -> &'static str {
+        match self {
+            Column::Path => "path",
+            Column::Size => "size",
+            Column::Files => "files",
+            Column::Mtime => "mtime",
+            Column::Ext => "ext",
+            Column::Depth => "depth",
+            Column::IsDir => "is_dir",
+        }
+    }
+
+    /// Raw (unescaped) value of this column for a node
+    fn value(&self, node: &FileNode, depth: usize) -> String {
+        match self {
+            Column::Path => node.path.to_string_lossy().to_string(),
+            Column::Size => node.size.to_string(),
+            Column::Files => node.file_count.to_string(),
+            Column::Mtime => node
+                .modified
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_secs().to_string())
+                .unwrap_or_default(),
+            Column::Ext => node.extension.clone().unwrap_or_default(),
+            Column::Depth => depth.to_string(),
+            Column::IsDir => node.is_dir.to_string(),
+        }
+    }
+}
+
+impl FromStr for Column {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Column::ALL
+            .iter()
+            .find(|c| c.name() == s.trim())
+            .copied()
+            .ok_or_else(|| {
+                let valid: Vec<&str> = Column::ALL.iter().map(|c| c.name()).collect();
+                format!("unknown column '{}' (valid: {})", s, valid.join(","))
+            })
+    }
+}
+
+impl fmt::Display for Column {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Field separator for delimited output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Delimiter {
+    /// RFC 4180 comma-separated values
+    Comma,
+    /// Tab-separated values with backslash escapes
+    Tab,
+}
+
+/// Options for customizing CSV/TSV export
+pub struct CsvOptions {
+    pub delimiter: Delimiter,
+    /// Columns to emit, in order
+    pub columns: Vec<Column>,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: Delimiter::Comma,
+            columns: Column::ALL.to_vec(),
+        }
+    }
+}
+
+/// Escape a field for the given delimiter.
+///
+/// CSV fields are quoted when they contain a comma, quote or line break
+/// (quotes are doubled). TSV cannot quote, so tabs, line breaks and
+/// backslashes are written as `\t`, `\n`, `\r` and `\\`.
+fn escape_field(value: &str, delimiter: Delimiter) -> String {
+    match delimiter {
+        Delimiter::Comma => {
+            if value.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", value.replace('"', "\"\""))
+            } else {
+                value.to_string()
+            }
+        }
+        Delimiter::Tab => {
+            let mut escaped = String::with_capacity(value.len());
+            for c in value.chars() {
+                match c {
+                    '\\' => escaped.push_str("\\\\"),
+                    '\t' => escaped.push_str("\\t"),
+                    '\n' => escaped.push_str("\\n"),
+                    '\r' => escaped.push_str("\\r"),
+                    _ => escaped.push(c),
+                }
+            }
+            escaped
+        }
+    }
+}
+
+fn write_row<'a>(
+    writer: &mut impl Write,
+    fields: impl Iterator<Item = &'a str>,
+    delimiter: Delimiter,
+) -> Result<(), std::io::Error> {
+    let separator = match delimiter {
+        Delimiter::Comma => ",",
+        Delimiter::Tab => "\t",
+    };
+
+    let line: Vec<String> = fields.map(|f| escape_field(f, delimiter)).collect();
+    writeln!(writer, "{}", line.join(separator))
+}
+
+/// Collect (node, depth) pairs in depth-first order without recursion
+fn collect_nodes(tree: &FileTree, root: NodeId) -> Vec<(NodeId, usize)> {
+    let mut result = Vec::new();
+    let mut stack = vec![(root, 0usize)];
+
+    while let Some((node_id, depth)) = stack.pop() {
+        result.push((node_id, depth));
+
+        // Push children reversed so they are visited in tree order
+        let children = tree.get_children(node_id);
+        for child_id in children.into_iter().rev() {
+            stack.push((child_id, depth + 1));
+        }
+    }
+
+    result
+}
+
+/// Export the FileTree as CSV or TSV
+///
+/// Writes a header row followed by one row per node. With `top_n` set the
+/// tree is flattened and only the n largest entries are written.
+///
+/// # Arguments
+/// * `tree` - The FileTree to export
+/// * `options` - Export options (top_n for flattening, None for full tree)
+/// * `csv_options` - Delimiter and column selection
+/// * `writer` - Output writer
+pub fn export_csv(
+    tree: &FileTree,
+    options: &ExportOptions,
+    csv_options: &CsvOptions,
+    writer: &mut impl Write,
+) -> Result<(), std::io::Error> {
+    write_row(
+        writer,
+        csv_options.columns.iter().map(|c| c.name()),
+        csv_options.delimiter,
+    )?;
+
+    let root = match tree.root {
+        Some(root) => root,
+        None => return Ok(()),
+    };
+
+    let mut rows = collect_nodes(tree, root);
+
+    if let Some(n) = options.top_n {
+        rows.sort_by_key(|&(node_id, _)| {
+            std::cmp::Reverse(tree.get_node(node_id).map(|node| node.size).unwrap_or(0))
+        });
+        rows.truncate(n);
+    }
+
+    for (node_id, depth) in rows {
+        let node = tree.get_node(node_id).expect("Node must exist");
+        let values: Vec<String> = csv_options
+            .columns
+            .iter()
+            .map(|c| c.value(node, depth))
+            .collect();
+        write_row(writer, values.iter().map(String::as_str), csv_options.delimiter)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn create_test_tree() -> FileTree {
+        let mut tree = FileTree::with_root(PathBuf::from("/test"));
+        let root = tree.root.unwrap();
+
+        let subdir = FileNode::new(PathBuf::from("/test/sub,dir"), true);
+        let subdir_id = tree.add_child(root, subdir);
+
+        let quoted = FileNode::new(PathBuf::from("/test/sub,dir/say \"hi\".txt"), false).with_size(500);
+        tree.add_child(subdir_id, quoted);
+
+        let newline = FileNode::new(PathBuf::from("/test/line\nbreak\tname.log"), false).with_size(1000);
+        tree.add_child(root, newline);
+
+        tree.calculate_sizes();
+        tree
+    }
+
+    fn export_to_string(tree: &FileTree, top_n: Option<usize>, csv_options: &CsvOptions) -> String {
+        let mut buffer = Vec::new();
+        export_csv(tree, &ExportOptions { top_n, ..Default::default() }, csv_options, &mut buffer).unwrap();
+        String::from_utf8(buffer).unwrap()
+    }
+
+    #[test]
+    fn test_parse_columns() {
+        assert_eq!("path".parse::<Column>(), Ok(Column::Path));
+        assert_eq!(" is_dir".parse::<Column>(), Ok(Column::IsDir));
+        assert!("bogus".parse::<Column>().unwrap_err().contains("valid:"));
+    }
+
+    #[test]
+    fn test_csv_full_tree_quoting() {
+        let tree = create_test_tree();
+        let csv_options = CsvOptions {
+            delimiter: Delimiter::Comma,
+            columns: vec![Column::Path, Column::Size, Column::Depth, Column::IsDir],
+        };
+
+        let output = export_to_string(&tree, None, &csv_options);
+
+        assert_eq!(
+            output,
+            "path,size,depth,is_dir\n\
+             /test,1500,0,true\n\
+             \"/test/sub,dir\",500,1,true\n\
+             \"/test/sub,dir/say \"\"hi\"\".txt\",500,2,false\n\
+             \"/test/line\nbreak\tname.log\",1000,1,false\n"
+        );
+    }
+
+    #[test]
+    fn test_tsv_escaping() {
+        let tree = create_test_tree();
+        let csv_options = CsvOptions {
+            delimiter: Delimiter::Tab,
+            columns: vec![Column::Ext, Column::Path],
+        };
+
+        let output = export_to_string(&tree, None, &csv_options);
+        let lines: Vec<&str> = output.lines().collect();
+
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[0], "ext\tpath");
+        assert_eq!(lines[4], "log\t/test/line\\nbreak\\tname.log");
+    }
+
+    #[test]
+    fn test_csv_top_n() {
+        let tree = create_test_tree();
+        let csv_options = CsvOptions {
+            delimiter: Delimiter::Comma,
+            columns: vec![Column::Size, Column::Files],
+        };
+
+        let output = export_to_string(&tree, Some(2), &csv_options);
+
+        assert_eq!(output, "size,files\n1500,2\n1000,1\n");
+    }
+
+    #[test]
+    fn test_csv_empty_tree() {
+        let tree = FileTree::new();
+        let output = export_to_string(&tree, None, &CsvOptions::default());
+
+        assert_eq!(output, "path,size,files,mtime,ext,depth,is_dir\n");
+    }
+}
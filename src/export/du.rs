@@ -0,0 +1,188 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use indextree::NodeEdge;
+
+use crate::format::{format_size_short, SizeUnits};
+use crate::tree::FileTree;
+
+/// Options for `du`-compatible output
+#[derive(Debug, Clone, Default)]
+pub struct DuOptions {
+    /// Units for sizes; None prints 1024-byte blocks like plain `du`
+    pub units: Option<SizeUnits>,
+    /// Deepest level printed (0 = only the root, like `du -s`)
+    pub max_depth: Option<usize>,
+    /// Print files as well as directories (`du -a`)
+    pub all: bool,
+    /// Path printed for the root, e.g. the argument as typed; entries below
+    /// it are printed relative to this. None prints the scanned paths.
+    pub display_root: Option<PathBuf>,
+}
+
+/// Size column for one entry
+fn du_size(bytes: u64, units: Option<SizeUnits>) -> String {
+    match units {
+        Some(units) => format_size_short(bytes, units),
+        // du rounds partial blocks up
+        None => bytes.div_ceil(1024).to_string(),
+    }
+}
+
+/// Path column for one entry
+fn du_path(path: &Path, root_path: &Path, display_root: Option<&Path>) -> String {
+    let shown = match (display_root, path.strip_prefix(root_path)) {
+        (Some(display_root), Ok(relative)) if relative.as_os_str().is_empty() => display_root.to_path_buf(),
+        (Some(display_root), Ok(relative)) => display_root.join(relative),
+        _ => path.to_path_buf(),
+    };
+    shown.to_string_lossy().to_string()
+}
+
+/// Export the FileTree in the format of `du`
+///
+/// Prints `size<TAB>path` lines in du's order: every directory after its
+/// contents, the root last. Directories deeper than `max_depth` are still
+/// counted in their parents, only not printed.
+///
+/// # Arguments
+/// * `tree` - The FileTree to export
+/// * `options` - Units, depth and which entries to print
+/// * `writer` - Output writer
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(std::io::Error)` on write failure
+pub fn export_du(tree: &FileTree, options: &DuOptions, writer: &mut impl Write) -> Result<(), std::io::Error> {
+    let root = match tree.root {
+        Some(root) => root,
+        None => return Ok(()),
+    };
+    let root_path = tree.get_node(root).map(|node| node.path.clone()).unwrap_or_default();
+    let display_root = options.display_root.as_deref();
+
+    let mut depth = 0usize;
+    // Depth of an excluded directory whose contents are being skipped
+    let mut skip_below: Option<usize> = None;
+
+    for edge in root.traverse(&tree.arena) {
+        match edge {
+            NodeEdge::Start(node_id) => {
+                depth += 1;
+                let excluded = tree.get_node(node_id).map(|node| node.excluded).unwrap_or(false);
+                if excluded && skip_below.is_none() {
+                    skip_below = Some(depth);
+                }
+            }
+            NodeEdge::End(node_id) => {
+                let level = depth - 1;
+                depth -= 1;
+
+                if let Some(skip_depth) = skip_below {
+                    if level + 1 == skip_depth {
+                        skip_below = None;
+                    }
+                    continue;
+                }
+
+                let node = tree.get_node(node_id).expect("Node must exist");
+                if !node.is_dir && !options.all && node_id != root {
+                    continue;
+                }
+                if options.max_depth.is_some_and(|max| level > max) {
+                    continue;
+                }
+
+                writeln!(
+                    writer,
+                    "{}\t{}",
+                    du_size(node.size, options.units),
+                    du_path(&node.path, &root_path, display_root)
+                )?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::FileNode;
+
+    fn create_test_tree() -> FileTree {
+        let mut tree = FileTree::with_root(PathBuf::from("/home/me/proj"));
+        let root = tree.root.unwrap();
+
+        let src = tree.add_child(root, FileNode::new(PathBuf::from("/home/me/proj/src"), true));
+        let ui = tree.add_child(src, FileNode::new(PathBuf::from("/home/me/proj/src/ui"), true));
+        tree.add_child(ui, FileNode::new(PathBuf::from("/home/me/proj/src/ui/app.rs"), false).with_size(8192));
+        tree.add_child(src, FileNode::new(PathBuf::from("/home/me/proj/src/main.rs"), false).with_size(4096));
+
+        let mut target = FileNode::new(PathBuf::from("/home/me/proj/target"), true);
+        target.excluded = true;
+        let target = tree.add_child(root, target);
+        tree.add_child(target, FileNode::new(PathBuf::from("/home/me/proj/target/big"), false).with_size(1 << 30));
+
+        tree.add_child(root, FileNode::new(PathBuf::from("/home/me/proj/README.md"), false).with_size(1500));
+
+        tree.calculate_sizes();
+        tree
+    }
+
+    fn export_to_string(tree: &FileTree, options: &DuOptions) -> String {
+        let mut buffer = Vec::new();
+        export_du(tree, options, &mut buffer).unwrap();
+        String::from_utf8(buffer).unwrap()
+    }
+
+    #[test]
+    fn test_directories_in_du_order() {
+        let output = export_to_string(&create_test_tree(), &DuOptions::default());
+        assert_eq!(
+            output,
+            "8\t/home/me/proj/src/ui\n12\t/home/me/proj/src\n14\t/home/me/proj\n"
+        );
+    }
+
+    #[test]
+    fn test_all_human_relative() {
+        let options = DuOptions {
+            units: Some(SizeUnits::Iec),
+            all: true,
+            display_root: Some(PathBuf::from(".")),
+            ..Default::default()
+        };
+        let output = export_to_string(&create_test_tree(), &options);
+        assert_eq!(
+            output,
+            "8.0K\t./src/ui/app.rs\n8.0K\t./src/ui\n4.0K\t./src/main.rs\n12K\t./src\n1.5K\t./README.md\n14K\t.\n"
+        );
+    }
+
+    #[test]
+    fn test_max_depth_and_summarize() {
+        let tree = create_test_tree();
+
+        let options = DuOptions { max_depth: Some(1), units: Some(SizeUnits::Bytes), ..Default::default() };
+        assert_eq!(
+            export_to_string(&tree, &options),
+            "12288\t/home/me/proj/src\n13788\t/home/me/proj\n"
+        );
+
+        let options = DuOptions { max_depth: Some(0), units: Some(SizeUnits::Si), ..Default::default() };
+        assert_eq!(export_to_string(&tree, &options), "14k\t/home/me/proj\n");
+    }
+
+    #[test]
+    fn test_single_file_root() {
+        let mut tree = FileTree::with_root(PathBuf::from("/tmp/file.bin"));
+        if let Some(root) = tree.get_node_mut(tree.root.unwrap()) {
+            root.is_dir = false;
+            root.size = 1025;
+        }
+        assert_eq!(export_to_string(&tree, &DuOptions::default()), "2\t/tmp/file.bin\n");
+        assert_eq!(export_to_string(&FileTree::new(), &DuOptions::default()), "");
+    }
+}
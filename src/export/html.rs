@@ -0,0 +1,512 @@
+//! Self-contained HTML report with an interactive treemap.
+//!
+//! The report is a single file with no external assets: the tree is embedded
+//! as JSON and rendered by inline JavaScript, so it can be opened offline or
+//! attached to a ticket.
+
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::time::UNIX_EPOCH;
+
+use ratatui::style::Color;
+use serde::Serialize;
+
+use crate::format::format_size;
+use crate::tree::{FileTree, NodeId};
+use crate::ui::colors::get_file_type_color;
+use crate::ui::AggregatedStats;
+
+/// Number of largest files listed in the report table
+const REPORT_TOP_FILES: usize = 500;
+
+/// Compact node representation embedded in the report
+#[derive(Serialize)]
+struct ReportNode {
+    /// Name
+    n: String,
+    /// Size in bytes
+    s: u64,
+    /// Extension (files only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    e: Option<String>,
+    /// Children (directories only, possibly empty)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    c: Option<Vec<ReportNode>>,
+}
+
+/// Row of the top files table
+#[derive(Serialize)]
+struct ReportFile {
+    path: String,
+    size: u64,
+    ext: String,
+    mtime: Option<u64>,
+}
+
+/// Colors used by the treemap
+#[derive(Serialize)]
+struct ReportColors {
+    dir: String,
+    other: String,
+    ext: BTreeMap<String, String>,
+}
+
+/// Everything the inline script needs
+#[derive(Serialize)]
+struct ReportData {
+    root_path: String,
+    tree: ReportNode,
+    top_files: Vec<ReportFile>,
+    colors: ReportColors,
+}
+
+/// Convert a terminal color to a CSS hex color
+fn css_color(color: Color) -> String {
+    match color {
+        Color::Rgb(r, g, b) => format!("#{:02x}{:02x}{:02x}", r, g, b),
+        _ => "#808080".to_string(),
+    }
+}
+
+/// Escape text for use in HTML element content or attributes
+fn escape_html(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Recursively convert a FileTree node to a ReportNode
+fn tree_to_report_node(tree: &FileTree, node_id: NodeId) -> ReportNode {
+    let node = tree.get_node(node_id).expect("Node must exist");
+
+    let children = if node.is_dir {
+        Some(
+            tree.get_children(node_id)
+                .into_iter()
+                .map(|child_id| tree_to_report_node(tree, child_id))
+                .collect(),
+        )
+    } else {
+        None
+    };
+
+    ReportNode {
+        n: node.name.clone(),
+        s: if node.excluded { 0 } else { node.size },
+        e: node.extension.clone(),
+        c: children,
+    }
+}
+
+/// Collect the largest files and the color of every extension in the tree
+fn collect_files(tree: &FileTree, root: NodeId) -> (Vec<ReportFile>, ReportColors) {
+    let mut files = Vec::new();
+    let mut ext_colors = BTreeMap::new();
+
+    for node_id in root.descendants(&tree.arena) {
+        let node = match tree.get_node(node_id) {
+            Some(node) if !node.is_dir && !node.excluded => node,
+            _ => continue,
+        };
+
+        if let Some(ref ext) = node.extension {
+            ext_colors
+                .entry(ext.clone())
+                .or_insert_with(|| css_color(get_file_type_color(Some(ext), false)));
+        }
+
+        files.push(ReportFile {
+            path: node.path.to_string_lossy().to_string(),
+            size: node.size,
+            ext: node.extension.clone().unwrap_or_default(),
+            mtime: node
+                .modified
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_secs()),
+        });
+    }
+
+    files.sort_by_key(|f| std::cmp::Reverse(f.size));
+    files.truncate(REPORT_TOP_FILES);
+
+    let colors = ReportColors {
+        dir: css_color(get_file_type_color(None, true)),
+        other: css_color(get_file_type_color(None, false)),
+        ext: ext_colors,
+    };
+
+    (files, colors)
+}
+
+/// Render the file type breakdown as a static HTML table
+fn file_type_table(stats: &AggregatedStats) -> String {
+    let mut rows = String::new();
+
+    for (category, category_stats) in stats.sorted_by_size() {
+        rows.push_str(&format!(
+            "<tr><td>{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td>\
+             <td><div class=\"bar\"><span style=\"width:{:.1}%\"></span></div></td>\
+             <td class=\"num\">{:.1}%</td></tr>\n",
+            escape_html(category.display_name()),
+            category_stats.count,
+            escape_html(&format_size(category_stats.total_size)),
+            category_stats.percentage,
+            category_stats.percentage,
+        ));
+    }
+
+    format!(
+        "<table class=\"types\"><thead><tr><th>Type</th><th>Files</th><th>Size</th>\
+         <th></th><th>Share</th></tr></thead><tbody>\n{}</tbody></table>",
+        rows
+    )
+}
+
+/// Export the FileTree as a self-contained HTML report
+///
+/// # Arguments
+/// * `tree` - The FileTree to export
+/// * `writer` - Output writer for the HTML document
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(std::io::Error)` on write failure or empty tree
+pub fn export_html(tree: &FileTree, writer: &mut impl Write) -> Result<(), std::io::Error> {
+    let root = tree
+        .root
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidInput, "empty tree"))?;
+    let root_node = tree.get_node(root).expect("Node must exist");
+
+    let (top_files, colors) = collect_files(tree, root);
+    let stats = AggregatedStats::from_tree(tree);
+
+    let data = ReportData {
+        root_path: root_node.path.to_string_lossy().to_string(),
+        tree: tree_to_report_node(tree, root),
+        top_files,
+        colors,
+    };
+
+    // `<` never occurs outside JSON strings, so escaping it keeps `</script>`
+    // in a filename from terminating the data block.
+    let json = serde_json::to_string(&data)
+        .map_err(std::io::Error::other)?
+        .replace('<', "\\u003c");
+
+    let root_path = escape_html(&data.root_path);
+    let generated = chrono::Local::now().format("%Y-%m-%d %H:%M:%S");
+
+    write!(
+        writer,
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>Data-X report: {root_path}</title>
+<style>{style}</style>
+</head>
+<body>
+<header>
+<h1>Data-X report</h1>
+<div class="meta"><code>{root_path}</code> &middot; {total_size} &middot; {total_files} files &middot; generated {generated} by data-x {version}</div>
+</header>
+<section>
+<h2>Treemap</h2>
+<nav id="breadcrumb"></nav>
+<div id="treemap"></div>
+</section>
+<section>
+<h2>File types</h2>
+{types}
+</section>
+<section>
+<h2>Largest files</h2>
+<table id="files"><thead><tr>
+<th data-key="path">Path</th><th data-key="size" class="num">Size</th><th data-key="ext">Type</th><th data-key="mtime">Modified</th>
+</tr></thead><tbody></tbody></table>
+</section>
+<script type="application/json" id="report-data">{json}</script>
+<script>{script}</script>
+</body>
+</html>
+"#,
+        root_path = root_path,
+        style = REPORT_CSS,
+        total_size = escape_html(&format_size(root_node.size)),
+        total_files = root_node.file_count,
+        generated = generated,
+        version = env!("CARGO_PKG_VERSION"),
+        types = file_type_table(&stats),
+        json = json,
+        script = REPORT_JS,
+    )
+}
+
+const REPORT_CSS: &str = r#"
+body { font-family: -apple-system, "Segoe UI", Helvetica, Arial, sans-serif; margin: 0; background: #1e1e24; color: #e0e0e0; }
+header, section { padding: 12px 24px; }
+h1 { margin: 0 0 4px; font-size: 22px; }
+h2 { font-size: 16px; margin: 8px 0; color: #9ab; }
+.meta { color: #aaa; font-size: 13px; }
+#breadcrumb { font-size: 13px; margin-bottom: 6px; }
+#breadcrumb a { color: #87cefa; cursor: pointer; }
+#treemap { position: relative; height: 600px; background: #111; overflow: hidden; }
+.cell { position: absolute; box-sizing: border-box; border: 1px solid #111; overflow: hidden; font-size: 11px; color: #000; padding: 1px 3px; white-space: nowrap; text-overflow: ellipsis; }
+.cell.dir { cursor: zoom-in; color: #fff; font-weight: bold; }
+.cell:hover { filter: brightness(1.2); }
+table { border-collapse: collapse; font-size: 13px; width: 100%; }
+th, td { padding: 3px 8px; border-bottom: 1px solid #333; text-align: left; }
+#files th { cursor: pointer; user-select: none; }
+td.num, th.num { text-align: right; }
+td { word-break: break-all; }
+.types { width: auto; }
+.bar { width: 200px; height: 10px; background: #333; }
+.bar span { display: block; height: 100%; background: #6495ed; }
+"#;
+
+const REPORT_JS: &str = r#"
+(function () {
+  var data = JSON.parse(document.getElementById('report-data').textContent);
+  var NEST_DEPTH = 2;
+
+  function formatSize(bytes) {
+    var units = ['B', 'KB', 'MB', 'GB', 'TB', 'PB'];
+    var i = 0;
+    while (bytes >= 1024 && i < units.length - 1) { bytes /= 1024; i++; }
+    return i === 0 ? bytes + ' B' : bytes.toFixed(1) + ' ' + units[i];
+  }
+
+  function colorOf(node) {
+    if (node.c) return data.colors.dir;
+    return (node.e && data.colors.ext[node.e]) || data.colors.other;
+  }
+
+  function pathOf(chain) {
+    var parts = chain.slice(1).map(function (n) { return n.n; });
+    return parts.length ? data.root_path.replace(/\/$/, '') + '/' + parts.join('/') : data.root_path;
+  }
+
+  function worst(areas, sum, side) {
+    var max = Math.max.apply(null, areas), min = Math.min.apply(null, areas);
+    var s2 = side * side, sum2 = sum * sum;
+    return Math.max(s2 * max / sum2, sum2 / (s2 * min));
+  }
+
+  // Squarified treemap layout (Bruls, Huizing, van Wijk)
+  function squarify(items, x, y, w, h) {
+    var out = [], total = 0;
+    items.forEach(function (it) { total += it.s; });
+    if (total <= 0 || w < 1 || h < 1) return out;
+    var scale = w * h / total, i = 0;
+    while (i < items.length) {
+      var side = Math.min(w, h);
+      var areas = [items[i].s * scale], sum = areas[0], j = i + 1;
+      while (j < items.length) {
+        var a = items[j].s * scale;
+        var next = areas.concat([a]);
+        if (worst(next, sum + a, side) > worst(areas, sum, side)) break;
+        areas = next; sum += a; j++;
+      }
+      var thick = sum / side, off = 0;
+      for (var k = i; k < j; k++) {
+        var len = areas[k - i] / thick;
+        if (w >= h) out.push({ node: items[k], x: x, y: y + off, w: thick, h: len });
+        else out.push({ node: items[k], x: x + off, y: y, w: len, h: thick });
+        off += len;
+      }
+      if (w >= h) { x += thick; w -= thick; } else { y += thick; h -= thick; }
+      i = j;
+    }
+    return out;
+  }
+
+  var box = document.getElementById('treemap');
+  var stack = [data.tree];
+
+  function layout(node, chain, x, y, w, h, depth) {
+    var kids = (node.c || []).filter(function (k) { return k.s > 0; })
+      .sort(function (a, b) { return b.s - a.s; });
+    squarify(kids, x, y, w, h).forEach(function (r) {
+      var childChain = chain.concat([r.node]);
+      var el = document.createElement('div');
+      el.className = 'cell' + (r.node.c ? ' dir' : '');
+      el.style.left = r.x + 'px';
+      el.style.top = r.y + 'px';
+      el.style.width = r.w + 'px';
+      el.style.height = r.h + 'px';
+      el.style.background = colorOf(r.node);
+      el.title = pathOf(childChain) + '\n' + formatSize(r.node.s);
+      if (r.w > 40 && r.h > 14) el.textContent = r.node.n;
+      box.appendChild(el);
+      if (r.node.c) {
+        el.addEventListener('click', function (ev) {
+          ev.stopPropagation();
+          stack = childChain;
+          render();
+        });
+        if (depth + 1 < NEST_DEPTH && r.w > 30 && r.h > 30) {
+          layout(r.node, childChain, r.x + 2, r.y + 16, r.w - 4, r.h - 18, depth + 1);
+        }
+      }
+    });
+  }
+
+  function render() {
+    box.innerHTML = '';
+    var node = stack[stack.length - 1];
+    layout(node, stack, 0, 0, box.clientWidth, box.clientHeight, 0);
+
+    var crumbs = document.getElementById('breadcrumb');
+    crumbs.innerHTML = '';
+    stack.forEach(function (n, i) {
+      if (i > 0) crumbs.appendChild(document.createTextNode(' / '));
+      var a = document.createElement('a');
+      a.textContent = i === 0 ? data.root_path : n.n;
+      a.addEventListener('click', function () { stack = stack.slice(0, i + 1); render(); });
+      crumbs.appendChild(a);
+    });
+    crumbs.appendChild(document.createTextNode(' (' + formatSize(node.s) + ')'));
+  }
+
+  var sortKey = 'size', sortDesc = true;
+
+  function renderFiles() {
+    var rows = data.top_files.slice().sort(function (a, b) {
+      var va = a[sortKey], vb = b[sortKey];
+      if (va === null) va = -1;
+      if (vb === null) vb = -1;
+      var cmp = va < vb ? -1 : va > vb ? 1 : 0;
+      return sortDesc ? -cmp : cmp;
+    });
+    var tbody = document.querySelector('#files tbody');
+    tbody.innerHTML = '';
+    rows.forEach(function (f) {
+      var tr = document.createElement('tr');
+      var cells = [
+        f.path,
+        formatSize(f.size),
+        f.ext,
+        f.mtime === null ? '' : new Date(f.mtime * 1000).toISOString().replace('T', ' ').slice(0, 19)
+      ];
+      cells.forEach(function (text, i) {
+        var td = document.createElement('td');
+        if (i === 1) td.className = 'num';
+        td.textContent = text;
+        tr.appendChild(td);
+      });
+      tbody.appendChild(tr);
+    });
+  }
+
+  document.querySelectorAll('#files th').forEach(function (th) {
+    th.addEventListener('click', function () {
+      var key = th.getAttribute('data-key');
+      if (key === sortKey) sortDesc = !sortDesc;
+      else { sortKey = key; sortDesc = key === 'size' || key === 'mtime'; }
+      renderFiles();
+    });
+  });
+
+  window.addEventListener('resize', render);
+  render();
+  renderFiles();
+})();
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::FileNode;
+    use std::path::PathBuf;
+
+    fn create_test_tree() -> FileTree {
+        let mut tree = FileTree::with_root(PathBuf::from("/test"));
+        let root = tree.root.unwrap();
+
+        let subdir = FileNode::new(PathBuf::from("/test/sub"), true);
+        let subdir_id = tree.add_child(root, subdir);
+
+        let hostile = FileNode::new(PathBuf::from("/test/sub/</script><b>x.rs"), false).with_size(500);
+        tree.add_child(subdir_id, hostile);
+
+        let video = FileNode::new(PathBuf::from("/test/movie.mp4"), false).with_size(1000);
+        tree.add_child(root, video);
+
+        tree.calculate_sizes();
+        tree
+    }
+
+    #[test]
+    fn test_export_html_is_self_contained() {
+        let tree = create_test_tree();
+
+        let mut buffer = Vec::new();
+        export_html(&tree, &mut buffer).unwrap();
+        let output = String::from_utf8(buffer).unwrap();
+
+        assert!(output.starts_with("<!DOCTYPE html>"));
+        assert!(!output.contains("src=\"http"));
+        assert!(!output.contains("href=\"http"));
+        // Type breakdown is rendered statically
+        assert!(output.contains("<td>Video</td>"));
+        assert!(output.contains("<td>Code</td>"));
+    }
+
+    #[test]
+    fn test_export_html_escapes_filenames() {
+        let tree = create_test_tree();
+
+        let mut buffer = Vec::new();
+        export_html(&tree, &mut buffer).unwrap();
+        let output = String::from_utf8(buffer).unwrap();
+
+        // Only the two real script blocks are closed
+        assert_eq!(output.matches("</script>").count(), 2);
+        assert!(output.contains("\\u003c/script>\\u003cb>x.rs"));
+    }
+
+    #[test]
+    fn test_report_data_round_trips() {
+        let tree = create_test_tree();
+        let root = tree.root.unwrap();
+
+        let (top_files, colors) = collect_files(&tree, root);
+        assert_eq!(top_files.len(), 2);
+        assert_eq!(top_files[0].path, "/test/movie.mp4");
+        assert!(colors.ext.contains_key("mp4"));
+
+        let node = tree_to_report_node(&tree, root);
+        assert_eq!(node.s, 1500);
+        assert_eq!(node.c.as_ref().unwrap().len(), 2);
+
+        // The embedded payload parses back to the same tree, hostile name included
+        let mut buffer = Vec::new();
+        export_html(&tree, &mut buffer).unwrap();
+        let output = String::from_utf8(buffer).unwrap();
+        let start_tag = r#"<script type="application/json" id="report-data">"#;
+        let start = output.find(start_tag).unwrap() + start_tag.len();
+        let end = start + output[start..].find("</script>").unwrap();
+        let data: serde_json::Value = serde_json::from_str(&output[start..end]).unwrap();
+
+        assert_eq!(data["tree"], serde_json::to_value(&node).unwrap());
+        assert_eq!(data["root_path"], "/test");
+        assert_eq!(data["top_files"][1]["path"], "/test/sub/</script><b>x.rs");
+    }
+
+    #[test]
+    fn test_export_html_empty_tree() {
+        let tree = FileTree::new();
+        let mut buffer = Vec::new();
+        assert!(export_html(&tree, &mut buffer).is_err());
+    }
+}
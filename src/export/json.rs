@@ -1,24 +1,203 @@
 use serde::{Deserialize, Serialize};
-use std::io::Write;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Cursor, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use crate::tree::{FileTree, NodeId};
+use crate::scanner::ScanOptions;
+use crate::tree::{FileNode, FileTree, NodeId};
+
+/// Version of the JSON export document, bumped on incompatible changes
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Published JSON Schema for the export document (docs/export.schema.json)
+pub const SCHEMA_URL: &str = "https://raw.githubusercontent.com/cassel/data-x/main/docs/export.schema.json";
 
 /// Represents a node in the exported tree structure
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ExportNode {
     pub path: String,
     pub name: String,
     pub size: u64,
     pub is_dir: bool,
+    /// Number of files in the subtree (1 for files)
+    #[serde(default)]
+    pub file_count: u64,
+    /// Modification time, whole seconds since the Unix epoch
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub mtime: Option<i64>,
+    /// Sub-second part of the modification time
+    #[serde(skip_serializing_if = "is_zero", default)]
+    pub mtime_nsec: u32,
+    /// Lowercase file extension, absent for directories
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub extension: Option<String>,
+    #[serde(default)]
+    pub hidden: bool,
+    #[serde(default)]
+    pub is_symlink: bool,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub symlink_target: Option<String>,
+    /// Excluded from size totals by the user
+    #[serde(default)]
+    pub excluded: bool,
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub children: Vec<ExportNode>,
 }
 
+fn is_zero(n: &u32) -> bool {
+    *n == 0
+}
+
+/// Scan options recorded in the export header
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportScanOptions {
+    pub max_depth: Option<usize>,
+    pub exclude_patterns: Vec<String>,
+    pub cross_mount: bool,
+    pub apparent_size: bool,
+}
+
+impl From<&ScanOptions> for ExportScanOptions {
+    fn from(options: &ScanOptions) -> Self {
+        Self {
+            max_depth: options.max_depth,
+            exclude_patterns: options.exclude_patterns.clone(),
+            cross_mount: options.cross_mount,
+            apparent_size: options.apparent_size,
+        }
+    }
+}
+
+impl ExportScanOptions {
+    /// Options for scanning `root` the way the recorded scan did
+    pub fn to_scan_options(&self, root: PathBuf) -> ScanOptions {
+        ScanOptions::new(root)
+            .with_max_depth(self.max_depth)
+            .with_exclude_patterns(self.exclude_patterns.clone())
+            .with_cross_mount(self.cross_mount)
+            .with_apparent_size(self.apparent_size)
+    }
+}
+
+/// Aggregate numbers for the whole scan
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportTotals {
+    pub size: u64,
+    pub files: u64,
+    pub dirs: u64,
+}
+
+/// Top-level metadata describing where and how the tree was produced
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportHeader {
+    pub schema_version: u32,
+    /// Version of data-x that wrote the document
+    pub data_x_version: String,
+    /// Scan root path
+    pub root: String,
+    /// Time the document was written (RFC 3339, UTC)
+    pub timestamp: String,
+    /// Options the scan ran with; None only for imported pre-versioning output
+    pub options: Option<ExportScanOptions>,
+    pub totals: ExportTotals,
+}
+
+/// Payload of the export: the full tree or a flat list of the largest entries
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ExportBody {
+    #[serde(rename = "tree")]
+    Tree(Option<ExportNode>),
+    #[serde(rename = "entries")]
+    Entries(Vec<ExportNode>),
+}
+
+/// Versioned JSON export document
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportDocument {
+    #[serde(rename = "$schema", default)]
+    pub schema: String,
+    pub header: ExportHeader,
+    #[serde(flatten)]
+    pub body: ExportBody,
+}
+
 /// Options for customizing the JSON export
+#[derive(Default)]
 pub struct ExportOptions {
     /// If Some(n), flatten tree and return top n items by size
     /// If None, export full tree structure
     pub top_n: Option<usize>,
+    /// Options the tree was scanned with, recorded in the header
+    pub scan_options: Option<ScanOptions>,
+}
+
+/// Split a SystemTime into (seconds, nanoseconds) relative to the Unix epoch
+pub fn to_epoch(time: SystemTime) -> (i64, u32) {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(d) => (d.as_secs() as i64, d.subsec_nanos()),
+        Err(e) => {
+            // Before 1970: keep nanoseconds non-negative
+            let d = e.duration();
+            if d.subsec_nanos() == 0 {
+                (-(d.as_secs() as i64), 0)
+            } else {
+                (-(d.as_secs() as i64) - 1, 1_000_000_000 - d.subsec_nanos())
+            }
+        }
+    }
+}
+
+pub fn from_epoch(secs: i64, nsec: u32) -> SystemTime {
+    let nsec = Duration::from_nanos(nsec.min(999_999_999) as u64);
+    if secs >= 0 {
+        UNIX_EPOCH + Duration::from_secs(secs as u64) + nsec
+    } else {
+        UNIX_EPOCH - Duration::from_secs(secs.unsigned_abs()) + nsec
+    }
+}
+
+/// Convert a single FileNode to an ExportNode without children
+pub fn node_to_export(node: &FileNode) -> ExportNode {
+    let (mtime, mtime_nsec) = match node.modified.map(to_epoch) {
+        Some((secs, nsec)) => (Some(secs), nsec),
+        None => (None, 0),
+    };
+
+    ExportNode {
+        path: node.path.to_string_lossy().to_string(),
+        name: node.name.clone(),
+        size: node.size,
+        is_dir: node.is_dir,
+        file_count: node.file_count,
+        mtime,
+        mtime_nsec,
+        extension: node.extension.clone(),
+        hidden: node.is_hidden,
+        is_symlink: node.is_symlink,
+        symlink_target: node
+            .symlink_target
+            .as_ref()
+            .map(|t| t.to_string_lossy().to_string()),
+        excluded: node.excluded,
+        children: Vec::new(),
+    }
+}
+
+/// Convert an ExportNode (without its children) back to a FileNode
+pub fn export_to_node(export: &ExportNode) -> FileNode {
+    let mut node = FileNode::new(PathBuf::from(&export.path), export.is_dir);
+    node.name = export.name.clone();
+    node.name_lower = export.name.to_lowercase();
+    node.size = export.size;
+    node.file_count = export.file_count;
+    node.modified = export.mtime.map(|secs| from_epoch(secs, export.mtime_nsec));
+    node.extension = export.extension.clone();
+    node.is_hidden = export.hidden;
+    node.is_symlink = export.is_symlink || export.symlink_target.is_some();
+    node.symlink_target = export.symlink_target.as_ref().map(PathBuf::from);
+    node.excluded = export.excluded;
+    node
 }
 
 /// Recursively convert a FileTree node to an ExportNode
@@ -32,33 +211,86 @@ pub fn tree_to_export_node(tree: &FileTree, node_id: NodeId) -> ExportNode {
         .collect();
 
     ExportNode {
-        path: node.path.to_string_lossy().to_string(),
-        name: node.name.clone(),
-        size: node.size,
-        is_dir: node.is_dir,
         children,
+        ..node_to_export(node)
+    }
+}
+
+/// Rebuild a FileTree from an exported node and its children.
+///
+/// Sizes and file counts are taken as exported rather than recalculated,
+/// so excluded entries keep the totals they were written with.
+pub fn export_node_to_tree(root: &ExportNode) -> FileTree {
+    let mut tree = FileTree::with_root(PathBuf::from(&root.path));
+    let root_id = tree.root.expect("Tree was created with a root");
+
+    if let Some(node) = tree.get_node_mut(root_id) {
+        *node = export_to_node(root);
+    }
+
+    let mut stack: Vec<(NodeId, &ExportNode)> = vec![(root_id, root)];
+    while let Some((parent_id, parent)) = stack.pop() {
+        for child in &parent.children {
+            let child_id = tree.add_child(parent_id, export_to_node(child));
+            stack.push((child_id, child));
+        }
     }
+
+    tree
 }
 
 /// Flatten a tree into a vector of ExportNodes (without children)
 fn flatten_tree(tree: &FileTree, node_id: NodeId, result: &mut Vec<ExportNode>) {
     let node = tree.get_node(node_id).expect("Node must exist");
 
-    result.push(ExportNode {
-        path: node.path.to_string_lossy().to_string(),
-        name: node.name.clone(),
-        size: node.size,
-        is_dir: node.is_dir,
-        children: Vec::new(),
-    });
+    result.push(node_to_export(node));
 
     for child_id in tree.get_children(node_id) {
         flatten_tree(tree, child_id, result);
     }
 }
 
+/// All node ids of the tree in depth-first order
+fn root_descendants(tree: &FileTree) -> Vec<NodeId> {
+    tree.root
+        .map(|root| root.descendants(&tree.arena).collect())
+        .unwrap_or_default()
+}
+
+/// Build the export header for a tree
+pub fn build_header(tree: &FileTree, options: &ExportOptions) -> ExportHeader {
+    let root_node = tree.root.and_then(|root| tree.get_node(root));
+
+    let dirs = root_descendants(tree)
+        .into_iter()
+        .filter(|&id| tree.get_node(id).is_some_and(|n| n.is_dir))
+        .count() as u64;
+
+    let root = match (root_node, &options.scan_options) {
+        (Some(node), _) => node.path.to_string_lossy().to_string(),
+        (None, Some(scan_options)) => scan_options.root_path.to_string_lossy().to_string(),
+        (None, None) => String::new(),
+    };
+
+    ExportHeader {
+        schema_version: SCHEMA_VERSION,
+        data_x_version: env!("CARGO_PKG_VERSION").to_string(),
+        root,
+        timestamp: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        options: options.scan_options.as_ref().map(ExportScanOptions::from),
+        totals: ExportTotals {
+            size: root_node.map(|n| n.size).unwrap_or(0),
+            files: root_node.map(|n| n.file_count).unwrap_or(0),
+            dirs,
+        },
+    }
+}
+
 /// Export the FileTree to JSON format
 ///
+/// Writes a versioned document with a `header` and either the full `tree`
+/// or, with `top_n`, a flat list of the largest `entries`.
+///
 /// # Arguments
 /// * `tree` - The FileTree to export
 /// * `options` - Export options (top_n for flattening, None for full tree)
@@ -72,40 +304,134 @@ pub fn export_json(
     options: &ExportOptions,
     writer: &mut impl Write,
 ) -> Result<(), std::io::Error> {
-    let root = match tree.root {
-        Some(root) => root,
-        None => {
-            // Empty tree - output empty object or array based on options
-            return if options.top_n.is_some() {
-                serde_json::to_writer_pretty(writer, &Vec::<ExportNode>::new())
-                    .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))
-            } else {
-                writer.write_all(b"null")
-            };
-        }
-    };
-
-    match options.top_n {
-        Some(n) => {
+    let body = match (tree.root, options.top_n) {
+        (None, Some(_)) => ExportBody::Entries(Vec::new()),
+        (None, None) => ExportBody::Tree(None),
+        (Some(root), Some(n)) => {
             // Flatten tree, sort by size descending, take top n
             let mut flattened = Vec::new();
             flatten_tree(tree, root, &mut flattened);
 
-            // Sort by size descending
-            flattened.sort_by(|a, b| b.size.cmp(&a.size));
+            flattened.sort_by_key(|node| std::cmp::Reverse(node.size));
+            flattened.truncate(n);
+
+            ExportBody::Entries(flattened)
+        }
+        (Some(root), None) => ExportBody::Tree(Some(tree_to_export_node(tree, root))),
+    };
+
+    let document = ExportDocument {
+        schema: SCHEMA_URL.to_string(),
+        header: build_header(tree, options),
+        body,
+    };
+
+    serde_json::to_writer_pretty(writer, &document).map_err(std::io::Error::other)
+}
+
+/// A tree read back from JSON export output
+#[derive(Debug)]
+pub struct ImportedScan {
+    /// Header of the document; None for pre-versioning output
+    #[allow(dead_code)]
+    pub header: Option<ExportHeader>,
+    pub tree: FileTree,
+}
+
+/// Reject headers written by a newer, incompatible data-x
+pub fn check_schema_version(header: &serde_json::Value) -> Result<(), std::io::Error> {
+    let version = header["schema_version"].as_u64().unwrap_or(0);
+    if version > SCHEMA_VERSION as u64 {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!(
+                "unsupported export schema version {} (this data-x reads up to {})",
+                version, SCHEMA_VERSION
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Parse `data-x --json` output back into a FileTree.
+///
+/// Accepts the versioned document as well as the bare node tree written by
+/// data-x before the schema was versioned. Flat `--top` listings cannot be
+/// turned back into a tree and are rejected.
+pub fn import_json(json: &str) -> Result<ImportedScan, std::io::Error> {
+    import_json_value(serde_json::from_str(json)?)
+}
+
+/// Like [`import_json`], parsing straight from `reader` as bytes arrive.
+pub fn read_json(reader: impl std::io::Read) -> Result<ImportedScan, std::io::Error> {
+    import_json_value(serde_json::from_reader(reader)?)
+}
 
-            // Take top n
-            let top_items: Vec<_> = flattened.into_iter().take(n).collect();
+fn import_json_value(value: serde_json::Value) -> Result<ImportedScan, std::io::Error> {
+    let invalid = |msg: String| std::io::Error::new(std::io::ErrorKind::InvalidData, msg);
 
-            serde_json::to_writer_pretty(writer, &top_items)
-                .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))
+    if value.get("header").is_none() {
+        // Legacy output: the root node itself, or null for an empty tree
+        if value.is_null() {
+            return Ok(ImportedScan { header: None, tree: FileTree::new() });
+        }
+        if !value.is_object() {
+            return Err(invalid("expected a data-x tree, not a --top listing".to_string()));
         }
-        None => {
-            // Export full tree structure
-            let export_tree = tree_to_export_node(tree, root);
-            serde_json::to_writer_pretty(writer, &export_tree)
-                .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))
+        let root: ExportNode = serde_json::from_value(value)?;
+        let mut tree = export_node_to_tree(&root);
+
+        // Old output had no file counts; nothing could be excluded either,
+        // so totals can safely be recomputed from the files
+        let ids: Vec<NodeId> = root_descendants(&tree);
+        for id in ids {
+            if let Some(node) = tree.get_node_mut(id) {
+                if !node.is_dir {
+                    node.file_count = 1;
+                }
+            }
+        }
+        tree.calculate_sizes();
+
+        return Ok(ImportedScan { header: None, tree });
+    }
+
+    check_schema_version(&value["header"])?;
+
+    let document: ExportDocument = serde_json::from_value(value)?;
+    let tree = match document.body {
+        ExportBody::Tree(Some(ref root)) => export_node_to_tree(root),
+        ExportBody::Tree(None) => FileTree::new(),
+        ExportBody::Entries(_) => {
+            return Err(invalid("expected a data-x tree, not a --top listing".to_string()))
         }
+    };
+
+    Ok(ImportedScan { header: Some(document.header), tree })
+}
+
+/// Read a saved scan written by `--json` or `--ndjson`.
+///
+/// The format is detected from the content: an NDJSON stream starts with a
+/// header-only line, a JSON document does not.
+pub fn load_scan(path: &Path) -> Result<ImportedScan, std::io::Error> {
+    let mut reader = BufReader::new(File::open(path)?);
+
+    let mut first_line = String::new();
+    while reader.read_line(&mut first_line)? > 0 && first_line.trim().is_empty() {
+        first_line.clear();
+    }
+    let is_ndjson = serde_json::from_str::<serde_json::Value>(&first_line)
+        .map(|value| value.get("header").is_some() && value.get("tree").is_none() && value.get("entries").is_none())
+        .unwrap_or(false);
+
+    if is_ndjson {
+        // Stream the entries instead of holding the whole file in memory
+        super::ndjson::import_ndjson(Cursor::new(first_line).chain(reader))
+    } else {
+        let mut content = first_line;
+        reader.read_to_string(&mut content)?;
+        import_json(&content)
     }
 }
 
@@ -119,8 +445,10 @@ mod tests {
         let mut tree = FileTree::with_root(PathBuf::from("/test"));
         let root = tree.root.unwrap();
 
-        let file1 = FileNode::new(PathBuf::from("/test/large.txt"), false).with_size(1000);
-        let file2 = FileNode::new(PathBuf::from("/test/small.txt"), false).with_size(100);
+        let file1 = FileNode::new(PathBuf::from("/test/large.txt"), false)
+            .with_size(1000)
+            .with_modified(UNIX_EPOCH + Duration::new(1_700_000_000, 123_456_789));
+        let file2 = FileNode::new(PathBuf::from("/test/.small.txt"), false).with_size(100);
 
         let subdir = FileNode::new(PathBuf::from("/test/subdir"), true);
         let subdir_id = tree.add_child(root, subdir);
@@ -128,13 +456,24 @@ mod tests {
         let file3 = FileNode::new(PathBuf::from("/test/subdir/medium.txt"), false).with_size(500);
         tree.add_child(subdir_id, file3);
 
+        let link = FileNode::new(PathBuf::from("/test/subdir/link"), false)
+            .with_symlink(PathBuf::from("../large.txt"));
+        tree.add_child(subdir_id, link);
+
         tree.add_child(root, file1);
-        tree.add_child(root, file2);
+        let file2_id = tree.add_child(root, file2);
+        tree.get_node_mut(file2_id).unwrap().excluded = true;
 
         tree.calculate_sizes();
         tree
     }
 
+    fn export_to_value(tree: &FileTree, options: &ExportOptions) -> serde_json::Value {
+        let mut buffer = Vec::new();
+        export_json(tree, options, &mut buffer).unwrap();
+        serde_json::from_slice(&buffer).unwrap()
+    }
+
     #[test]
     fn test_tree_to_export_node() {
         let tree = create_test_tree();
@@ -146,58 +485,167 @@ mod tests {
         assert_eq!(export.path, "/test");
         assert!(export.is_dir);
         assert_eq!(export.children.len(), 3);
+
+        let large = &export.children[1];
+        assert_eq!(large.mtime, Some(1_700_000_000));
+        assert_eq!(large.mtime_nsec, 123_456_789);
+        assert_eq!(large.extension.as_deref(), Some("txt"));
+        assert!(export.children[2].hidden && export.children[2].excluded);
+        assert_eq!(export.children[0].children[1].symlink_target.as_deref(), Some("../large.txt"));
     }
 
     #[test]
     fn test_export_json_full_tree() {
         let tree = create_test_tree();
-        let options = ExportOptions { top_n: None };
-
-        let mut buffer = Vec::new();
-        export_json(&tree, &options, &mut buffer).unwrap();
-
-        let output = String::from_utf8(buffer).unwrap();
-        assert!(output.contains("\"name\": \"test\""));
-        assert!(output.contains("\"children\""));
+        let options = ExportOptions {
+            top_n: None,
+            scan_options: Some(ScanOptions::new(PathBuf::from("/test")).with_max_depth(Some(4))),
+        };
+
+        let value = export_to_value(&tree, &options);
+
+        assert_eq!(value["$schema"], SCHEMA_URL);
+        assert_eq!(value["header"]["schema_version"], SCHEMA_VERSION);
+        assert_eq!(value["header"]["root"], "/test");
+        assert_eq!(value["header"]["options"]["max_depth"], 4);
+        assert_eq!(value["header"]["totals"]["size"], 1500);
+        assert_eq!(value["header"]["totals"]["files"], 3);
+        assert_eq!(value["header"]["totals"]["dirs"], 2);
+        assert_eq!(value["tree"]["name"], "test");
+        assert_eq!(value["tree"]["children"].as_array().unwrap().len(), 3);
     }
 
     #[test]
     fn test_export_json_top_n() {
         let tree = create_test_tree();
-        let options = ExportOptions { top_n: Some(2) };
+        let options = ExportOptions { top_n: Some(2), ..Default::default() };
 
-        let mut buffer = Vec::new();
-        export_json(&tree, &options, &mut buffer).unwrap();
+        let value = export_to_value(&tree, &options);
+        let entries: Vec<ExportNode> = serde_json::from_value(value["entries"].clone()).unwrap();
 
-        let output = String::from_utf8(buffer).unwrap();
-        // Should be an array with 2 items
-        let parsed: Vec<ExportNode> = serde_json::from_str(&output).unwrap();
-        assert_eq!(parsed.len(), 2);
-        // First item should be largest (root with total size 1600)
-        assert!(parsed[0].size >= parsed[1].size);
+        assert!(value.get("tree").is_none());
+        assert_eq!(entries.len(), 2);
+        // First item should be largest (root with total size 1500)
+        assert!(entries[0].size >= entries[1].size);
+        assert!(entries.iter().all(|e| e.children.is_empty()));
     }
 
     #[test]
     fn test_export_empty_tree() {
         let tree = FileTree::new();
-        let options = ExportOptions { top_n: None };
 
-        let mut buffer = Vec::new();
-        export_json(&tree, &options, &mut buffer).unwrap();
+        let value = export_to_value(&tree, &ExportOptions::default());
+        assert!(value["tree"].is_null());
+        assert_eq!(value["header"]["totals"]["files"], 0);
 
-        let output = String::from_utf8(buffer).unwrap();
-        assert_eq!(output, "null");
+        let value = export_to_value(&tree, &ExportOptions { top_n: Some(10), ..Default::default() });
+        assert_eq!(value["entries"], serde_json::json!([]));
     }
 
     #[test]
-    fn test_export_empty_tree_top_n() {
-        let tree = FileTree::new();
-        let options = ExportOptions { top_n: Some(10) };
+    fn test_import_round_trip() {
+        let tree = create_test_tree();
+        let mut buffer = Vec::new();
+        export_json(&tree, &ExportOptions::default(), &mut buffer).unwrap();
+
+        let imported = import_json(std::str::from_utf8(&buffer).unwrap()).unwrap();
+        assert_eq!(imported.header.unwrap().totals.size, 1500);
 
+        let original = tree_to_export_node(&tree, tree.root.unwrap());
+        let restored = tree_to_export_node(&imported.tree, imported.tree.root.unwrap());
+        assert_eq!(original, restored);
+
+        let link = imported.tree.find_by_path(&PathBuf::from("/test/subdir/link")).unwrap();
+        assert!(imported.tree.get_node(link).unwrap().is_symlink);
+
+        // Re-exporting a loaded scan keeps the options it was made with
+        let scan_options = ScanOptions::new(PathBuf::from("/test"))
+            .with_max_depth(Some(2))
+            .with_exclude_patterns(vec!["*.iso".to_string()])
+            .with_apparent_size(true);
+        let options = ExportOptions { top_n: None, scan_options: Some(scan_options.clone()) };
         let mut buffer = Vec::new();
         export_json(&tree, &options, &mut buffer).unwrap();
+        let header = import_json(std::str::from_utf8(&buffer).unwrap()).unwrap().header.unwrap();
+        assert_eq!(header.options.unwrap().to_scan_options(PathBuf::from(&header.root)), scan_options);
+    }
+
+    #[test]
+    fn test_import_legacy_and_errors() {
+        let legacy = r#"{"path": "/old", "name": "old", "size": 10, "is_dir": true,
+            "children": [{"path": "/old/a.log", "name": "a.log", "size": 10, "is_dir": false}]}"#;
+        let imported = import_json(legacy).unwrap();
+        assert!(imported.header.is_none());
+        assert_eq!(imported.tree.node_count(), 2);
+        assert_eq!(imported.tree.total_file_count(), 1);
+
+        assert!(import_json("null").unwrap().tree.root.is_none());
+        assert!(import_json("[]").is_err());
+
+        let future = r#"{"header": {"schema_version": 99}, "tree": null}"#;
+        assert!(import_json(future).unwrap_err().to_string().contains("99"));
+    }
+
+    #[test]
+    fn test_epoch_conversion_before_1970() {
+        let time = UNIX_EPOCH - Duration::new(10, 250_000_000);
+        let (secs, nsec) = to_epoch(time);
+        assert_eq!((secs, nsec), (-11, 750_000_000));
+        assert_eq!(from_epoch(secs, nsec), time);
+    }
+
+    #[test]
+    fn test_published_schema_covers_output() {
+        let schema: serde_json::Value =
+            serde_json::from_str(include_str!("../../docs/export.schema.json")).unwrap();
+        assert_eq!(schema["$id"], SCHEMA_URL);
+
+        let tree = create_test_tree();
+        let options = ExportOptions {
+            top_n: None,
+            scan_options: Some(ScanOptions::new(PathBuf::from("/test"))),
+        };
+        let value = export_to_value(&tree, &options);
+
+        // Every emitted key is declared, and every required key is emitted
+        let check = |object: &serde_json::Value, definition: &serde_json::Value| {
+            let properties = definition["properties"].as_object().unwrap();
+            for key in object.as_object().unwrap().keys() {
+                assert!(properties.contains_key(key), "undeclared key {}", key);
+            }
+            for key in definition["required"].as_array().unwrap() {
+                assert!(object.get(key.as_str().unwrap()).is_some(), "missing key {}", key);
+            }
+        };
+
+        check(&value, &schema);
+        check(&value["header"], &schema["$defs"]["header"]);
+        check(&value["header"]["options"], &schema["$defs"]["options"]);
+        check(&value["header"]["totals"], &schema["$defs"]["totals"]);
+        check(&value["tree"]["children"][1], &schema["$defs"]["node"]);
+        check(&value["tree"]["children"][0]["children"][1], &schema["$defs"]["node"]);
+    }
+
+    #[test]
+    fn test_load_scan_detects_format() {
+        let tree = create_test_tree();
+        let dir = tempfile::tempdir().unwrap();
+
+        let json_path = dir.path().join("scan.json");
+        let mut file = std::fs::File::create(&json_path).unwrap();
+        export_json(&tree, &ExportOptions::default(), &mut file).unwrap();
+
+        let ndjson_path = dir.path().join("scan.txt");
+        let mut file = std::fs::File::create(&ndjson_path).unwrap();
+        crate::export::export_ndjson(&tree, &ExportOptions::default(), &mut file).unwrap();
+
+        let expected = tree_to_export_node(&tree, tree.root.unwrap());
+        for path in [&json_path, &ndjson_path] {
+            let imported = load_scan(path).unwrap();
+            assert!(imported.header.is_some());
+            assert_eq!(tree_to_export_node(&imported.tree, imported.tree.root.unwrap()), expected);
+        }
 
-        let output = String::from_utf8(buffer).unwrap();
-        assert_eq!(output, "[]");
+        assert!(load_scan(&dir.path().join("missing.json")).is_err());
     }
 }
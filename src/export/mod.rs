@@ -1,6 +1,27 @@
+mod csv;
+mod du;
+mod html;
 mod json;
+mod ndjson;
+mod progress;
+mod prometheus;
+#[cfg(feature = "sqlite")]
+mod sqlite;
 
-pub use json::{export_json, ExportOptions};
+pub use csv::{export_csv, Column, CsvOptions, Delimiter};
+pub use du::{export_du, DuOptions};
+pub use html::export_html;
+pub use json::{build_header, export_json, from_epoch, load_scan, read_json, to_epoch, ExportOptions};
+pub use ndjson::{export_ndjson, NdjsonTreeBuilder};
+pub use progress::{scan_headless, write_progress_json, ProgressRecord};
+pub use prometheus::{export_prometheus, MetricsOptions};
+#[cfg(feature = "sqlite")]
+pub use sqlite::export_sqlite;
 // Re-exported for potential future use
 #[allow(unused_imports)]
-pub use json::{tree_to_export_node, ExportNode};
+pub use json::{
+    export_node_to_tree, export_to_node, import_json, node_to_export, tree_to_export_node, ExportBody, ExportDocument,
+    ExportHeader, ExportNode, ImportedScan, SCHEMA_URL, SCHEMA_VERSION,
+};
+#[allow(unused_imports)]
+pub use ndjson::{import_ndjson, NdjsonRecord};
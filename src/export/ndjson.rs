@@ -0,0 +1,297 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+
+use crate::tree::{FileNode, FileTree, NodeId};
+
+use super::json::{build_header, check_schema_version, export_to_node, node_to_export, SCHEMA_URL};
+use super::{ExportHeader, ExportNode, ExportOptions, ImportedScan};
+
+/// First line of an NDJSON stream
+#[derive(Serialize, Deserialize)]
+struct NdjsonHeader {
+    #[serde(rename = "$schema")]
+    schema: String,
+    header: ExportHeader,
+}
+
+/// One node per line, written parents before children
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NdjsonRecord {
+    /// Unique id of the node within the stream
+    pub id: u64,
+    /// Id of the parent record, None for the root
+    pub parent_id: Option<u64>,
+    pub parent_path: Option<String>,
+    #[serde(flatten)]
+    pub node: ExportNode,
+}
+
+fn invalid_data(msg: String) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, msg)
+}
+
+/// Export the FileTree as newline-delimited JSON
+///
+/// The first line holds the same header as `--json`; every following line
+/// is one node with the id and path of its parent. Nodes are written while
+/// walking the arena, so memory use does not grow with the tree and deep
+/// trees cannot overflow the stack.
+///
+/// # Arguments
+/// * `tree` - The FileTree to export
+/// * `options` - Export options (`top_n` is not supported)
+/// * `writer` - Output writer, ideally buffered
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(std::io::Error)` on write failure or when `top_n` is set
+pub fn export_ndjson(
+    tree: &FileTree,
+    options: &ExportOptions,
+    writer: &mut impl Write,
+) -> Result<(), std::io::Error> {
+    if options.top_n.is_some() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "--top is not supported with NDJSON output",
+        ));
+    }
+
+    let header = NdjsonHeader {
+        schema: SCHEMA_URL.to_string(),
+        header: build_header(tree, options),
+    };
+    serde_json::to_writer(&mut *writer, &header).map_err(std::io::Error::other)?;
+    writer.write_all(b"\n")?;
+
+    let root = match tree.root {
+        Some(root) => root,
+        None => return Ok(()),
+    };
+
+    for node_id in root.descendants(&tree.arena) {
+        let node = tree.get_node(node_id).expect("Node must exist");
+        let parent = if node_id == root { None } else { tree.get_parent(node_id) };
+
+        let record = NdjsonRecord {
+            id: usize::from(node_id) as u64,
+            parent_id: parent.map(|p| usize::from(p) as u64),
+            parent_path: parent
+                .and_then(|p| tree.get_node(p))
+                .map(|p| p.path.to_string_lossy().to_string()),
+            node: node_to_export(node),
+        };
+
+        serde_json::to_writer(&mut *writer, &record).map_err(std::io::Error::other)?;
+        writer.write_all(b"\n")?;
+    }
+
+    Ok(())
+}
+
+/// Rebuilds a FileTree from NDJSON lines as they arrive.
+#[derive(Default)]
+pub struct NdjsonTreeBuilder {
+    header: Option<ExportHeader>,
+    tree: FileTree,
+    ids: HashMap<u64, NodeId>,
+    line_number: usize,
+}
+
+impl NdjsonTreeBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed one line of the stream; blank lines are ignored.
+    ///
+    /// # Returns
+    /// * `Ok(Some(node))` with the node a record line added
+    /// * `Ok(None)` for the header and blank lines
+    /// * `Err(std::io::Error)` for malformed or out-of-order records
+    pub fn push_line(&mut self, line: &str) -> Result<Option<&FileNode>, std::io::Error> {
+        self.line_number += 1;
+        let line_number = self.line_number;
+        let at_line = |e: std::io::Error| invalid_data(format!("line {}: {}", line_number, e));
+
+        if line.trim().is_empty() {
+            return Ok(None);
+        }
+
+        let value: serde_json::Value = serde_json::from_str(line).map_err(|e| at_line(e.into()))?;
+
+        if value.get("header").is_some() {
+            if self.header.is_some() || !self.ids.is_empty() {
+                return Err(at_line(invalid_data("unexpected header record".to_string())));
+            }
+            check_schema_version(&value["header"]).map_err(at_line)?;
+            let header: NdjsonHeader = serde_json::from_value(value).map_err(|e| at_line(e.into()))?;
+            self.header = Some(header.header);
+            return Ok(None);
+        }
+
+        let record: NdjsonRecord = serde_json::from_value(value).map_err(|e| at_line(e.into()))?;
+        if self.ids.contains_key(&record.id) {
+            return Err(at_line(invalid_data(format!("duplicate record id {}", record.id))));
+        }
+
+        let node = export_to_node(&record.node);
+        let node_id = match record.parent_id {
+            None => {
+                if self.tree.root.is_some() {
+                    return Err(at_line(invalid_data("more than one root record".to_string())));
+                }
+                self.tree = FileTree::with_root(PathBuf::from(&record.node.path));
+                let root_id = self.tree.root.expect("Tree was created with a root");
+                if let Some(root) = self.tree.get_node_mut(root_id) {
+                    *root = node;
+                }
+                root_id
+            }
+            Some(parent_id) => {
+                let parent = *self.ids.get(&parent_id).ok_or_else(|| {
+                    at_line(invalid_data(format!("record {} has unknown parent {}", record.id, parent_id)))
+                })?;
+                self.tree.add_child(parent, node)
+            }
+        };
+
+        self.ids.insert(record.id, node_id);
+        Ok(self.tree.get_node(node_id))
+    }
+
+    pub fn finish(self) -> ImportedScan {
+        ImportedScan {
+            header: self.header,
+            tree: self.tree,
+        }
+    }
+}
+
+/// Read a `data-x --ndjson` stream back into a FileTree.
+pub fn import_ndjson(reader: impl BufRead) -> Result<ImportedScan, std::io::Error> {
+    let mut builder = NdjsonTreeBuilder::new();
+    for line in reader.lines() {
+        builder.push_line(&line?)?;
+    }
+    Ok(builder.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::tree_to_export_node;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn create_test_tree() -> FileTree {
+        let mut tree = FileTree::with_root(PathBuf::from("/data"));
+        let root = tree.root.unwrap();
+
+        let photos = tree.add_child(root, FileNode::new(PathBuf::from("/data/photos"), true));
+        tree.add_child(
+            photos,
+            FileNode::new(PathBuf::from("/data/photos/cat.JPG"), false)
+                .with_size(3000)
+                .with_modified(UNIX_EPOCH + Duration::new(1_650_000_000, 42)),
+        );
+        tree.add_child(root, FileNode::new(PathBuf::from("/data/notes\nv2.md"), false).with_size(12));
+        tree.add_child(root, FileNode::new(PathBuf::from("/data/latest"), false).with_symlink(PathBuf::from("photos")));
+
+        tree.calculate_sizes();
+        tree
+    }
+
+    fn export_to_string(tree: &FileTree) -> String {
+        let mut buffer = Vec::new();
+        export_ndjson(tree, &ExportOptions::default(), &mut buffer).unwrap();
+        String::from_utf8(buffer).unwrap()
+    }
+
+    #[test]
+    fn test_one_record_per_line() {
+        let tree = create_test_tree();
+        let output = export_to_string(&tree);
+        let lines: Vec<&str> = output.lines().collect();
+
+        // Header + 5 nodes; the newline in a file name stays escaped
+        assert_eq!(lines.len(), 6);
+
+        let header: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(header["header"]["totals"]["size"], 3012);
+
+        let root: NdjsonRecord = serde_json::from_str(lines[1]).unwrap();
+        assert_eq!(root.parent_id, None);
+        assert_eq!(root.node.path, "/data");
+
+        let photo: NdjsonRecord = serde_json::from_str(lines[3]).unwrap();
+        assert_eq!(photo.node.name, "cat.JPG");
+        assert_eq!(photo.parent_path.as_deref(), Some("/data/photos"));
+        let photos: NdjsonRecord = serde_json::from_str(lines[2]).unwrap();
+        assert_eq!(photo.parent_id, Some(photos.id));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let tree = create_test_tree();
+        let output = export_to_string(&tree);
+
+        let imported = import_ndjson(output.as_bytes()).unwrap();
+        assert_eq!(imported.header.unwrap().root, "/data");
+        assert_eq!(
+            tree_to_export_node(&tree, tree.root.unwrap()),
+            tree_to_export_node(&imported.tree, imported.tree.root.unwrap())
+        );
+    }
+
+    #[test]
+    fn test_deep_tree_is_iterative() {
+        let mut tree = FileTree::with_root(PathBuf::from("/d"));
+        let mut parent = tree.root.unwrap();
+        let mut path = PathBuf::from("/d");
+        for _ in 0..2_000 {
+            path.push("x");
+            parent = tree.add_child(parent, FileNode::new(path.clone(), true));
+        }
+
+        let output = export_to_string(&tree);
+        let imported = import_ndjson(output.as_bytes()).unwrap();
+
+        assert_eq!(imported.tree.node_count(), 2_001);
+        let deepest = imported.tree.find_by_path(&path).unwrap();
+        assert_eq!(imported.tree.get_node(deepest).unwrap().name, "x");
+    }
+
+    #[test]
+    fn test_empty_tree() {
+        let output = export_to_string(&FileTree::new());
+        assert_eq!(output.lines().count(), 1);
+
+        let imported = import_ndjson(output.as_bytes()).unwrap();
+        assert!(imported.header.is_some());
+        assert!(imported.tree.root.is_none());
+    }
+
+    #[test]
+    fn test_rejects_top_n() {
+        let options = ExportOptions { top_n: Some(5), ..Default::default() };
+        let err = export_ndjson(&create_test_tree(), &options, &mut Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_import_errors() {
+        let orphan = r#"{"id": 7, "parent_id": 3, "parent_path": "/x", "path": "/x/y", "name": "y", "size": 1, "is_dir": false}"#;
+        let err = import_ndjson(orphan.as_bytes()).unwrap_err();
+        assert!(err.to_string().starts_with("line 1:"));
+        assert!(err.to_string().contains("unknown parent 3"));
+
+        let future = "{\"header\": {\"schema_version\": 99}}\n";
+        assert!(import_ndjson(future.as_bytes()).unwrap_err().to_string().contains("99"));
+
+        let two_roots = r#"{"id": 1, "parent_id": null, "parent_path": null, "path": "/a", "name": "a", "size": 0, "is_dir": true}
+{"id": 2, "parent_id": null, "parent_path": null, "path": "/b", "name": "b", "size": 0, "is_dir": true}"#;
+        assert!(import_ndjson(two_roots.as_bytes()).unwrap_err().to_string().contains("line 2"));
+    }
+}
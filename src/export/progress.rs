@@ -0,0 +1,187 @@
+//! Machine-readable scan progress for `--progress-json`.
+//!
+//! One JSON object per line on stderr, so a caller driving data-x over a
+//! pipe (such as a remote scan) can show progress while stdout carries
+//! the tree.
+
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::thread;
+
+use crate::scanner::ScanProgress;
+
+/// One progress event
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ProgressRecord {
+    Scanning {
+        path: String,
+        files_found: u64,
+        estimated_total: u64,
+        bytes_processed: u64,
+    },
+    Error {
+        path: String,
+        error: String,
+    },
+    Transfer {
+        received: u64,
+        decoded: u64,
+    },
+    Completed {
+        total_files: u64,
+        total_size: u64,
+    },
+}
+
+impl ProgressRecord {
+    /// Record for a scanner event, if it is reported at all
+    pub fn from_progress(progress: &ScanProgress) -> Option<Self> {
+        match progress {
+            ScanProgress::Scanning {
+                path,
+                files_found,
+                estimated_total,
+                bytes_processed,
+            } => Some(Self::Scanning {
+                path: path.to_string_lossy().into_owned(),
+                files_found: *files_found,
+                estimated_total: *estimated_total,
+                bytes_processed: *bytes_processed,
+            }),
+            ScanProgress::Error { path, error } => Some(Self::Error {
+                path: path.to_string_lossy().into_owned(),
+                error: error.clone(),
+            }),
+            ScanProgress::Transfer { received, decoded } => Some(Self::Transfer {
+                received: *received,
+                decoded: *decoded,
+            }),
+            ScanProgress::Completed {
+                total_files, total_size, ..
+            } => Some(Self::Completed {
+                total_files: *total_files,
+                total_size: *total_size,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Parse one line, None when it isn't a progress record
+    pub fn parse(line: &str) -> Option<Self> {
+        serde_json::from_str(line.trim()).ok()
+    }
+
+    /// The scanner event to replay locally
+    ///
+    /// Completion is left out: the tree arrives on stdout.
+    pub fn into_progress(self) -> Option<ScanProgress> {
+        match self {
+            Self::Scanning {
+                path,
+                files_found,
+                estimated_total,
+                bytes_processed,
+            } => Some(ScanProgress::Scanning {
+                path: PathBuf::from(path),
+                files_found,
+                estimated_total,
+                bytes_processed,
+            }),
+            Self::Error { path, error } => Some(ScanProgress::Error {
+                path: PathBuf::from(path),
+                error,
+            }),
+            // Measured again by whoever receives this host's output
+            Self::Transfer { .. } => None,
+            Self::Completed { .. } => None,
+        }
+    }
+}
+
+/// Drain scanner events until the scanner is dropped, writing the reported
+/// ones as JSON lines when a writer is given.
+pub fn write_progress_json(rx: Receiver<ScanProgress>, mut writer: Option<impl Write>) {
+    for progress in rx {
+        let (Some(writer), Some(record)) = (writer.as_mut(), ProgressRecord::from_progress(&progress)) else {
+            continue;
+        };
+        let written = serde_json::to_writer(&mut *writer, &record)
+            .map_err(std::io::Error::other)
+            .and_then(|_| writer.write_all(b"\n"))
+            .and_then(|_| writer.flush());
+        if written.is_err() {
+            break;
+        }
+    }
+}
+
+/// Run a headless scan, draining its progress on another thread so the
+/// scanner never blocks on a full channel.
+///
+/// Progress goes to stderr as JSON lines with `progress_json`.
+pub fn scan_headless<T>(
+    progress_json: bool,
+    scan: impl FnOnce(SyncSender<ScanProgress>) -> anyhow::Result<T>,
+) -> anyhow::Result<T> {
+    let (tx, rx) = mpsc::sync_channel(1000);
+    let progress = thread::spawn(move || write_progress_json(rx, progress_json.then(std::io::stderr)));
+    let result = scan(tx);
+    let _ = progress.join();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::FileTree;
+    use std::sync::mpsc::sync_channel;
+
+    #[test]
+    fn test_progress_lines_round_trip() {
+        let (tx, rx) = sync_channel(10);
+        tx.send(ScanProgress::Started).unwrap();
+        tx.send(ScanProgress::Scanning {
+            path: PathBuf::from("/srv/a \"b\""),
+            files_found: 100,
+            estimated_total: 1000,
+            bytes_processed: 4096,
+        })
+        .unwrap();
+        tx.send(ScanProgress::Completed {
+            total_files: 120,
+            total_size: 5000,
+            tree: FileTree::new(),
+        })
+        .unwrap();
+        drop(tx);
+
+        let mut output = Vec::new();
+        write_progress_json(rx, Some(&mut output));
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<_> = output.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            lines[0],
+            r#"{"event":"scanning","path":"/srv/a \"b\"","files_found":100,"estimated_total":1000,"bytes_processed":4096}"#
+        );
+
+        match ProgressRecord::parse(lines[0]).and_then(ProgressRecord::into_progress) {
+            Some(ScanProgress::Scanning { path, files_found, .. }) => {
+                assert_eq!(path, PathBuf::from("/srv/a \"b\""));
+                assert_eq!(files_found, 100);
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        assert_eq!(
+            ProgressRecord::parse(lines[1]),
+            Some(ProgressRecord::Completed {
+                total_files: 120,
+                total_size: 5000
+            })
+        );
+        assert_eq!(ProgressRecord::parse("error: unexpected argument '--progress-json'"), None);
+    }
+}
@@ -0,0 +1,272 @@
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::scanner::DiskSpaceInfo;
+use crate::tree::{FileTree, NodeId};
+
+/// Options for the Prometheus textfile exporter
+#[derive(Default)]
+pub struct MetricsOptions {
+    /// Deepest directory level to emit (0 = only the scan root)
+    pub max_depth: usize,
+    /// If Some(n), emit only the n largest directories to bound cardinality
+    pub top_n: Option<usize>,
+    /// Write OpenMetrics (`# UNIT` lines and `# EOF`) instead of the
+    /// Prometheus text format
+    pub openmetrics: bool,
+    /// Usage of the filesystem holding the scan root, with its mount point
+    pub filesystem: Option<(PathBuf, DiskSpaceInfo)>,
+    /// How long the scan took
+    pub scan_duration: Option<Duration>,
+}
+
+/// Escape a label value: backslash, double quote and line feed
+fn escape_label(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Write the `# HELP`/`# TYPE` (and for OpenMetrics `# UNIT`) lines of a gauge
+fn write_family(
+    writer: &mut impl Write,
+    name: &str,
+    help: &str,
+    unit: Option<&str>,
+    openmetrics: bool,
+) -> Result<(), std::io::Error> {
+    writeln!(writer, "# HELP {} {}", name, help)?;
+    writeln!(writer, "# TYPE {} gauge", name)?;
+    if openmetrics {
+        if let Some(unit) = unit {
+            writeln!(writer, "# UNIT {} {}", name, unit)?;
+        }
+    }
+    Ok(())
+}
+
+/// Directories up to `max_depth`, in tree order, with their depth
+fn collect_directories(tree: &FileTree, root: NodeId, max_depth: usize) -> Vec<(NodeId, usize)> {
+    let mut result = Vec::new();
+    let mut stack = vec![(root, 0usize)];
+
+    while let Some((node_id, depth)) = stack.pop() {
+        let node = tree.get_node(node_id).expect("Node must exist");
+        if !node.is_dir || node.excluded {
+            continue;
+        }
+        result.push((node_id, depth));
+
+        if depth < max_depth {
+            // Push children reversed so they are visited in tree order
+            for child_id in tree.get_children(node_id).into_iter().rev() {
+                stack.push((child_id, depth + 1));
+            }
+        }
+    }
+
+    result
+}
+
+/// Export directory sizes and filesystem usage as Prometheus gauges
+///
+/// Meant for node_exporter's textfile collector. Emits bytes and file counts
+/// per directory down to `max_depth`, the scan time, and the usage of the
+/// filesystem holding the root when known.
+///
+/// # Arguments
+/// * `tree` - The FileTree to export
+/// * `options` - Depth, top-N limit, format and filesystem info
+/// * `writer` - Output writer
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(std::io::Error)` on write failure
+pub fn export_prometheus(
+    tree: &FileTree,
+    options: &MetricsOptions,
+    writer: &mut impl Write,
+) -> Result<(), std::io::Error> {
+    let om = options.openmetrics;
+    let root = tree.root.and_then(|id| tree.get_node(id).map(|node| (id, node)));
+    let root_label = root
+        .map(|(_, node)| escape_label(&node.path.to_string_lossy()))
+        .unwrap_or_default();
+
+    if let Some((root_id, _)) = root {
+        let mut directories = collect_directories(tree, root_id, options.max_depth);
+
+        if let Some(n) = options.top_n {
+            // Keep the n largest, then restore tree order for stable output
+            let mut ranked: Vec<usize> = (0..directories.len()).collect();
+            ranked.sort_by_key(|&i| {
+                std::cmp::Reverse(tree.get_node(directories[i].0).map(|node| node.size).unwrap_or(0))
+            });
+            ranked.truncate(n);
+            ranked.sort_unstable();
+            directories = ranked.into_iter().map(|i| directories[i]).collect();
+        }
+
+        let labels: Vec<(String, u64, u64)> = directories
+            .iter()
+            .map(|&(node_id, depth)| {
+                let node = tree.get_node(node_id).expect("Node must exist");
+                let labels = format!(
+                    "root=\"{}\",path=\"{}\",depth=\"{}\"",
+                    root_label,
+                    escape_label(&node.path.to_string_lossy()),
+                    depth
+                );
+                (labels, node.size, node.file_count)
+            })
+            .collect();
+
+        write_family(writer, "datax_directory_size_bytes", "Size of the directory tree in bytes.", Some("bytes"), om)?;
+        for (labels, size, _) in &labels {
+            writeln!(writer, "datax_directory_size_bytes{{{}}} {}", labels, size)?;
+        }
+
+        write_family(writer, "datax_directory_files", "Number of files in the directory tree.", None, om)?;
+        for (labels, _, files) in &labels {
+            writeln!(writer, "datax_directory_files{{{}}} {}", labels, files)?;
+        }
+    }
+
+    if let Some((ref mount_point, info)) = options.filesystem {
+        let labels = format!("mountpoint=\"{}\"", escape_label(&mount_point.to_string_lossy()));
+
+        write_family(writer, "datax_filesystem_size_bytes", "Filesystem size in bytes.", Some("bytes"), om)?;
+        writeln!(writer, "datax_filesystem_size_bytes{{{}}} {}", labels, info.total)?;
+        write_family(writer, "datax_filesystem_used_bytes", "Filesystem space used in bytes.", Some("bytes"), om)?;
+        writeln!(writer, "datax_filesystem_used_bytes{{{}}} {}", labels, info.used)?;
+        write_family(writer, "datax_filesystem_avail_bytes", "Filesystem space available in bytes.", Some("bytes"), om)?;
+        writeln!(writer, "datax_filesystem_avail_bytes{{{}}} {}", labels, info.free)?;
+    }
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    write_family(writer, "datax_scan_timestamp_seconds", "Unix time the scan finished.", Some("seconds"), om)?;
+    writeln!(writer, "datax_scan_timestamp_seconds{{root=\"{}\"}} {}", root_label, timestamp)?;
+
+    if let Some(duration) = options.scan_duration {
+        write_family(writer, "datax_scan_duration_seconds", "Time the scan took in seconds.", Some("seconds"), om)?;
+        writeln!(
+            writer,
+            "datax_scan_duration_seconds{{root=\"{}\"}} {:.3}",
+            root_label,
+            duration.as_secs_f64()
+        )?;
+    }
+
+    if om {
+        writeln!(writer, "# EOF")?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::FileNode;
+
+    fn create_test_tree() -> FileTree {
+        let mut tree = FileTree::with_root(PathBuf::from("/srv"));
+        let root = tree.root.unwrap();
+
+        let www = tree.add_child(root, FileNode::new(PathBuf::from("/srv/www"), true));
+        let assets = tree.add_child(www, FileNode::new(PathBuf::from("/srv/www/assets"), true));
+        tree.add_child(assets, FileNode::new(PathBuf::from("/srv/www/assets/app.js"), false).with_size(700));
+        tree.add_child(www, FileNode::new(PathBuf::from("/srv/www/index.html"), false).with_size(100));
+
+        let odd = tree.add_child(root, FileNode::new(PathBuf::from("/srv/say \"hi\"\\\nnow"), true));
+        tree.add_child(odd, FileNode::new(PathBuf::from("/srv/say \"hi\"\\\nnow/a.txt"), false).with_size(50));
+
+        tree.add_child(root, FileNode::new(PathBuf::from("/srv/top.log"), false).with_size(10));
+
+        tree.calculate_sizes();
+        tree
+    }
+
+    fn export_to_string(tree: &FileTree, options: &MetricsOptions) -> String {
+        let mut buffer = Vec::new();
+        export_prometheus(tree, options, &mut buffer).unwrap();
+        String::from_utf8(buffer).unwrap()
+    }
+
+    fn samples<'a>(output: &'a str, metric: &str) -> Vec<&'a str> {
+        output
+            .lines()
+            .filter(|line| line.starts_with(&format!("{}{{", metric)))
+            .collect()
+    }
+
+    #[test]
+    fn test_escape_label() {
+        assert_eq!(escape_label("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+        assert_eq!(escape_label("/plain/path"), "/plain/path");
+    }
+
+    #[test]
+    fn test_directories_up_to_depth() {
+        let tree = create_test_tree();
+        let options = MetricsOptions { max_depth: 1, ..Default::default() };
+        let output = export_to_string(&tree, &options);
+
+        let sizes = samples(&output, "datax_directory_size_bytes");
+        assert_eq!(
+            sizes,
+            vec![
+                "datax_directory_size_bytes{root=\"/srv\",path=\"/srv\",depth=\"0\"} 860",
+                "datax_directory_size_bytes{root=\"/srv\",path=\"/srv/www\",depth=\"1\"} 800",
+                "datax_directory_size_bytes{root=\"/srv\",path=\"/srv/say \\\"hi\\\"\\\\\\nnow\",depth=\"1\"} 50",
+            ]
+        );
+        assert_eq!(samples(&output, "datax_directory_files").len(), 3);
+        assert!(output.contains("# TYPE datax_directory_size_bytes gauge"));
+        assert!(!output.contains("# EOF"));
+        assert!(!output.contains("datax_filesystem"));
+    }
+
+    #[test]
+    fn test_top_n_bounds_cardinality() {
+        let tree = create_test_tree();
+        let options = MetricsOptions { max_depth: 5, top_n: Some(2), ..Default::default() };
+        let output = export_to_string(&tree, &options);
+
+        let files = samples(&output, "datax_directory_files");
+        assert_eq!(files.len(), 2);
+        assert!(files[0].contains("path=\"/srv\"") && files[0].ends_with(" 4"));
+        assert!(files[1].contains("path=\"/srv/www\"") && files[1].ends_with(" 2"));
+    }
+
+    #[test]
+    fn test_filesystem_and_openmetrics() {
+        let tree = create_test_tree();
+        let options = MetricsOptions {
+            max_depth: 0,
+            openmetrics: true,
+            filesystem: Some((PathBuf::from("/"), DiskSpaceInfo { total: 1000, used: 600, free: 400 })),
+            scan_duration: Some(Duration::from_millis(1500)),
+            ..Default::default()
+        };
+        let output = export_to_string(&tree, &options);
+
+        assert!(output.contains("datax_filesystem_size_bytes{mountpoint=\"/\"} 1000\n"));
+        assert!(output.contains("datax_filesystem_used_bytes{mountpoint=\"/\"} 600\n"));
+        assert!(output.contains("datax_filesystem_avail_bytes{mountpoint=\"/\"} 400\n"));
+        assert!(output.contains("datax_scan_duration_seconds{root=\"/srv\"} 1.500\n"));
+        assert!(output.contains("# UNIT datax_directory_size_bytes bytes"));
+        assert!(output.ends_with("# EOF\n"));
+    }
+}
@@ -0,0 +1,246 @@
+use std::path::{Path, PathBuf};
+
+use rusqlite::{params, Connection};
+
+use crate::tree::{FileTree, NodeId};
+
+use super::json::{build_header, to_epoch};
+use super::ExportOptions;
+
+/// Version of the database layout, stored in the metadata table
+pub const SQLITE_SCHEMA_VERSION: u32 = 1;
+
+const SCHEMA: &str = "
+CREATE TABLE metadata (
+    key TEXT PRIMARY KEY,
+    value TEXT
+);
+CREATE TABLE nodes (
+    id INTEGER PRIMARY KEY,
+    parent_id INTEGER REFERENCES nodes(id),
+    path TEXT NOT NULL,
+    name TEXT NOT NULL,
+    size INTEGER NOT NULL,
+    file_count INTEGER NOT NULL,
+    mtime INTEGER,
+    ext TEXT,
+    is_dir INTEGER NOT NULL,
+    depth INTEGER NOT NULL
+);
+";
+
+const INDEXES: &str = "
+CREATE INDEX nodes_size ON nodes(size);
+CREATE INDEX nodes_ext ON nodes(ext);
+CREATE INDEX nodes_parent_id ON nodes(parent_id);
+";
+
+fn sqlite_error(e: rusqlite::Error) -> std::io::Error {
+    std::io::Error::other(e)
+}
+
+/// Write every node into an open database
+fn write_database(conn: &mut Connection, tree: &FileTree, options: &ExportOptions) -> Result<(), rusqlite::Error> {
+    conn.execute_batch(SCHEMA)?;
+    let tx = conn.transaction()?;
+
+    let header = build_header(tree, options);
+    {
+        let mut insert = tx.prepare("INSERT INTO metadata (key, value) VALUES (?1, ?2)")?;
+        let options_json = header
+            .options
+            .as_ref()
+            .map(|o| serde_json::to_string(o).unwrap_or_default());
+        let metadata: [(&str, Option<String>); 8] = [
+            ("schema_version", Some(SQLITE_SCHEMA_VERSION.to_string())),
+            ("data_x_version", Some(header.data_x_version)),
+            ("root", Some(header.root)),
+            ("timestamp", Some(header.timestamp)),
+            ("options", options_json),
+            ("total_size", Some(header.totals.size.to_string())),
+            ("total_files", Some(header.totals.files.to_string())),
+            ("total_dirs", Some(header.totals.dirs.to_string())),
+        ];
+        for (key, value) in metadata {
+            insert.execute(params![key, value])?;
+        }
+    }
+
+    if let Some(root) = tree.root {
+        let mut insert = tx.prepare(
+            "INSERT INTO nodes (id, parent_id, path, name, size, file_count, mtime, ext, is_dir, depth)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        )?;
+
+        // Parents are inserted before their children
+        let mut stack: Vec<(NodeId, Option<NodeId>, usize)> = vec![(root, None, 0)];
+        while let Some((node_id, parent, depth)) = stack.pop() {
+            let node = tree.get_node(node_id).expect("Node must exist");
+            insert.execute(params![
+                usize::from(node_id) as i64,
+                parent.map(|p| usize::from(p) as i64),
+                node.path.to_string_lossy(),
+                node.name,
+                node.size as i64,
+                node.file_count as i64,
+                node.modified.map(|m| to_epoch(m).0),
+                node.extension,
+                node.is_dir,
+                depth as i64,
+            ])?;
+
+            for child_id in tree.get_children(node_id).into_iter().rev() {
+                stack.push((child_id, Some(node_id), depth + 1));
+            }
+        }
+    }
+
+    // Indexes are cheaper to build once the rows are in
+    tx.execute_batch(INDEXES)?;
+    tx.commit()
+}
+
+/// Export the FileTree into a SQLite database at `path`
+///
+/// Creates a `nodes` table with one row per node and a `metadata` table with
+/// the scan header (root, timestamp, options, totals). The database is
+/// written to a temporary file and renamed into place, replacing any
+/// existing file.
+///
+/// # Arguments
+/// * `tree` - The FileTree to export
+/// * `options` - Export options (`top_n` is not supported)
+/// * `path` - Database file to create
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(std::io::Error)` on database or file errors, or when `top_n` is set
+pub fn export_sqlite(tree: &FileTree, options: &ExportOptions, path: &Path) -> Result<(), std::io::Error> {
+    if options.top_n.is_some() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "--top is not supported with SQLite export",
+        ));
+    }
+
+    let mut temp_path = path.as_os_str().to_owned();
+    temp_path.push(".tmp");
+    let temp_path = PathBuf::from(temp_path);
+    if temp_path.exists() {
+        std::fs::remove_file(&temp_path)?;
+    }
+
+    let result = Connection::open(&temp_path)
+        .and_then(|mut conn| {
+            write_database(&mut conn, tree, options)?;
+            conn.close().map_err(|(_, e)| e)
+        })
+        .map_err(sqlite_error);
+
+    match result {
+        Ok(()) => std::fs::rename(&temp_path, path),
+        Err(e) => {
+            let _ = std::fs::remove_file(&temp_path);
+            Err(e)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::FileNode;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn create_test_tree() -> FileTree {
+        let mut tree = FileTree::with_root(PathBuf::from("/srv"));
+        let root = tree.root.unwrap();
+
+        let logs = tree.add_child(root, FileNode::new(PathBuf::from("/srv/logs"), true));
+        tree.add_child(
+            logs,
+            FileNode::new(PathBuf::from("/srv/logs/app.log"), false)
+                .with_size(5000)
+                .with_modified(UNIX_EPOCH + Duration::from_secs(1_700_000_000)),
+        );
+        tree.add_child(logs, FileNode::new(PathBuf::from("/srv/logs/old.LOG"), false).with_size(300));
+        tree.add_child(root, FileNode::new(PathBuf::from("/srv/README"), false).with_size(20));
+
+        tree.calculate_sizes();
+        tree
+    }
+
+    #[test]
+    fn test_nodes_and_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("scan.db");
+        export_sqlite(&create_test_tree(), &ExportOptions::default(), &db_path).unwrap();
+
+        let conn = Connection::open(&db_path).unwrap();
+        let count: i64 = conn.query_row("SELECT COUNT(*) FROM nodes", [], |row| row.get(0)).unwrap();
+        assert_eq!(count, 5);
+
+        let (size, files, is_dir, depth): (i64, i64, bool, i64) = conn
+            .query_row(
+                "SELECT size, file_count, is_dir, depth FROM nodes WHERE parent_id IS NULL",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )
+            .unwrap();
+        assert_eq!((size, files, is_dir, depth), (5320, 3, true, 0));
+
+        let (name, mtime, depth, parent_path): (String, Option<i64>, i64, String) = conn
+            .query_row(
+                "SELECT n.name, n.mtime, n.depth, p.path FROM nodes n JOIN nodes p ON n.parent_id = p.id
+                 WHERE n.ext = 'log' ORDER BY n.size DESC LIMIT 1",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )
+            .unwrap();
+        assert_eq!(name, "app.log");
+        assert_eq!(mtime, Some(1_700_000_000));
+        assert_eq!(depth, 2);
+        assert_eq!(parent_path, "/srv/logs");
+
+        let root: String = conn
+            .query_row("SELECT value FROM metadata WHERE key = 'root'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(root, "/srv");
+        let total: String = conn
+            .query_row("SELECT value FROM metadata WHERE key = 'total_size'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(total, "5320");
+
+        let indexes: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type = 'index' AND name IN ('nodes_size', 'nodes_ext')",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(indexes, 2);
+    }
+
+    #[test]
+    fn test_replaces_existing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("scan.db");
+        std::fs::write(&db_path, b"not a database").unwrap();
+
+        export_sqlite(&create_test_tree(), &ExportOptions::default(), &db_path).unwrap();
+        export_sqlite(&FileTree::new(), &ExportOptions::default(), &db_path).unwrap();
+
+        let conn = Connection::open(&db_path).unwrap();
+        let count: i64 = conn.query_row("SELECT COUNT(*) FROM nodes", [], |row| row.get(0)).unwrap();
+        assert_eq!(count, 0);
+        assert!(!dir.path().join("scan.db.tmp").exists());
+    }
+
+    #[test]
+    fn test_rejects_top_n() {
+        let dir = tempfile::tempdir().unwrap();
+        let options = ExportOptions { top_n: Some(3), ..Default::default() };
+        let err = export_sqlite(&create_test_tree(), &options, &dir.path().join("scan.db")).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    }
+}
@@ -0,0 +1,466 @@
+//! Fleet scans: the same path on many hosts at once.
+//!
+//! Hosts are listed one `[user@]host[:port]` per line. Each is scanned by
+//! its own [`RemoteScanner`], a bounded number at a time and each against
+//! its own deadline, and the trees are combined under one root with a
+//! top-level directory per host.
+
+use std::ffi::OsString;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Context, Result};
+use rayon::prelude::*;
+use serde::Serialize;
+
+use crate::export::{build_header, tree_to_export_node, ExportBody, ExportDocument, ExportOptions, SCHEMA_URL};
+use crate::format::format_size;
+use crate::remote::{RemoteScanner, SshTarget, TimedOut};
+use crate::scanner::ScanOptions;
+use crate::tree::{FileTree, NodeId};
+
+/// How one host's scan ended
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HostStatus {
+    Ok,
+    Failed(String),
+    TimedOut,
+}
+
+impl HostStatus {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Ok => "ok",
+            Self::Failed(_) => "failed",
+            Self::TimedOut => "timed out",
+        }
+    }
+
+    fn error(&self) -> Option<&str> {
+        match self {
+            Self::Failed(error) => Some(error),
+            _ => None,
+        }
+    }
+
+    /// Status with the error, for the summary table
+    pub fn describe(&self) -> String {
+        match self.error() {
+            Some(error) => format!("{}: {}", self.name(), error),
+            None => self.name().to_string(),
+        }
+    }
+}
+
+/// One host's part of a fleet scan
+#[derive(Debug)]
+pub struct HostScan {
+    /// The host as listed
+    pub host: String,
+    pub target: SshTarget,
+    pub elapsed: Duration,
+    pub status: HostStatus,
+    /// The host's tree, if the scan succeeded
+    pub tree: Option<FileTree>,
+}
+
+impl HostScan {
+    fn totals(&self) -> Option<(u64, u64)> {
+        let tree = self.tree.as_ref()?;
+        let root = tree.get_node(tree.root?)?;
+        Some((root.size, root.file_count))
+    }
+}
+
+/// Read a hosts file: one `[user@]host[:port]` per line, with blank lines
+/// and `#` comments skipped.
+pub fn read_hosts(path: &Path) -> Result<Vec<String>> {
+    let content = std::fs::read_to_string(path).with_context(|| format!("Cannot read {}", path.display()))?;
+    Ok(content
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or("").trim())
+        .filter(|line| !line.is_empty())
+        .map(String::from)
+        .collect())
+}
+
+/// The same path scanned on a list of hosts
+pub struct Fleet {
+    /// Hosts as listed, with their targets
+    targets: Vec<(String, SshTarget)>,
+    options: ScanOptions,
+    jobs: usize,
+    timeout: Duration,
+    ssh_program: PathBuf,
+}
+
+impl Fleet {
+    /// Scan `template`'s path on each of `hosts`, connecting with its ssh
+    /// options and compression.
+    pub fn new(hosts: &[String], template: &SshTarget) -> Result<Self> {
+        if !template.path.is_absolute() {
+            return Err(anyhow!("Fleet path must be absolute, e.g. /var/log"));
+        }
+        let targets = hosts
+            .iter()
+            .map(|host| {
+                let target = parse_host(host, template)
+                    .ok_or_else(|| anyhow!("Invalid host '{}' (expected [user@]host[:port])", host))?;
+                Ok((host.clone(), target))
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            targets,
+            options: ScanOptions::new(template.path.clone()),
+            jobs: 8,
+            timeout: Duration::from_secs(600),
+            ssh_program: PathBuf::from("ssh"),
+        })
+    }
+
+    /// Scan every host with `options`.
+    pub fn with_options(mut self, options: ScanOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Scan at most `jobs` hosts at once.
+    pub fn with_jobs(mut self, jobs: usize) -> Self {
+        self.jobs = jobs.max(1);
+        self
+    }
+
+    /// Give up on a host once its scan has run for `timeout`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Run `program` instead of `ssh`.
+    #[cfg(test)]
+    pub fn with_ssh_program(mut self, program: impl Into<PathBuf>) -> Self {
+        self.ssh_program = program.into();
+        self
+    }
+
+    /// Scan all hosts, calling `on_done` as each one finishes.
+    ///
+    /// Scans come back in the order the hosts were listed.
+    pub fn scan(&self, on_done: impl Fn(&HostScan) + Sync) -> Result<Vec<HostScan>> {
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(self.jobs).build()?;
+        Ok(pool.install(|| {
+            self.targets
+                .par_iter()
+                .map(|(host, target)| {
+                    let scan = self.scan_host(host, target);
+                    on_done(&scan);
+                    scan
+                })
+                .collect()
+        }))
+    }
+
+    fn scan_host(&self, host: &str, target: &SshTarget) -> HostScan {
+        let start = Instant::now();
+        // Nobody watches a single host's progress; with the receiver gone
+        // the scanner's sends are no-ops
+        let (tx, _) = mpsc::sync_channel(0);
+        let result = RemoteScanner::new(target.clone(), tx)
+            .with_options(self.options.clone())
+            .with_ssh_program(&self.ssh_program)
+            .with_deadline(start + self.timeout)
+            .scan();
+
+        let (status, tree) = match result {
+            Ok(tree) => (HostStatus::Ok, Some(tree)),
+            Err(err) if err.is::<TimedOut>() => (HostStatus::TimedOut, None),
+            Err(err) => (HostStatus::Failed(err.to_string()), None),
+        };
+        HostScan {
+            host: host.to_string(),
+            target: target.clone(),
+            elapsed: start.elapsed(),
+            status,
+            tree,
+        }
+    }
+}
+
+/// Target for `host` as listed, with everything else from `template`
+fn parse_host(host: &str, template: &SshTarget) -> Option<SshTarget> {
+    if host.contains(['/', ' ', '\t']) {
+        return None;
+    }
+    let parsed = SshTarget::parse(&format!("ssh://{}/", host))?;
+    if parsed.host.is_empty() || (host.contains(':') && parsed.port.is_none()) {
+        return None;
+    }
+    Some(SshTarget {
+        user: parsed.user,
+        host: parsed.host,
+        port: parsed.port,
+        ..template.clone()
+    })
+}
+
+/// `path` on `host`, as `host:path`
+fn located(host: &str, path: &Path) -> PathBuf {
+    let mut located = OsString::from(host);
+    located.push(":");
+    located.push(path.as_os_str());
+    PathBuf::from(located)
+}
+
+/// Combine the hosts' trees under `path`, one top-level directory per
+/// scanned host. Paths below it read `host:/path` so they stay apart.
+pub fn combine(scans: &[HostScan], path: &Path) -> FileTree {
+    let mut tree = FileTree::with_root(path.to_path_buf());
+    let root = tree.root.unwrap();
+    for scan in scans {
+        let Some(ref host_tree) = scan.tree else { continue };
+        let Some(mut node) = host_tree.root.and_then(|id| host_tree.get_node(id)).cloned() else { continue };
+
+        node.path = located(&scan.host, &node.path);
+        node.name = scan.host.clone();
+        node.name_lower = node.name.to_lowercase();
+        node.is_hidden = false;
+        let host_root = tree.add_child(root, node);
+        graft(host_tree, host_tree.root.unwrap(), &mut tree, host_root, &scan.host);
+    }
+    tree.calculate_sizes();
+    tree
+}
+
+/// Copy the children of `from` in `source` below `to`, located on `host`
+fn graft(source: &FileTree, from: NodeId, tree: &mut FileTree, to: NodeId, host: &str) {
+    for child in source.get_children(from) {
+        let Some(mut node) = source.get_node(child).cloned() else { continue };
+        node.path = located(host, &node.path);
+        let id = tree.add_child(to, node);
+        graft(source, child, tree, id, host);
+    }
+}
+
+/// Write a table of the hosts' sizes, file counts, times and statuses.
+pub fn write_summary(scans: &[HostScan], writer: &mut impl Write) -> std::io::Result<()> {
+    let width = scans.iter().map(|scan| scan.host.len()).max().unwrap_or(0).max(4);
+    writeln!(writer, "{:<width$} {:>10} {:>10} {:>8}  STATUS", "HOST", "SIZE", "FILES", "TIME")?;
+    for scan in scans {
+        let (size, files) = match scan.totals() {
+            Some((size, files)) => (format_size(size), files.to_string()),
+            None => ("-".to_string(), "-".to_string()),
+        };
+        writeln!(
+            writer,
+            "{:<width$} {:>10} {:>10} {:>7.1}s  {}",
+            scan.host,
+            size,
+            files,
+            scan.elapsed.as_secs_f64(),
+            scan.status.describe()
+        )?;
+    }
+
+    let ok: Vec<_> = scans.iter().filter_map(HostScan::totals).collect();
+    writeln!(
+        writer,
+        "{} of {} hosts scanned, {} in {} files",
+        ok.len(),
+        scans.len(),
+        format_size(ok.iter().map(|(size, _)| size).sum()),
+        ok.iter().map(|(_, files)| files).sum::<u64>()
+    )
+}
+
+/// Fleet scan as JSON: a `--json` document of the combined tree, loadable
+/// with `--load`, plus how each host fared
+#[derive(Serialize)]
+struct FleetReport<'a> {
+    #[serde(flatten)]
+    document: ExportDocument,
+    hosts: Vec<HostReport<'a>>,
+}
+
+#[derive(Serialize)]
+struct HostReport<'a> {
+    host: &'a str,
+    target: String,
+    status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<&'a str>,
+    size: Option<u64>,
+    files: Option<u64>,
+    seconds: f64,
+}
+
+/// Write the JSON report of a fleet scan combined into `tree`.
+pub fn write_report(
+    scans: &[HostScan],
+    tree: &FileTree,
+    options: &ScanOptions,
+    writer: &mut impl Write,
+) -> std::io::Result<()> {
+    let export_options = ExportOptions {
+        top_n: None,
+        scan_options: Some(options.clone()),
+    };
+    let report = FleetReport {
+        document: ExportDocument {
+            schema: SCHEMA_URL.to_string(),
+            header: build_header(tree, &export_options),
+            body: ExportBody::Tree(tree.root.map(|root| tree_to_export_node(tree, root))),
+        },
+        hosts: scans
+            .iter()
+            .map(|scan| HostReport {
+                host: &scan.host,
+                target: scan.target.display(),
+                status: scan.status.name(),
+                error: scan.status.error(),
+                size: scan.totals().map(|(size, _)| size),
+                files: scan.totals().map(|(_, files)| files),
+                seconds: scan.elapsed.as_secs_f64(),
+            })
+            .collect(),
+    };
+    serde_json::to_writer_pretty(writer, &report).map_err(std::io::Error::other)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::FileNode;
+
+    #[test]
+    fn test_parse_host() {
+        let template = SshTarget {
+            path: PathBuf::from("/var/log"),
+            jump_hosts: vec!["bastion".to_string()],
+            ..Default::default()
+        };
+        let target = parse_host("admin@web2:2222", &template).unwrap();
+        assert_eq!(target.user.as_deref(), Some("admin"));
+        assert_eq!(target.host, "web2");
+        assert_eq!(target.port, Some(2222));
+        assert_eq!(target.path, PathBuf::from("/var/log"));
+        assert_eq!(target.jump_hosts, ["bastion"]);
+
+        for host in ["web1:ssh", "web1/var", "@", "web 1"] {
+            assert!(parse_host(host, &template).is_none(), "{}", host);
+        }
+        assert!(Fleet::new(&["web1".to_string()], &SshTarget { path: PathBuf::from("logs"), ..template }).is_err());
+    }
+
+    /// `ssh` stand-in for a fleet: web3 hangs, web4 refuses connections
+    /// and the others have data-x and a `/srv` tree as big as their number.
+    #[cfg(unix)]
+    fn fleet_ssh(dir: &Path) -> PathBuf {
+        use crate::export::export_ndjson;
+        use std::os::unix::fs::PermissionsExt;
+
+        for (host, size) in [("web1", 1000), ("web2", 2000), ("web5", 5000), ("web6", 6000)] {
+            let mut tree = FileTree::with_root(PathBuf::from("/srv"));
+            let root = tree.root.unwrap();
+            let logs = tree.add_child(root, FileNode::new(PathBuf::from("/srv/logs"), true));
+            tree.add_child(logs, FileNode::new(PathBuf::from("/srv/logs/app.log"), false).with_size(size));
+            tree.calculate_sizes();
+            let mut ndjson = Vec::new();
+            export_ndjson(&tree, &ExportOptions::default(), &mut ndjson).unwrap();
+            std::fs::write(dir.join(format!("ssh.{}.ndjson", host)), ndjson).unwrap();
+        }
+
+        let ssh = dir.join("ssh");
+        std::fs::write(
+            &ssh,
+            concat!(
+                "#!/bin/sh\n",
+                "for arg; do host=$command; command=$arg; done\n",
+                "host=${host##*@}\n",
+                "case \"$host\" in\n",
+                "    web4) echo 'ssh: connect to host web4 port 22: Connection refused' >&2; exit 255 ;;\n",
+                "esac\n",
+                "case \"$command\" in\n",
+                "    which\\ *) echo /usr/local/bin/data-x; exit 0 ;;\n",
+                "esac\n",
+                "case \"$host\" in\n",
+                "    web3) exec sleep 10 ;;\n",
+                "esac\n",
+                "echo start >> \"$0.events\"\n",
+                "sleep 0.2\n",
+                "echo end >> \"$0.events\"\n",
+                "cat \"$0.$host.ndjson\"\n",
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&ssh, std::fs::Permissions::from_mode(0o755)).unwrap();
+        ssh
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_fleet_scan() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let ssh = fleet_ssh(dir.path());
+        let hosts: Vec<String> =
+            ["web1", "admin@web2:2222", "web3", "web4", "web5", "web6"].iter().map(|h| h.to_string()).collect();
+        let template = SshTarget {
+            path: PathBuf::from("/srv"),
+            ..Default::default()
+        };
+        let fleet = Fleet::new(&hosts, &template)
+            .unwrap()
+            .with_jobs(2)
+            .with_timeout(Duration::from_millis(500))
+            .with_ssh_program(&ssh);
+
+        let finished = std::sync::Mutex::new(Vec::new());
+        let scans = fleet.scan(|scan| finished.lock().unwrap().push(scan.host.clone())).unwrap();
+        assert_eq!(finished.into_inner().unwrap().len(), hosts.len());
+
+        // In the order listed, whatever order they finished in
+        let listed: Vec<_> = scans.iter().map(|scan| scan.host.as_str()).collect();
+        assert_eq!(listed, hosts);
+        let statuses: Vec<_> = scans.iter().map(|scan| scan.status.name()).collect();
+        assert_eq!(statuses, ["ok", "ok", "timed out", "failed", "ok", "ok"]);
+        assert!(scans[2].elapsed < Duration::from_secs(5), "{:?}", scans[2].elapsed);
+        assert!(scans[3].status.describe().contains("Connection refused"));
+
+        // Never more than two hosts at once
+        let events = std::fs::read_to_string(dir.path().join("ssh.events")).unwrap();
+        let mut running = 0;
+        for event in events.lines() {
+            running += if event == "start" { 1 } else { -1 };
+            assert!(running <= 2, "{}", events);
+        }
+
+        let tree = combine(&scans, Path::new("/srv"));
+        let root = tree.root.unwrap();
+        assert_eq!(tree.get_node(root).unwrap().size, 14000);
+        let children: Vec<_> = tree.get_children(root).iter().map(|&id| tree.get_node(id).unwrap().name.clone()).collect();
+        assert_eq!(children, ["web1", "admin@web2:2222", "web5", "web6"]);
+        let log = tree.find_by_path(&PathBuf::from("admin@web2:2222:/srv/logs/app.log")).unwrap();
+        assert_eq!(tree.get_node(log).unwrap().size, 2000);
+
+        let mut summary = Vec::new();
+        write_summary(&scans, &mut summary).unwrap();
+        let summary = String::from_utf8(summary).unwrap();
+        assert!(summary.lines().next().unwrap().starts_with("HOST "));
+        assert!(summary.contains("timed out"));
+        assert!(summary.ends_with("4 of 6 hosts scanned, 13.7 KB in 4 files\n"), "{}", summary);
+
+        // The report reads back as the combined tree
+        let mut report = Vec::new();
+        write_report(&scans, &tree, &ScanOptions::new(PathBuf::from("/srv")), &mut report).unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&report).unwrap();
+        assert_eq!(value["hosts"][1]["target"], "admin@web2:2222:/srv");
+        assert_eq!(value["hosts"][1]["size"], 2000);
+        assert_eq!(value["hosts"][2]["status"], "timed out");
+        assert_eq!(value["hosts"][2]["size"], serde_json::Value::Null);
+        assert!(value["hosts"][3]["error"].as_str().unwrap().contains("Connection refused"));
+        let imported = crate::export::import_json(std::str::from_utf8(&report).unwrap()).unwrap();
+        assert_eq!(imported.tree.total_size(), 14000);
+    }
+}
@@ -0,0 +1,217 @@
+//! Human-readable byte sizes shared by every front-end.
+//!
+//! All size strings in the TUI, GUI, exports and `--du` output go through
+//! this module so that the same number is always shown the same way. It
+//! also parses the sizes and durations given on the command line.
+
+use std::time::Duration;
+
+/// Unit system used when formatting sizes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SizeUnits {
+    /// Powers of 1024, labelled KB, MB, GB, ... as the UI has always shown them
+    #[default]
+    Iec,
+    /// Powers of 1000, labelled kB, MB, GB, ...
+    Si,
+    /// Exact byte counts
+    Bytes,
+}
+
+const IEC_LABELS: [&str; 7] = ["B", "KB", "MB", "GB", "TB", "PB", "EB"];
+const SI_LABELS: [&str; 7] = ["B", "kB", "MB", "GB", "TB", "PB", "EB"];
+const IEC_SHORT_LABELS: [&str; 7] = ["", "K", "M", "G", "T", "P", "E"];
+const SI_SHORT_LABELS: [&str; 7] = ["", "k", "M", "G", "T", "P", "E"];
+
+impl SizeUnits {
+    fn base(self) -> u64 {
+        match self {
+            SizeUnits::Iec => 1024,
+            SizeUnits::Si => 1000,
+            SizeUnits::Bytes => 1,
+        }
+    }
+}
+
+/// Largest unit index (0 = bytes) such that `bytes` is at least one unit,
+/// together with the size of that unit
+fn scale(bytes: u64, base: u64) -> (usize, u64) {
+    let mut index = 0;
+    let mut unit = 1u64;
+
+    while index < IEC_LABELS.len() - 1 {
+        match unit.checked_mul(base) {
+            Some(next) if base > 1 && bytes >= next => {
+                unit = next;
+                index += 1;
+            }
+            _ => break,
+        }
+    }
+
+    (index, unit)
+}
+
+/// Format a byte size with the default (1024-based) units, e.g. "1.5 KB".
+pub fn format_size(bytes: u64) -> String {
+    format_size_with(bytes, SizeUnits::Iec)
+}
+
+/// Format a byte size in the given units.
+///
+/// # Examples
+/// - `Iec`: 1536 -> "1.5 KB"
+/// - `Si`: 1500 -> "1.5 kB"
+/// - `Bytes`: 1536 -> "1536 B"
+pub fn format_size_with(bytes: u64, units: SizeUnits) -> String {
+    let (index, unit) = scale(bytes, units.base());
+    if index == 0 {
+        return format!("{} B", bytes);
+    }
+
+    let labels = match units {
+        SizeUnits::Si => &SI_LABELS,
+        _ => &IEC_LABELS,
+    };
+    format!("{:.1} {}", bytes as f64 / unit as f64, labels[index])
+}
+
+/// Format a byte size the way `du -h` / `du --si` do.
+///
+/// Values are rounded up: one decimal below 10 ("4.0K"), whole numbers
+/// above ("15K"), and plain byte counts below one unit ("512"). `Bytes`
+/// always prints the exact count.
+pub fn format_size_short(bytes: u64, units: SizeUnits) -> String {
+    let (index, unit) = scale(bytes, units.base());
+    if index == 0 {
+        return bytes.to_string();
+    }
+
+    let labels = match units {
+        SizeUnits::Si => &SI_SHORT_LABELS,
+        _ => &IEC_SHORT_LABELS,
+    };
+
+    let value = bytes as f64 / unit as f64;
+    if value < 10.0 {
+        let tenths = (value * 10.0).ceil() as u64;
+        if tenths < 100 {
+            return format!("{}.{}{}", tenths / 10, tenths % 10, labels[index]);
+        }
+    }
+
+    let whole = value.ceil() as u64;
+    if whole >= units.base() && index + 1 < labels.len() {
+        // Rounding up reached the next unit
+        return format!("1.0{}", labels[index + 1]);
+    }
+    format!("{}{}", whole, labels[index])
+}
+
+/// Split "1.5G" into (1.5, "G")
+fn split_number(s: &str) -> Option<(f64, &str)> {
+    let s = s.trim();
+    let end = s.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(s.len());
+    let value: f64 = s[..end].parse().ok()?;
+    Some((value, s[end..].trim()))
+}
+
+/// Parse a size such as "500M", "1.5G", "2GiB" or "4096".
+///
+/// Suffixes are case-insensitive and always powers of 1024, matching how
+/// sizes are displayed; a trailing "B" or "iB" is optional.
+pub fn parse_size(s: &str) -> Option<u64> {
+    let (value, suffix) = split_number(s)?;
+    let suffix = suffix.to_ascii_uppercase();
+    let unit = suffix
+        .strip_suffix("IB")
+        .or_else(|| suffix.strip_suffix('B'))
+        .unwrap_or(&suffix);
+
+    let index = IEC_SHORT_LABELS.iter().position(|label| *label == unit)?;
+    let bytes = value * 1024f64.powi(index as i32);
+    (bytes < u64::MAX as f64).then_some(bytes as u64)
+}
+
+/// Parse a duration such as "90s", "30m", "12h", "7d" or "2w".
+///
+/// A bare number is taken as seconds.
+pub fn parse_duration(s: &str) -> Option<Duration> {
+    let (value, suffix) = split_number(s)?;
+    let unit = match suffix {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86400,
+        "w" => 7 * 86400,
+        _ => return None,
+    };
+    Duration::try_from_secs_f64(value * unit as f64).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(0), "0 B");
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1023), "1023 B");
+        assert_eq!(format_size(1024), "1.0 KB");
+        assert_eq!(format_size(1536), "1.5 KB");
+        assert_eq!(format_size(1024 * 512), "512.0 KB");
+        assert_eq!(format_size(1024 * 1024), "1.0 MB");
+        assert_eq!(format_size(1024 * 1024 * 100), "100.0 MB");
+        assert_eq!(format_size(1024 * 1024 * 1024), "1.0 GB");
+        assert_eq!(format_size(1024u64 * 1024 * 1024 * 1024), "1.0 TB");
+        assert_eq!(format_size(1024u64 * 1024 * 1024 * 1024 * 5), "5.0 TB");
+        assert_eq!(format_size(u64::MAX), "16.0 EB");
+    }
+
+    #[test]
+    fn test_format_size_with_units() {
+        assert_eq!(format_size_with(1500, SizeUnits::Si), "1.5 kB");
+        assert_eq!(format_size_with(999, SizeUnits::Si), "999 B");
+        assert_eq!(format_size_with(2_000_000_000, SizeUnits::Si), "2.0 GB");
+        assert_eq!(format_size_with(1536, SizeUnits::Bytes), "1536 B");
+    }
+
+    #[test]
+    fn test_format_size_short() {
+        assert_eq!(format_size_short(0, SizeUnits::Iec), "0");
+        assert_eq!(format_size_short(512, SizeUnits::Iec), "512");
+        assert_eq!(format_size_short(4096, SizeUnits::Iec), "4.0K");
+        assert_eq!(format_size_short(1536, SizeUnits::Iec), "1.5K");
+        // Rounded up like du
+        assert_eq!(format_size_short(1025, SizeUnits::Iec), "1.1K");
+        assert_eq!(format_size_short(10 * 1024 + 1, SizeUnits::Iec), "11K");
+        assert_eq!(format_size_short(1024 * 1024 - 1, SizeUnits::Iec), "1.0M");
+        assert_eq!(format_size_short(10 * 1024 - 1, SizeUnits::Iec), "10K");
+        assert_eq!(format_size_short(4096, SizeUnits::Si), "4.1k");
+        assert_eq!(format_size_short(3_500_000, SizeUnits::Si), "3.5M");
+        assert_eq!(format_size_short(4096, SizeUnits::Bytes), "4096");
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("4096"), Some(4096));
+        assert_eq!(parse_size("500M"), Some(500 * 1024 * 1024));
+        assert_eq!(parse_size("1.5k"), Some(1536));
+        assert_eq!(parse_size("2GiB"), Some(2 * 1024 * 1024 * 1024));
+        assert_eq!(parse_size("1 TB"), Some(1024u64.pow(4)));
+        assert_eq!(parse_size("12X"), None);
+        assert_eq!(parse_size("G"), None);
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90"), Some(Duration::from_secs(90)));
+        assert_eq!(parse_duration("30m"), Some(Duration::from_secs(1800)));
+        assert_eq!(parse_duration("1.5h"), Some(Duration::from_secs(5400)));
+        assert_eq!(parse_duration("7d"), Some(Duration::from_secs(7 * 86400)));
+        assert_eq!(parse_duration("2w"), Some(Duration::from_secs(14 * 86400)));
+        assert_eq!(parse_duration("3y"), None);
+        assert_eq!(parse_duration(""), None);
+    }
+}
@@ -10,6 +10,8 @@ use eframe::egui::{self, Color32, Key, Modifiers, Pos2, Rect, RichText, Rounding
 use indextree::NodeId;
 use rfd::FileDialog;
 
+use crate::format::format_size;
+use crate::history::{format_growth, HistoryStore, SizeHistory};
 use crate::scanner::{get_disk_space, DiskSpaceInfo, ScanOptions, ScanProgress, Scanner};
 use crate::tree::FileTree;
 
@@ -141,6 +143,10 @@ pub struct DataXApp {
     // Disk info
     disk_info: Option<DiskSpaceInfo>,
 
+    // Directory sizes from earlier scans
+    history: Option<SizeHistory>,
+    history_receiver: Option<Receiver<SizeHistory>>,
+
     // Treemap
     treemap_rects: Vec<TreemapRect>,
     treemap_root: Option<NodeId>,
@@ -153,6 +159,9 @@ pub struct DataXApp {
 
     // Recent folders (v0.2.0)
     recent_folders: Vec<PathBuf>,
+
+    // Saved scan being browsed read-only (no rescans)
+    loaded_scan: Option<PathBuf>,
 }
 
 #[derive(Clone, PartialEq)]
@@ -201,6 +210,8 @@ impl DataXApp {
             progress_receiver: None,
             scan_started: false,
             disk_info: None,
+            history: None,
+            history_receiver: None,
             treemap_rects: Vec::new(),
             treemap_root: None,
             needs_rebuild: true,
@@ -208,6 +219,7 @@ impl DataXApp {
             show_about: false,
             show_shortcuts: false,
             recent_folders: vec![root_path],
+            loaded_scan: None,
         }
     }
 
@@ -221,14 +233,7 @@ impl DataXApp {
         let (tx, rx) = mpsc::sync_channel(1000);
         self.progress_receiver = Some(rx);
 
-        let options = ScanOptions {
-            root_path: self.root_path.clone(),
-            max_depth: None,
-            exclude_patterns: vec![],
-            cross_mount: true,
-            apparent_size: false,
-        };
-
+        let options = self.scan_options();
         thread::spawn(move || {
             let scanner = Scanner::new(options, tx);
             let _ = scanner.scan();
@@ -251,6 +256,7 @@ impl DataXApp {
                     self.scan_state = ScanState::Complete;
                     self.progress_receiver = None;
                     self.disk_info = get_disk_space(&self.root_path);
+                    self.load_history();
 
                     // Initialize view
                     if let Some(ref tree) = self.tree {
@@ -274,6 +280,41 @@ impl DataXApp {
         }
     }
 
+    /// Options of every scan the GUI makes
+    fn scan_options(&self) -> ScanOptions {
+        ScanOptions {
+            root_path: self.root_path.clone(),
+            max_depth: None,
+            exclude_patterns: vec![],
+            cross_mount: true,
+            apparent_size: false,
+        }
+    }
+
+    /// Read the snapshots of the current root made with the GUI's options
+    /// in the background.
+    fn load_history(&mut self) {
+        let (tx, rx) = mpsc::sync_channel(1);
+        self.history_receiver = Some(rx);
+
+        let options = self.scan_options();
+        thread::spawn(move || {
+            let _ = tx.send(HistoryStore::new().size_history(&options));
+        });
+    }
+
+    fn poll_history(&mut self) {
+        let Some(ref receiver) = self.history_receiver else { return };
+        match receiver.try_recv() {
+            Ok(history) => {
+                self.history = (history.times.len() >= 2).then_some(history);
+                self.history_receiver = None;
+            }
+            Err(TryRecvError::Empty) => {}
+            Err(TryRecvError::Disconnected) => self.history_receiver = None,
+        }
+    }
+
     fn compute_category_stats(&mut self) {
         self.category_stats.clear();
 
@@ -298,19 +339,6 @@ impl DataXApp {
         }
     }
 
-    fn format_size(bytes: u64) -> String {
-        const KB: u64 = 1024;
-        const MB: u64 = KB * 1024;
-        const GB: u64 = MB * 1024;
-        const TB: u64 = GB * 1024;
-
-        if bytes >= TB { format!("{:.1} TB", bytes as f64 / TB as f64) }
-        else if bytes >= GB { format!("{:.1} GB", bytes as f64 / GB as f64) }
-        else if bytes >= MB { format!("{:.1} MB", bytes as f64 / MB as f64) }
-        else if bytes >= KB { format!("{:.1} KB", bytes as f64 / KB as f64) }
-        else { format!("{} B", bytes) }
-    }
-
     // ========================================================================
     // v0.2.0: Folder Picker
     // ========================================================================
@@ -330,7 +358,10 @@ impl DataXApp {
 
         // Reset state
         self.root_path = path;
+        self.loaded_scan = None;
         self.tree = None;
+        self.history = None;
+        self.history_receiver = None;
         self.selected_node = None;
         self.expanded_nodes.clear();
         self.treemap_rects.clear();
@@ -340,6 +371,57 @@ impl DataXApp {
         self.scan_started = false;
     }
 
+    // ========================================================================
+    // Saved Scans
+    // ========================================================================
+
+    fn open_scan_dialog(&mut self) {
+        if let Some(path) = FileDialog::new()
+            .add_filter("data-x scan", &["json", "ndjson", "jsonl"])
+            .pick_file()
+        {
+            match crate::export::load_scan(&path) {
+                Ok(scan) => self.load_scan(scan.tree, path),
+                Err(e) => self.scan_state = ScanState::Error(format!("Cannot load {}: {}", path.display(), e)),
+            }
+        }
+    }
+
+    /// Browse a tree exported with --json/--ndjson instead of scanning.
+    ///
+    /// The tree may describe another machine, so refreshing is disabled
+    /// until a folder is opened again.
+    pub fn load_scan(&mut self, tree: FileTree, source: PathBuf) {
+        let root = tree.root;
+        if let Some(node) = root.and_then(|id| tree.get_node(id)) {
+            self.root_path = node.path.clone();
+            self.scan_progress = ScanProgressInfo {
+                total_files: node.file_count,
+                total_size: node.size,
+                ..Default::default()
+            };
+        }
+
+        self.tree = Some(tree);
+        self.loaded_scan = Some(source);
+        self.scan_state = ScanState::Complete;
+        self.scan_started = true;
+        self.progress_receiver = None;
+        self.disk_info = None;
+        self.history = None;
+        self.history_receiver = None;
+
+        self.expanded_nodes.clear();
+        self.treemap_rects.clear();
+        self.selected_node = root;
+        self.treemap_root = root;
+        if let Some(root) = root {
+            self.expanded_nodes.insert(root);
+        }
+        self.needs_rebuild = true;
+        self.compute_category_stats();
+    }
+
     fn add_to_recent(&mut self, path: &PathBuf) {
         self.recent_folders.retain(|p| p != path);
         self.recent_folders.insert(0, path.clone());
@@ -359,8 +441,8 @@ impl DataXApp {
                 handled = true;
             }
 
-            // Cmd+R - Refresh
-            if i.consume_shortcut(&egui::KeyboardShortcut::new(Modifiers::COMMAND, Key::R)) {
+            // Cmd+R - Refresh (not for saved scans)
+            if i.consume_shortcut(&egui::KeyboardShortcut::new(Modifiers::COMMAND, Key::R)) && self.loaded_scan.is_none() {
                 self.scan_started = false;
                 self.tree = None;
                 self.needs_rebuild = true;
@@ -409,13 +491,7 @@ impl DataXApp {
             return handled;
         }
 
-        ctx.input(|i| {
-            if i.modifiers.command && i.key_pressed(Key::O) {
-                true
-            } else {
-                false
-            }
-        })
+        ctx.input(|i| i.modifiers.command && i.key_pressed(Key::O))
     }
 
     // ========================================================================
@@ -498,6 +574,7 @@ impl DataXApp {
 
     fn render_menu_bar(&mut self, ui: &mut egui::Ui) -> bool {
         let mut open_folder = false;
+        let mut open_scan = false;
 
         egui::menu::bar(ui, |ui| {
             ui.menu_button("File", |ui| {
@@ -506,6 +583,11 @@ impl DataXApp {
                     ui.close_menu();
                 }
 
+                if ui.button("Open Saved Scan...").clicked() {
+                    open_scan = true;
+                    ui.close_menu();
+                }
+
                 ui.menu_button("Recent Folders", |ui| {
                     if self.recent_folders.is_empty() {
                         ui.label("No recent folders");
@@ -524,7 +606,7 @@ impl DataXApp {
 
                 ui.separator();
 
-                if ui.button("Refresh    Cmd+R").clicked() {
+                if ui.add_enabled(self.loaded_scan.is_none(), egui::Button::new("Refresh    Cmd+R")).clicked() {
                     self.scan_started = false;
                     self.tree = None;
                     self.needs_rebuild = true;
@@ -559,6 +641,10 @@ impl DataXApp {
             });
         });
 
+        if open_scan {
+            self.open_scan_dialog();
+        }
+
         open_folder
     }
 
@@ -665,7 +751,22 @@ impl DataXApp {
 
             // Size
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                ui.label(RichText::new(Self::format_size(node.size)).size(11.0).color(Color32::from_rgb(140, 140, 150)));
+                ui.label(RichText::new(format_size(node.size)).size(11.0).color(Color32::from_rgb(140, 140, 150)));
+
+                // Size over time, from earlier scans
+                if let Some(ref history) = self.history {
+                    if let Some(growth) = history.growth_per_day(&node.path) {
+                        let growth_color = if growth > 0.0 {
+                            Color32::from_rgb(220, 140, 90)
+                        } else {
+                            Color32::from_rgb(120, 190, 120)
+                        };
+                        ui.label(RichText::new(format_growth(growth)).size(10.0).color(growth_color));
+                    }
+                    if let Some(sparkline) = history.sparkline(&node.path, 12) {
+                        ui.label(RichText::new(sparkline).size(10.0).color(Color32::from_rgb(70, 130, 200)));
+                    }
+                }
             });
         });
 
@@ -893,7 +994,7 @@ impl DataXApp {
                 };
 
                 let text = if tr.rect.height() > 40.0 {
-                    format!("{}\n{}", name, Self::format_size(tr.size))
+                    format!("{}\n{}", name, format_size(tr.size))
                 } else {
                     name
                 };
@@ -949,7 +1050,7 @@ impl DataXApp {
                 if let Some(node) = tree.get_node(hid) {
                     egui::show_tooltip(ui.ctx(), ui.layer_id(), egui::Id::new("tm_tip"), |ui| {
                         ui.label(RichText::new(&node.name).strong().size(13.0));
-                        ui.label(format!("Size: {}", Self::format_size(node.size)));
+                        ui.label(format!("Size: {}", format_size(node.size)));
                         if node.is_dir {
                             ui.label(format!("Files: {}", node.file_count));
                         } else if let Some(ref ext) = node.extension {
@@ -1045,7 +1146,7 @@ impl DataXApp {
 
                 // Stats text
                 ui.horizontal(|ui| {
-                    ui.label(RichText::new(Self::format_size(stats.size)).size(11.0).color(Color32::from_rgb(180, 180, 190)));
+                    ui.label(RichText::new(format_size(stats.size)).size(11.0).color(Color32::from_rgb(180, 180, 190)));
                     ui.label(RichText::new(format!("{}%", pct)).size(11.0).color(Color32::from_rgb(140, 140, 150)));
                     ui.label(RichText::new(format!("{} files", stats.count)).size(10.0).color(Color32::from_rgb(120, 120, 130)));
                 });
@@ -1069,6 +1170,7 @@ impl eframe::App for DataXApp {
         }
 
         self.poll_progress();
+        self.poll_history();
 
         if self.scan_state == ScanState::Scanning {
             ctx.request_repaint();
@@ -1111,6 +1213,10 @@ impl eframe::App for DataXApp {
                 // Path display
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                     ui.label(RichText::new(self.root_path.display().to_string()).size(12.0).color(Color32::from_rgb(150, 150, 160)));
+                    if let Some(ref source) = self.loaded_scan {
+                        ui.label(RichText::new("Read-only").size(12.0).strong().color(Color32::from_rgb(220, 200, 80)))
+                            .on_hover_text(format!("Loaded from {}", source.display()));
+                    }
                 });
             });
         });
@@ -1124,12 +1230,13 @@ impl eframe::App for DataXApp {
                         ui.label(format!("Scanning... {} files", self.scan_progress.files_found));
                     }
                     ScanState::Complete => {
-                        ui.label(format!("✓ {} files • {}", self.scan_progress.total_files, Self::format_size(self.scan_progress.total_size)));
+                        ui.label(format!("✓ {} files • {}", self.scan_progress.total_files, format_size(self.scan_progress.total_size)));
                         if let Some(ref d) = self.disk_info {
                             ui.separator();
-                            ui.label(format!("Disk: {} / {}", Self::format_size(d.used), Self::format_size(d.total)));
+                            ui.label(format!("Disk: {} / {}", format_size(d.used), format_size(d.total)));
                         }
                     }
+                    ScanState::Error(e) => { ui.label(RichText::new(e).color(Color32::from_rgb(220, 80, 80))); }
                     _ => { ui.label("Ready"); }
                 }
 
@@ -1137,7 +1244,7 @@ impl eframe::App for DataXApp {
                     if let Some(nid) = self.selected_node {
                         if let Some(ref tree) = self.tree {
                             if let Some(node) = tree.get_node(nid) {
-                                ui.label(RichText::new(format!("{} • {}", node.name, Self::format_size(node.size))).size(12.0));
+                                ui.label(RichText::new(format!("{} • {}", node.name, format_size(node.size))).size(12.0));
                             }
                         }
                     }
@@ -1203,6 +1310,8 @@ impl Default for DataXApp {
             progress_receiver: None,
             scan_started: false,
             disk_info: None,
+            history: None,
+            history_receiver: None,
             treemap_rects: Vec::new(),
             treemap_root: None,
             needs_rebuild: true,
@@ -1210,6 +1319,7 @@ impl Default for DataXApp {
             show_about: false,
             show_shortcuts: false,
             recent_folders: Vec::new(),
+            loaded_scan: None,
         }
     }
 }
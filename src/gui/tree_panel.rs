@@ -9,6 +9,7 @@ use std::collections::HashSet;
 
 use egui::{Color32, Key, Response, Sense, Ui, Vec2};
 
+use crate::format::format_size;
 use crate::tree::{FileNode, FileTree, NodeId};
 
 /// Size thresholds for file categorization (matching TUI colors.rs)
@@ -246,26 +247,6 @@ impl TreePanelColors {
     }
 }
 
-/// Format a byte size into a human-readable string.
-pub fn format_size(bytes: u64) -> String {
-    const KB: u64 = 1024;
-    const MB: u64 = KB * 1024;
-    const GB: u64 = MB * 1024;
-    const TB: u64 = GB * 1024;
-
-    if bytes >= TB {
-        format!("{:.1} TB", bytes as f64 / TB as f64)
-    } else if bytes >= GB {
-        format!("{:.1} GB", bytes as f64 / GB as f64)
-    } else if bytes >= MB {
-        format!("{:.1} MB", bytes as f64 / MB as f64)
-    } else if bytes >= KB {
-        format!("{:.1} KB", bytes as f64 / KB as f64)
-    } else {
-        format!("{} B", bytes)
-    }
-}
-
 /// Get the appropriate icon for a file node.
 fn get_icon(node: &FileNode, is_expanded: bool) -> &'static str {
     if node.is_symlink {
@@ -745,16 +726,6 @@ mod tests {
         assert!(state.is_expanded(root));
     }
 
-    #[test]
-    fn test_format_size() {
-        assert_eq!(format_size(0), "0 B");
-        assert_eq!(format_size(512), "512 B");
-        assert_eq!(format_size(1024), "1.0 KB");
-        assert_eq!(format_size(1024 * 1024), "1.0 MB");
-        assert_eq!(format_size(1024 * 1024 * 1024), "1.0 GB");
-        assert_eq!(format_size(1024u64 * 1024 * 1024 * 1024), "1.0 TB");
-    }
-
     #[test]
     fn test_colors_default() {
         let colors = TreePanelColors::default();
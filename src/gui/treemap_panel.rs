@@ -7,6 +7,8 @@
 
 use egui::{Color32, Pos2, Rect, Response, Sense, Stroke, Ui, Vec2};
 
+use crate::format::format_size;
+use crate::render::squarify;
 use crate::tree::{FileTree, NodeId};
 
 /// File type category for coloring.
@@ -308,13 +310,13 @@ impl<'a> TreemapPanel<'a> {
 
         // Calculate treemap layout using squarified algorithm
         let total_size: u64 = items.iter().map(|(_, s, _, _, _, _)| *s).sum();
+        let sizes: Vec<u64> = items.iter().map(|(_, s, _, _, _, _)| *s).collect();
         let layout_rects = squarify(
-            &items,
-            rect.min.x,
-            rect.min.y,
-            rect.width(),
-            rect.height(),
-            total_size,
+            &sizes,
+            rect.min.x as f64,
+            rect.min.y as f64,
+            rect.width() as f64,
+            rect.height() as f64,
         );
 
         // Draw background
@@ -323,7 +325,8 @@ impl<'a> TreemapPanel<'a> {
         // Build and cache TreemapRects
         let mut cached_rects = Vec::new();
 
-        for (node_id, size, name, is_dir, extension, path, x, y, w, h) in layout_rects {
+        for ((node_id, size, name, is_dir, extension, path), (x, y, w, h)) in items.into_iter().zip(layout_rects) {
+            let (x, y, w, h) = (x as f32, y as f32, w as f32, h as f32);
             // Skip very small rectangles
             if w < self.min_rect_size || h < self.min_rect_size {
                 continue;
@@ -518,170 +521,6 @@ fn truncate_name(s: &str, max_chars: usize) -> String {
     }
 }
 
-/// Format a byte size into a human-readable string.
-fn format_size(bytes: u64) -> String {
-    const KB: u64 = 1024;
-    const MB: u64 = KB * 1024;
-    const GB: u64 = MB * 1024;
-    const TB: u64 = GB * 1024;
-
-    if bytes >= TB {
-        format!("{:.1} TB", bytes as f64 / TB as f64)
-    } else if bytes >= GB {
-        format!("{:.1} GB", bytes as f64 / GB as f64)
-    } else if bytes >= MB {
-        format!("{:.1} MB", bytes as f64 / MB as f64)
-    } else if bytes >= KB {
-        format!("{:.1} KB", bytes as f64 / KB as f64)
-    } else {
-        format!("{} B", bytes)
-    }
-}
-
-/// Squarified treemap algorithm.
-/// Returns: Vec<(NodeId, size, name, is_dir, extension, path, x, y, width, height)>
-fn squarify(
-    items: &[(NodeId, u64, String, bool, Option<String>, String)],
-    x: f32,
-    y: f32,
-    width: f32,
-    height: f32,
-    total_size: u64,
-) -> Vec<(NodeId, u64, String, bool, Option<String>, String, f32, f32, f32, f32)> {
-    let mut result = Vec::new();
-
-    if items.is_empty() || total_size == 0 || width <= 0.0 || height <= 0.0 {
-        return result;
-    }
-
-    // Normalize sizes to area
-    let area = (width * height) as f64;
-    let scale = area / total_size as f64;
-
-    // Use squarified algorithm with row-based layout
-    let mut remaining: Vec<_> = items.to_vec();
-    let mut current_x = x;
-    let mut current_y = y;
-    let mut current_width = width;
-    let mut current_height = height;
-
-    while !remaining.is_empty() {
-        // Determine layout direction (lay out along the shorter side)
-        let horizontal = current_width >= current_height;
-        let side = if horizontal { current_height } else { current_width };
-
-        // Find the best row
-        let (row, rest) = find_best_row(&remaining, side as f64, scale);
-
-        if row.is_empty() {
-            break;
-        }
-
-        // Calculate total area for this row
-        let row_area: f64 = row.iter().map(|(_, size, _, _, _, _)| *size as f64 * scale).sum();
-        let row_size = if side > 0.0 { (row_area / side as f64) as f32 } else { 0.0 };
-
-        // Layout items in the row
-        let mut offset = 0.0f32;
-        for (node_id, size, name, is_dir, extension, path) in &row {
-            let item_area = *size as f64 * scale;
-            let item_size = if row_size > 0.0 { (item_area / row_size as f64) as f32 } else { 0.0 };
-
-            let (rx, ry, rw, rh) = if horizontal {
-                (current_x, current_y + offset, row_size, item_size)
-            } else {
-                (current_x + offset, current_y, item_size, row_size)
-            };
-
-            result.push((
-                *node_id,
-                *size,
-                name.clone(),
-                *is_dir,
-                extension.clone(),
-                path.clone(),
-                rx,
-                ry,
-                rw,
-                rh,
-            ));
-
-            offset += item_size;
-        }
-
-        // Update remaining area
-        if horizontal {
-            current_x += row_size;
-            current_width -= row_size;
-        } else {
-            current_y += row_size;
-            current_height -= row_size;
-        }
-
-        remaining = rest;
-    }
-
-    result
-}
-
-/// Find the best row of items to lay out along a side.
-/// Returns (items in row, remaining items).
-fn find_best_row(
-    items: &[(NodeId, u64, String, bool, Option<String>, String)],
-    side: f64,
-    scale: f64,
-) -> (Vec<(NodeId, u64, String, bool, Option<String>, String)>, Vec<(NodeId, u64, String, bool, Option<String>, String)>) {
-    if items.is_empty() {
-        return (vec![], vec![]);
-    }
-
-    let mut row: Vec<(NodeId, u64, String, bool, Option<String>, String)> = Vec::new();
-    let mut row_area = 0.0f64;
-    let mut best_ratio = f64::MAX;
-
-    for (i, item) in items.iter().enumerate() {
-        let item_area = item.1 as f64 * scale;
-        let new_row_area = row_area + item_area;
-
-        // Calculate worst aspect ratio if we add this item
-        let row_size = new_row_area / side;
-        let mut worst_ratio = 0.0f64;
-
-        // Check existing items in row
-        for existing in &row {
-            let existing_area = existing.1 as f64 * scale;
-            let item_size = existing_area / row_size;
-            let ratio = if item_size > row_size {
-                item_size / row_size
-            } else {
-                row_size / item_size
-            };
-            worst_ratio = worst_ratio.max(ratio);
-        }
-
-        // Check new item
-        let new_item_size = item_area / row_size;
-        let new_ratio = if new_item_size > row_size {
-            new_item_size / row_size
-        } else {
-            row_size / new_item_size
-        };
-        worst_ratio = worst_ratio.max(new_ratio);
-
-        if worst_ratio <= best_ratio || row.is_empty() {
-            // Adding this item improves or maintains the aspect ratio
-            row.push(item.clone());
-            row_area = new_row_area;
-            best_ratio = worst_ratio;
-        } else {
-            // Adding this item makes it worse, stop here
-            return (row, items[i..].to_vec());
-        }
-    }
-
-    (row, vec![])
-}
-
 #[cfg(all(test, feature = "gui"))]
 mod tests {
     use super::*;
@@ -737,15 +576,6 @@ mod tests {
         assert_eq!(truncate_name("hello", 2), "he");
     }
 
-    #[test]
-    fn test_format_size() {
-        assert_eq!(format_size(0), "0 B");
-        assert_eq!(format_size(512), "512 B");
-        assert_eq!(format_size(1024), "1.0 KB");
-        assert_eq!(format_size(1024 * 1024), "1.0 MB");
-        assert_eq!(format_size(1024 * 1024 * 1024), "1.0 GB");
-    }
-
     #[test]
     fn test_base_colors_distinct() {
         let categories = [
@@ -0,0 +1,474 @@
+//! Scan history: timestamped snapshots per root directory.
+//!
+//! Each snapshot is a complete tree in the binary cache format, stored as
+//! `history/<hash>/<scan_time>-<nanos>-<pid>.dxc` next to the scan cache,
+//! with the options of its scan. The per-directory size series built from
+//! the snapshots made with the same options drive the sparklines and
+//! growth column in the TUI and GUI. They cover the root and the two levels
+//! below it, which a cache file stores first, so the rest of each snapshot
+//! is never read for them.
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::cache::{header_for, open_cache_file, options_cover, write_cache_file, CacheHeader, CacheManager, CacheReader};
+use crate::export::scan_headless;
+use crate::format::format_size;
+use crate::scanner::{ScanOptions, Scanner};
+use crate::tree::FileTree;
+
+/// How many snapshots are kept per root directory
+#[derive(Debug, Clone, Copy)]
+pub struct Retention {
+    /// Newest snapshots to keep
+    pub keep: usize,
+    /// Drop snapshots older than this
+    pub max_age: Option<Duration>,
+}
+
+impl Default for Retention {
+    fn default() -> Self {
+        Self {
+            keep: 30,
+            max_age: Some(Duration::from_secs(365 * 86400)),
+        }
+    }
+}
+
+/// One recorded scan
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub file_path: PathBuf,
+    pub header: CacheHeader,
+}
+
+/// Stores snapshots under the cache directory.
+pub struct HistoryStore {
+    dir: PathBuf,
+}
+
+impl HistoryStore {
+    /// Create a store in the default location (`$XDG_CACHE_HOME/data-x/history`).
+    pub fn new() -> Self {
+        Self::with_dir(CacheManager::new().cache_dir().join("history"))
+    }
+
+    /// Create a store that keeps its files in `dir`.
+    pub fn with_dir(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Directory holding the snapshots of `root_path`.
+    ///
+    /// Roots with colliding hashes share it; every snapshot records its root
+    /// and `snapshots` only returns the matching ones.
+    fn root_dir(&self, root_path: &Path) -> PathBuf {
+        self.dir.join(format!("{:016x}", CacheManager::path_hash(root_path)))
+    }
+
+    /// Record a completed scan made with `options` and apply the retention
+    /// policy.
+    pub fn record(
+        &self,
+        tree: &FileTree,
+        options: &ScanOptions,
+        retention: Retention,
+    ) -> Result<Snapshot, std::io::Error> {
+        let root_path = &options.root_path;
+        let dir = self.root_dir(root_path);
+        fs::create_dir_all(&dir)?;
+
+        let header = header_for(tree, root_path).with_scan(Some(options), None);
+        // Scan times are whole seconds; the rest keeps scans in the same
+        // second, even from two processes, from replacing each other
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().subsec_nanos();
+        let file_path = dir.join(format!("{}-{:09}-{}.dxc", header.scan_time, nanos, std::process::id()));
+        write_cache_file(&file_path, tree, &header)?;
+
+        self.apply_retention(root_path, retention)?;
+        Ok(Snapshot { file_path, header })
+    }
+
+    /// Scan with `options` and record the result.
+    pub fn record_scan(&self, options: &ScanOptions, retention: Retention) -> anyhow::Result<Snapshot> {
+        let tree = scan_headless(false, |tx| Ok(Scanner::new(options.clone(), tx).scan()?))?;
+        Ok(self.record(&tree, options, retention)?)
+    }
+
+    /// Snapshots of `root_path`, oldest first.
+    pub fn snapshots(&self, root_path: &Path) -> Vec<Snapshot> {
+        let Ok(entries) = fs::read_dir(self.root_dir(root_path)) else { return Vec::new() };
+
+        let mut snapshots: Vec<Snapshot> = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "dxc"))
+            .filter_map(|file_path| {
+                let header = open_cache_file(&file_path)?.header().clone();
+                (header.root_path == root_path).then_some(Snapshot { file_path, header })
+            })
+            .collect();
+        snapshots.sort_by(|a, b| (a.header.scan_time, &a.file_path).cmp(&(b.header.scan_time, &b.file_path)));
+        snapshots
+    }
+
+    /// Snapshots of `options.root_path` made with the same exclusions,
+    /// depth and size options, oldest first.
+    ///
+    /// Others found a different set of files, so sizes can't be compared
+    /// with them.
+    pub fn comparable_snapshots(&self, options: &ScanOptions) -> Vec<Snapshot> {
+        let mut snapshots = self.snapshots(&options.root_path);
+        snapshots.retain(|snapshot| options_cover(snapshot.header.options.as_ref(), options, 0));
+        snapshots
+    }
+
+    /// Read the full tree of a snapshot.
+    pub fn load(&self, snapshot: &Snapshot) -> Result<FileTree, std::io::Error> {
+        open_snapshot(snapshot)?.finish()
+    }
+
+    /// Read the root of a snapshot and the two levels below it.
+    fn load_top_levels(&self, snapshot: &Snapshot) -> Result<FileTree, std::io::Error> {
+        let mut reader = open_snapshot(snapshot)?;
+        reader.read_block()?;
+        Ok(reader.snapshot())
+    }
+
+    /// Remove snapshots of `root_path` beyond what `retention` keeps.
+    ///
+    /// # Returns
+    /// The number of snapshots removed
+    pub fn apply_retention(&self, root_path: &Path, retention: Retention) -> Result<usize, std::io::Error> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let snapshots = self.snapshots(root_path);
+        let excess = snapshots.len().saturating_sub(retention.keep);
+
+        let mut removed = 0;
+        for (index, snapshot) in snapshots.iter().enumerate() {
+            let too_old = retention
+                .max_age
+                .is_some_and(|max_age| now.saturating_sub(snapshot.header.scan_time) > max_age.as_secs());
+            if index < excess || too_old {
+                fs::remove_file(&snapshot.file_path)?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Size of the root and the directories up to two levels below it
+    /// across the snapshots comparable to a scan with `options`.
+    pub fn size_history(&self, options: &ScanOptions) -> SizeHistory {
+        let mut history = SizeHistory::default();
+        for snapshot in self.comparable_snapshots(options) {
+            if let Ok(tree) = self.load_top_levels(&snapshot) {
+                history.push(snapshot.header.scan_time, &tree);
+            }
+        }
+        history
+    }
+}
+
+fn open_snapshot(snapshot: &Snapshot) -> Result<CacheReader<BufReader<File>>, std::io::Error> {
+    open_cache_file(&snapshot.file_path)
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidData, "unreadable snapshot"))
+}
+
+impl Default for HistoryStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Directory sizes over time, one column per snapshot.
+#[derive(Debug, Clone, Default)]
+pub struct SizeHistory {
+    /// Scan times (Unix seconds), oldest first
+    pub times: Vec<u64>,
+    /// Size per snapshot; None where the directory didn't exist
+    series: HashMap<PathBuf, Vec<Option<u64>>>,
+}
+
+impl SizeHistory {
+    /// Add the directories of one snapshot, taken at `scan_time`.
+    pub fn push(&mut self, scan_time: u64, tree: &FileTree) {
+        let column = self.times.len();
+        self.times.push(scan_time);
+
+        if let Some(root) = tree.root {
+            for node in root.descendants(&tree.arena).filter_map(|id| tree.get_node(id)) {
+                if !node.is_dir {
+                    continue;
+                }
+                let series = self.series.entry(node.path.clone()).or_default();
+                series.resize(column, None);
+                series.push(Some(node.size));
+            }
+        }
+        for series in self.series.values_mut() {
+            series.resize(column + 1, None);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.times.is_empty()
+    }
+
+    /// Sizes of `path` in the snapshots where it exists, as (time, size)
+    fn points(&self, path: &Path) -> Vec<(u64, u64)> {
+        self.series
+            .get(path)
+            .map(|series| {
+                self.times
+                    .iter()
+                    .zip(series)
+                    .filter_map(|(&time, size)| size.map(|size| (time, size)))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Average growth of `path` in bytes per day, between its first and
+    /// last snapshot. None with fewer than two snapshots.
+    pub fn growth_per_day(&self, path: &Path) -> Option<f64> {
+        let points = self.points(path);
+        let (&(first_time, first_size), &(last_time, last_size)) = (points.first()?, points.last()?);
+        if last_time <= first_time {
+            return None;
+        }
+        let days = (last_time - first_time) as f64 / 86400.0;
+        Some((last_size as f64 - first_size as f64) / days)
+    }
+
+    /// Sparkline of the last `width` sizes of `path`.
+    pub fn sparkline(&self, path: &Path, width: usize) -> Option<String> {
+        let points = self.points(path);
+        if points.len() < 2 {
+            return None;
+        }
+        let sizes: Vec<u64> = points[points.len().saturating_sub(width)..].iter().map(|&(_, size)| size).collect();
+        Some(sparkline(&sizes))
+    }
+}
+
+/// Draw values as a line of block characters, scaled from min to max.
+pub fn sparkline(values: &[u64]) -> String {
+    const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let min = values.iter().copied().min().unwrap_or(0);
+    let max = values.iter().copied().max().unwrap_or(0);
+
+    values
+        .iter()
+        .map(|&value| {
+            if max == min {
+                BLOCKS[0]
+            } else {
+                let level = (value - min) as f64 / (max - min) as f64 * (BLOCKS.len() - 1) as f64;
+                BLOCKS[level.round() as usize]
+            }
+        })
+        .collect()
+}
+
+/// Format a growth rate, e.g. "+1.5 GB/d" or "-200.0 KB/d".
+pub fn format_growth(bytes_per_day: f64) -> String {
+    let sign = if bytes_per_day < 0.0 { "-" } else { "+" };
+    format!("{}{}/d", sign, format_size(bytes_per_day.abs().round() as u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::FileNode;
+    use tempfile::TempDir;
+
+    fn create_tree(log_size: u64) -> FileTree {
+        let mut tree = FileTree::with_root(PathBuf::from("/srv"));
+        let root = tree.root.unwrap();
+        let logs = tree.add_child(root, FileNode::new(PathBuf::from("/srv/logs"), true));
+        tree.add_child(logs, FileNode::new(PathBuf::from("/srv/logs/app.log"), false).with_size(log_size));
+        tree.calculate_sizes();
+        tree
+    }
+
+    fn srv_options() -> ScanOptions {
+        ScanOptions {
+            root_path: PathBuf::from("/srv"),
+            max_depth: None,
+            exclude_patterns: vec![],
+            cross_mount: true,
+            apparent_size: false,
+        }
+    }
+
+    fn write_snapshot(store: &HistoryStore, tree: &FileTree, scan_time: u64) {
+        let root_path = Path::new("/srv");
+        let dir = store.root_dir(root_path);
+        fs::create_dir_all(&dir).unwrap();
+        let header = CacheHeader {
+            scan_time,
+            ..header_for(tree, root_path).with_scan(Some(&srv_options()), None)
+        };
+        write_cache_file(&dir.join(format!("{}.dxc", scan_time)), tree, &header).unwrap();
+    }
+
+    #[test]
+    fn test_record_and_retention() {
+        let dir = TempDir::new().unwrap();
+        let store = HistoryStore::with_dir(dir.path().to_path_buf());
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+
+        for (days_ago, size) in [(400, 1), (3, 10), (2, 20), (1, 30)] {
+            write_snapshot(&store, &create_tree(size), now - days_ago * 86400);
+        }
+        assert_eq!(store.snapshots(Path::new("/srv")).len(), 4);
+        assert!(store.snapshots(Path::new("/other")).is_empty());
+
+        // The year-old one expires, then only the newest three are kept
+        let retention = Retention { keep: 3, max_age: Some(Duration::from_secs(365 * 86400)) };
+        store.record(&create_tree(40), &srv_options(), retention).unwrap();
+
+        let sizes: Vec<u64> = store
+            .snapshots(Path::new("/srv"))
+            .iter()
+            .map(|snapshot| snapshot.header.total_size)
+            .collect();
+        assert_eq!(sizes, [20, 30, 40]);
+    }
+
+    #[test]
+    fn test_snapshots_in_the_same_second() {
+        let dir = TempDir::new().unwrap();
+        let store = HistoryStore::with_dir(dir.path().to_path_buf());
+        for size in [10, 20, 30] {
+            store.record(&create_tree(size), &srv_options(), Retention::default()).unwrap();
+        }
+
+        let sizes: Vec<u64> = store
+            .snapshots(Path::new("/srv"))
+            .iter()
+            .map(|snapshot| snapshot.header.total_size)
+            .collect();
+        assert_eq!(sizes.len(), 3);
+    }
+
+    #[test]
+    fn test_record_scan_of_many_entries() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().join("srv");
+        fs::create_dir(&root).unwrap();
+        // Enough entries for the scanner's throttled progress to overflow
+        // the channel, about one message per 50 nodes
+        for i in 0..40_000 {
+            fs::File::create(root.join(i.to_string())).unwrap();
+        }
+        let options = ScanOptions {
+            root_path: root,
+            ..srv_options()
+        };
+
+        // A scan blocked on its progress never answers
+        let (tx, rx) = std::sync::mpsc::channel();
+        let store = HistoryStore::with_dir(dir.path().join("history"));
+        let scan_options = options.clone();
+        std::thread::spawn(move || {
+            let _ = tx.send(store.record_scan(&scan_options, Retention::default()).map(|s| s.header.total_files));
+        });
+        let total_files = rx.recv_timeout(Duration::from_secs(60)).expect("scan blocked").unwrap();
+        assert_eq!(total_files, 40_000);
+    }
+
+    #[test]
+    fn test_growth_and_sparkline() {
+        let dir = TempDir::new().unwrap();
+        let store = HistoryStore::with_dir(dir.path().to_path_buf());
+        for (day, size) in [(0, 1000), (1, 1500), (2, 1200), (4, 3000)] {
+            write_snapshot(&store, &create_tree(size), 1_700_000_000 + day * 86400);
+        }
+
+        let history = store.size_history(&srv_options());
+        assert_eq!(history.times.len(), 4);
+        assert_eq!(history.growth_per_day(Path::new("/srv/logs")), Some(500.0));
+        assert_eq!(history.sparkline(Path::new("/srv/logs"), 8).unwrap(), "▁▃▂█");
+        assert_eq!(history.sparkline(Path::new("/srv/logs"), 2).unwrap(), "▁█");
+        // Files have no series of their own
+        assert_eq!(history.growth_per_day(Path::new("/srv/logs/app.log")), None);
+    }
+
+    #[test]
+    fn test_only_comparable_snapshots() {
+        let dir = TempDir::new().unwrap();
+        let store = HistoryStore::with_dir(dir.path().to_path_buf());
+        let excluding = ScanOptions {
+            exclude_patterns: vec!["*.log".to_string()],
+            ..srv_options()
+        };
+        store.record(&create_tree(100), &srv_options(), Retention::default()).unwrap();
+        store.record(&create_tree(0), &excluding, Retention::default()).unwrap();
+        store.record(&create_tree(200), &srv_options(), Retention::default()).unwrap();
+
+        assert_eq!(store.snapshots(Path::new("/srv")).len(), 3);
+        let sizes: Vec<u64> = store
+            .comparable_snapshots(&srv_options())
+            .iter()
+            .map(|snapshot| snapshot.header.total_size)
+            .collect();
+        assert_eq!(sizes, [100, 200]);
+        assert_eq!(store.size_history(&excluding).times.len(), 1);
+        let deeper = ScanOptions {
+            max_depth: Some(3),
+            ..srv_options()
+        };
+        assert!(store.comparable_snapshots(&deeper).is_empty());
+    }
+
+    #[test]
+    fn test_size_history_reads_top_levels() {
+        let dir = TempDir::new().unwrap();
+        let store = HistoryStore::with_dir(dir.path().to_path_buf());
+        for size in [100, 300] {
+            let mut tree = create_tree(size);
+            let mut parent = tree.root.unwrap();
+            for path in ["/srv/a", "/srv/a/b", "/srv/a/b/c"] {
+                parent = tree.add_child(parent, FileNode::new(PathBuf::from(path), true));
+            }
+            tree.add_child(parent, FileNode::new(PathBuf::from("/srv/a/b/c/f"), false).with_size(size));
+            tree.calculate_sizes();
+            store.record(&tree, &srv_options(), Retention::default()).unwrap();
+        }
+
+        let history = store.size_history(&srv_options());
+        assert_eq!(history.sparkline(Path::new("/srv"), 8).unwrap(), "▁█");
+        assert_eq!(history.sparkline(Path::new("/srv/a/b"), 8).unwrap(), "▁█");
+        assert_eq!(history.sparkline(Path::new("/srv/a/b/c"), 8), None);
+    }
+
+    #[test]
+    fn test_directory_added_later() {
+        let mut history = SizeHistory::default();
+        history.push(0, &create_tree(100));
+
+        let mut tree = create_tree(100);
+        let root = tree.root.unwrap();
+        tree.add_child(root, FileNode::new(PathBuf::from("/srv/new"), true).with_size(50));
+        history.push(86400, &tree);
+        history.push(2 * 86400, &tree);
+
+        assert_eq!(history.growth_per_day(Path::new("/srv/new")), Some(0.0));
+        assert_eq!(history.sparkline(Path::new("/srv/new"), 8).unwrap(), "▁▁");
+        assert_eq!(history.growth_per_day(Path::new("/srv/logs")), Some(0.0));
+    }
+
+    #[test]
+    fn test_format_growth() {
+        assert_eq!(format_growth(1536.0), "+1.5 KB/d");
+        assert_eq!(format_growth(-2048.0), "-2.0 KB/d");
+        assert_eq!(format_growth(0.0), "+0 B/d");
+    }
+}
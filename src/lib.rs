@@ -0,0 +1,10 @@
+//! Data-X core library.
+//!
+//! Exposes the directory scanner and file tree so that other front-ends
+//! (such as the Tauri desktop app) report the same numbers as the CLI.
+
+pub mod format;
+pub mod scanner;
+pub mod shell;
+pub mod ssh_config;
+pub mod tree;
@@ -1,20 +1,25 @@
+mod actions;
+mod agent;
 mod app;
 mod cache;
 mod export;
+mod fleet;
 #[cfg(feature = "gui")]
 mod gui;
+mod history;
 mod remote;
-mod scanner;
-mod tree;
+mod render;
 mod ui;
 
+use data_x::{format, scanner, shell, ssh_config, tree};
+
 use std::io::{self, Write};
 use std::panic;
 use std::path::PathBuf;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use anyhow::Result;
-use clap::Parser;
+use clap::{Arg, ArgAction, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
 use crossterm::{
     event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyEventKind},
     execute,
@@ -25,21 +30,51 @@ use ratatui::{backend::CrosstermBackend, Terminal};
 use cache::CacheManager;
 
 use app::App;
-use export::{export_json, ExportOptions};
+use export::{
+    export_csv, export_du, export_html, export_json, export_ndjson, export_prometheus, Column, CsvOptions,
+    Delimiter, DuOptions, ExportOptions, MetricsOptions,
+};
+use export::scan_headless;
+use format::SizeUnits;
+use render::{render_treemap, ImageFormat, RenderOptions};
 use scanner::ScanOptions;
+use tree::FileTree;
 use ui::{handle_key, ColorScheme};
 
+/// Output format for non-interactive export
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum OutputFormat {
+    Json,
+    /// One JSON record per line, streamed
+    Ndjson,
+    Csv,
+    Tsv,
+}
+
+/// File formats written by --export
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum ExportTarget {
+    /// SQLite database with `nodes` and `metadata` tables
+    Sqlite,
+}
+
 #[derive(Parser, Debug)]
 #[command(name = "data-x")]
 #[command(author = "Cassel")]
 #[command(version = "0.4.1")]
 #[command(about = "TUI disk analyzer with colorful visualization", long_about = None)]
+#[command(args_conflicts_with_subcommands = true)]
+// -h is du's --human-readable; without --du it still prints help (see parse_args)
+#[command(disable_help_flag = true)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Directory to analyze (default: current directory)
     #[arg(default_value = ".")]
     path: PathBuf,
 
-    /// Maximum depth to scan
+    /// Maximum depth to scan (with --du: deepest level printed, like du -d)
     #[arg(short, long)]
     depth: Option<usize>,
 
@@ -47,18 +82,98 @@ struct Args {
     #[arg(short = 'x', long = "exclude", action = clap::ArgAction::Append)]
     exclude: Vec<String>,
 
-    /// Output JSON instead of TUI
+    /// Output JSON instead of TUI (same as --format json)
     #[arg(long)]
     json: bool,
 
-    /// Show only N largest items (with --json)
+    /// Stream newline-delimited JSON, one record per node (same as --format ndjson)
+    #[arg(long)]
+    ndjson: bool,
+
+    /// Output format instead of TUI: json, ndjson, csv, tsv
+    #[arg(long, value_enum)]
+    format: Option<OutputFormat>,
+
+    /// Columns for csv/tsv output (comma-separated): path,size,files,mtime,ext,depth,is_dir (default: all)
+    #[arg(long, value_delimiter = ',')]
+    columns: Option<Vec<Column>>,
+
+    /// Show only N largest items (with --json/--format)
     #[arg(short = 'n', long)]
     top: Option<usize>,
 
+    /// Report scan progress on stderr as JSON lines (with --json/--format/--du)
+    #[arg(long)]
+    progress_json: bool,
+
+    /// Write the scan to FILE in FORMAT (sqlite) instead of opening the TUI
+    #[arg(long, num_args = 2, value_names = ["FORMAT", "FILE"])]
+    export: Vec<String>,
+
+    /// Write a self-contained HTML report to FILE instead of opening the TUI
+    #[arg(long, value_name = "FILE")]
+    report: Option<PathBuf>,
+
+    /// Render the treemap to an image FILE (.svg or .png) instead of opening the TUI
+    #[arg(long, value_name = "FILE")]
+    render: Option<PathBuf>,
+
+    /// Width of the rendered image in pixels (with --render)
+    #[arg(long, value_name = "PX", default_value = "1600", value_parser = clap::value_parser!(u32).range(1..=8192))]
+    width: u32,
+
+    /// Height of the rendered image in pixels (with --render)
+    #[arg(long, value_name = "PX", default_value = "1000", value_parser = clap::value_parser!(u32).range(1..=8192))]
+    height: u32,
+
+    /// Directory levels nested in the rendered image (with --render)
+    #[arg(long, value_name = "N", default_value = "3")]
+    render_depth: usize,
+
+    /// Don't draw labels in the rendered image
+    #[arg(long)]
+    no_labels: bool,
+
+    /// Print sizes like du (1K blocks, directories after their contents) instead of opening the TUI
+    #[arg(long, conflicts_with_all = ["json", "ndjson", "format"])]
+    du: bool,
+
+    /// Print only the total for the root (with --du, like du -s)
+    #[arg(short, long, requires = "du", conflicts_with_all = ["all", "depth"])]
+    summarize: bool,
+
+    /// Print files too, not only directories (with --du)
+    #[arg(short, long, requires = "du")]
+    all: bool,
+
+    /// Print sizes like 1.5K, 12M, 3.0G in powers of 1024 (with --du)
+    #[arg(short = 'h', long, requires = "du")]
+    human_readable: bool,
+
+    /// Like --human-readable, but in powers of 1000 (with --du)
+    #[arg(long, requires = "du", conflicts_with = "human_readable")]
+    si: bool,
+
+    /// Print apparent sizes in bytes (with --du, like du -b)
+    #[arg(short, long, requires = "du", conflicts_with_all = ["human_readable", "si"])]
+    bytes: bool,
+
     /// Don't cross filesystem boundaries
     #[arg(long)]
     no_cross_mount: bool,
 
+    /// SSH jump host for remote targets (can be repeated)
+    #[arg(short = 'J', long = "jump", value_name = "HOST", action = clap::ArgAction::Append)]
+    jump: Vec<String>,
+
+    /// Extra ssh option for remote targets (can be repeated)
+    #[arg(short = 'o', long = "ssh-option", value_name = "OPTION", action = clap::ArgAction::Append)]
+    ssh_options: Vec<String>,
+
+    /// Compression of remote scan output: auto, zstd, gzip, ssh or none
+    #[arg(long, value_enum, value_name = "MODE", default_value = "auto")]
+    compress: remote::Compression,
+
     /// Use apparent size instead of disk usage
     #[arg(long)]
     apparent_size: bool,
@@ -83,48 +198,389 @@ struct Args {
     #[arg(long)]
     clear_cache: bool,
 
+    /// Ignore caches older than this, e.g. 12h or 30d [default: 7d]
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration_arg)]
+    cache_max_age: Option<Duration>,
+
+    /// Record the finished scan in the scan history (see `data-x history`)
+    #[arg(long)]
+    history: bool,
+
     /// Force TUI mode (default is GUI when gui feature is enabled)
     #[arg(long)]
     tui: bool,
+
+    /// Browse a saved --json/--ndjson scan read-only instead of scanning PATH
+    #[arg(long, value_name = "FILE")]
+    load: Option<PathBuf>,
+
+    /// Print help (also -h, without --du)
+    #[arg(long, action = clap::ArgAction::Help)]
+    help: Option<bool>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Write directory sizes as Prometheus gauges (node_exporter textfile format)
+    Metrics(MetricsArgs),
+    /// List, inspect and clean up cached scans
+    Cache(CacheArgs),
+    /// List recorded snapshots of a directory and what changed between them
+    History(HistoryArgs),
+    /// Scan the same path on many hosts over SSH
+    Fleet(FleetArgs),
+    /// Answer listing, scan, stat and delete requests, for remote sessions
+    Agent(AgentArgs),
+}
+
+#[derive(clap::Args, Debug)]
+struct AgentArgs {
+    /// Read requests from stdin and write replies to stdout
+    #[arg(long, required = true)]
+    stdio: bool,
+
+    /// Maximum depth to scan
+    #[arg(short, long)]
+    depth: Option<usize>,
+
+    /// Patterns to exclude (can be repeated)
+    #[arg(short = 'x', long = "exclude", action = clap::ArgAction::Append)]
+    exclude: Vec<String>,
+
+    /// Don't cross filesystem boundaries
+    #[arg(long)]
+    no_cross_mount: bool,
+
+    /// Use apparent size instead of disk usage
+    #[arg(long)]
+    apparent_size: bool,
+}
+
+#[derive(clap::Args, Debug)]
+struct FleetArgs {
+    /// Absolute path to scan on every host
+    path: PathBuf,
+
+    /// File listing the hosts, one [user@]host[:port] per line
+    #[arg(long, value_name = "FILE")]
+    hosts: PathBuf,
+
+    /// Hosts scanned at once
+    #[arg(short, long, value_name = "N", default_value = "8")]
+    jobs: usize,
+
+    /// Give up on a host after this long, e.g. 90s or 10m
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration_arg, default_value = "10m")]
+    timeout: Duration,
+
+    /// Write a JSON report to stdout instead of the summary table
+    #[arg(long)]
+    json: bool,
+
+    /// Browse the combined tree in the TUI after scanning
+    #[arg(long, conflicts_with = "json")]
+    tui: bool,
+
+    /// Maximum depth to scan
+    #[arg(short, long)]
+    depth: Option<usize>,
+
+    /// Patterns to exclude (can be repeated)
+    #[arg(short = 'x', long = "exclude", action = clap::ArgAction::Append)]
+    exclude: Vec<String>,
+
+    /// Don't cross filesystem boundaries
+    #[arg(long)]
+    no_cross_mount: bool,
+
+    /// Use apparent size instead of disk usage
+    #[arg(long)]
+    apparent_size: bool,
+
+    /// SSH jump host (can be repeated)
+    #[arg(short = 'J', long = "jump", value_name = "HOST", action = clap::ArgAction::Append)]
+    jump: Vec<String>,
+
+    /// Extra ssh option (can be repeated)
+    #[arg(short = 'o', long = "ssh-option", value_name = "OPTION", action = clap::ArgAction::Append)]
+    ssh_options: Vec<String>,
+
+    /// Compression of scan output: auto, zstd, gzip, ssh or none
+    #[arg(long, value_enum, value_name = "MODE", default_value = "auto")]
+    compress: remote::Compression,
+}
+
+#[derive(clap::Args, Debug)]
+struct HistoryArgs {
+    /// Directory whose snapshots to list (default: current directory)
+    #[arg(default_value = ".")]
+    path: PathBuf,
+
+    /// Scan PATH now and record a snapshot before listing
+    #[arg(long)]
+    record: bool,
+
+    /// Largest directory changes shown between consecutive snapshots
+    #[arg(long, value_name = "N", default_value = "3")]
+    changes: usize,
+
+    /// Snapshots kept per directory when recording
+    #[arg(long, value_name = "N", default_value = "30")]
+    keep: usize,
+
+    /// Drop snapshots older than this when recording, e.g. 90d
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration_arg, default_value = "365d")]
+    max_age: Duration,
+
+    /// Patterns excluded by the scans to record and list (can be repeated)
+    #[arg(short = 'x', long = "exclude", action = clap::ArgAction::Append)]
+    exclude: Vec<String>,
+
+    /// Scans to record and list don't cross filesystem boundaries
+    #[arg(long)]
+    no_cross_mount: bool,
+
+    /// Scans to record and list use apparent size instead of disk usage
+    #[arg(long)]
+    apparent_size: bool,
+}
+
+#[derive(clap::Args, Debug)]
+struct CacheArgs {
+    #[command(subcommand)]
+    action: CacheAction,
+
+    /// Age after which a cache is stale, e.g. 12h or 30d [default: 7d]
+    #[arg(long, global = true, value_name = "DURATION", value_parser = parse_duration_arg)]
+    max_age: Option<Duration>,
+}
+
+#[derive(Subcommand, Debug)]
+enum CacheAction {
+    /// List cached scans, newest first
+    List,
+    /// Remove old caches, or the oldest ones beyond a total size
+    #[command(group(clap::ArgGroup::new("limit").required(true).multiple(true)))]
+    Prune {
+        /// Remove caches of scans older than this, e.g. 30d
+        #[arg(long, value_name = "DURATION", value_parser = parse_duration_arg, group = "limit")]
+        older_than: Option<Duration>,
+
+        /// Keep the newest caches up to this total size, e.g. 500M
+        #[arg(long, value_name = "SIZE", value_parser = parse_size_arg, group = "limit")]
+        max_total_size: Option<u64>,
+    },
+    /// Show details of the cache for a directory
+    Show {
+        /// Scanned directory (default: current directory)
+        #[arg(default_value = ".")]
+        path: PathBuf,
+    },
+    /// Remove the cache for a directory
+    Rm {
+        /// Scanned directory
+        #[arg(required_unless_present = "all")]
+        path: Option<PathBuf>,
+
+        /// Remove every cached scan
+        #[arg(long, conflicts_with = "path")]
+        all: bool,
+    },
+}
+
+fn parse_duration_arg(s: &str) -> Result<Duration, String> {
+    format::parse_duration(s).ok_or_else(|| format!("invalid duration '{}' (expected e.g. 90s, 30m, 12h, 7d, 2w)", s))
+}
+
+fn parse_size_arg(s: &str) -> Result<u64, String> {
+    format::parse_size(s).ok_or_else(|| format!("invalid size '{}' (expected e.g. 500M, 2G)", s))
+}
+
+#[derive(clap::Args, Debug)]
+struct MetricsArgs {
+    /// Directory to measure (default: current directory)
+    #[arg(default_value = ".")]
+    path: PathBuf,
+
+    /// Emit directories down to this depth below the root
+    #[arg(short, long, default_value = "2")]
+    depth: usize,
+
+    /// Emit only the N largest directories
+    #[arg(short = 'n', long)]
+    top: Option<usize>,
+
+    /// Patterns to exclude (can be repeated)
+    #[arg(short = 'x', long = "exclude", action = clap::ArgAction::Append)]
+    exclude: Vec<String>,
+
+    /// Don't cross filesystem boundaries
+    #[arg(long)]
+    no_cross_mount: bool,
+
+    /// Use apparent size instead of disk usage
+    #[arg(long)]
+    apparent_size: bool,
+
+    /// Write OpenMetrics instead of the Prometheus text format
+    #[arg(long)]
+    openmetrics: bool,
+
+    /// Write to FILE atomically (temporary file + rename) instead of stdout
+    #[arg(short, long, value_name = "FILE")]
+    output: Option<PathBuf>,
+
+    /// SSH jump host for a remote PATH (can be repeated)
+    #[arg(short = 'J', long = "jump", value_name = "HOST", action = clap::ArgAction::Append)]
+    jump: Vec<String>,
+
+    /// Extra ssh option for a remote PATH (can be repeated)
+    #[arg(long = "ssh-option", value_name = "OPTION", action = clap::ArgAction::Append)]
+    ssh_options: Vec<String>,
+
+    /// Compression of remote scan output: auto, zstd, gzip, ssh or none
+    #[arg(long, value_enum, value_name = "MODE", default_value = "auto")]
+    compress: remote::Compression,
+}
+
+/// Parse the command line.
+///
+/// `-h` is du's --human-readable, so it only prints help at the top level
+/// when --du isn't given. Subcommands get back the -h/--help that
+/// `disable_help_flag` takes from them.
+fn parse_args() -> Args {
+    let mut command = with_help_flag(Args::command());
+    let argv: Vec<std::ffi::OsString> = std::env::args_os().collect();
+    let subcommands: Vec<String> = command.get_subcommands().map(|sub| sub.get_name().to_string()).collect();
+    let mut top_level = argv
+        .iter()
+        .skip(1)
+        .take_while(|arg| *arg != "--" && !subcommands.iter().any(|name| arg.as_os_str() == name.as_str()));
+    if top_level.any(|arg| arg == "-h") && !argv.iter().any(|arg| arg == "--du") {
+        let _ = command.print_help();
+        std::process::exit(0);
+    }
+
+    let matches = command.get_matches_from(argv);
+    Args::from_arg_matches(&matches).unwrap_or_else(|err| err.exit())
+}
+
+fn with_help_flag(command: clap::Command) -> clap::Command {
+    command.mut_subcommands(|sub| {
+        with_help_flag(sub).arg(Arg::new("help").short('h').long("help").help("Print help").action(ArgAction::Help))
+    })
 }
 
 fn main() -> Result<()> {
-    let args = Args::parse();
+    let args = parse_args();
+
+    match args.command {
+        Some(Command::Metrics(metrics_args)) => return run_metrics_mode(metrics_args),
+        Some(Command::Cache(cache_args)) => return run_cache_command(cache_args),
+        Some(Command::History(history_args)) => return run_history_command(history_args),
+        Some(Command::Fleet(fleet_args)) => return run_fleet_command(fleet_args),
+        Some(Command::Agent(agent_args)) => return run_agent_command(agent_args),
+        None => {}
+    }
+
+    let headless = HeadlessOutput::from_args(&args)?;
 
     // Determine if we should use GUI mode
     // GUI is default when gui feature is enabled, unless --tui flag is passed
     #[cfg(feature = "gui")]
-    let use_gui = !args.tui && !args.json;
+    let use_gui = !args.tui && !headless.is_requested();
     #[cfg(not(feature = "gui"))]
     let use_gui = false;
 
-    // Check if this is a remote path (SSH)
-    let path_str = args.path.to_string_lossy().to_string();
-    if remote::is_remote_path(&path_str) {
-        let ssh_target = remote::SshTarget::parse(&path_str)
-            .ok_or_else(|| anyhow::anyhow!("Invalid SSH path format. Use: user@host:/path or ssh://user@host/path"))?;
+    // Saved scan: browse or convert it without touching the filesystem
+    if let Some(ref scan_path) = args.load {
+        let scan = export::load_scan(scan_path)
+            .map_err(|e| anyhow::anyhow!("Cannot load {}: {}", scan_path.display(), e))?;
 
-        // JSON mode for remote
-        if args.json {
-            return run_remote_json_mode(&ssh_target, args.top);
+        if headless.is_requested() {
+            let scan_options = scan.header.as_ref().and_then(|header| {
+                header.options.as_ref().map(|options| options.to_scan_options(PathBuf::from(&header.root)))
+            });
+            return headless.write(&scan.tree, scan_options.as_ref());
         }
 
-        // TUI mode for remote (GUI not supported for remote yet)
-        return run_remote_tui_mode(ssh_target, &args.color_scheme, args.no_color);
-    }
+        if use_gui {
+            #[cfg(feature = "gui")]
+            {
+                let options = eframe::NativeOptions {
+                    viewport: egui::ViewportBuilder::default()
+                        .with_inner_size([1200.0, 800.0])
+                        .with_title("Data-X - Disk Analyzer"),
+                    ..Default::default()
+                };
+                let scan_path = scan_path.clone();
+                return eframe::run_native(
+                    "Data-X",
+                    options,
+                    Box::new(move |cc| {
+                        let mut app = gui::DataXApp::new(cc, args.path.clone());
+                        app.load_scan(scan.tree, scan_path);
+                        Ok(Box::new(app))
+                    }),
+                )
+                .map_err(|e| anyhow::anyhow!("GUI error: {}", e));
+            }
+        }
 
-    // Local path - resolve it
-    let root_path = args.path.canonicalize().unwrap_or(args.path.clone());
+        return run_loaded_tui_mode(scan.tree, &args.color_scheme, args.no_color);
+    }
 
-    // Create scan options
+    // Create scan options; remote scans get the same ones
+    let path_str = args.path.to_string_lossy().to_string();
+    let is_remote = remote::is_remote_path(&path_str);
+    let root_path = if is_remote {
+        args.path.clone()
+    } else {
+        args.path.canonicalize().unwrap_or(args.path.clone())
+    };
     let scan_options = ScanOptions {
         root_path: root_path.clone(),
-        max_depth: args.depth,
+        // du counts everything and only limits what it prints
+        max_depth: if args.du { None } else { args.depth },
         exclude_patterns: args.exclude,
         cross_mount: !args.no_cross_mount,
-        apparent_size: args.apparent_size,
+        apparent_size: args.apparent_size || args.bytes,
     };
 
+    // Check if this is a remote path (SSH)
+    if is_remote {
+        let ssh_target = remote::SshTarget::parse(&path_str)
+            .ok_or_else(|| anyhow::anyhow!("Invalid SSH path format. Use: user@host:/path or ssh://user@host/path"))?
+            .with_jump_hosts(args.jump)
+            .with_ssh_options(args.ssh_options)
+            .with_compression(args.compress);
+
+        // Export mode for remote
+        if headless.is_requested() {
+            return run_remote_export_mode(&ssh_target, scan_options, &headless);
+        }
+
+        // Cached per host, user and port, as well as path
+        let cache_manager = CacheManager::new()
+            .with_max_age(args.cache_max_age.unwrap_or(cache::DEFAULT_MAX_AGE))
+            .with_host(ssh_target.cache_host(&ssh_config::SshConfig::load()));
+        if args.clear_cache {
+            if let Err(e) = cache_manager.clear(&ssh_target.path) {
+                eprintln!("Warning: Could not clear cache: {}", e);
+            }
+        }
+
+        // TUI mode for remote (GUI not supported for remote yet)
+        return run_remote_tui_mode(
+            ssh_target,
+            scan_options,
+            &args.color_scheme,
+            args.no_color,
+            cache_manager,
+            args.use_cache,
+        );
+    }
+
     // Handle cache clearing
     if args.clear_cache {
         let cache_manager = CacheManager::new();
@@ -133,9 +589,9 @@ fn main() -> Result<()> {
         }
     }
 
-    // JSON mode - no TUI/GUI
-    if args.json {
-        return run_json_mode(scan_options, args.top);
+    // Export mode - no TUI/GUI
+    if headless.is_requested() {
+        return run_export_mode(scan_options, &headless, args.history);
     }
 
     // GUI mode (default when gui feature enabled and --tui not passed)
@@ -158,44 +614,539 @@ fn main() -> Result<()> {
     }
 
     // TUI mode (cache disabled by default, use --use-cache to enable)
-    run_tui_mode(scan_options, &args.color_scheme, args.no_color, args.use_cache)
+    let cache_manager = CacheManager::new().with_max_age(args.cache_max_age.unwrap_or(cache::DEFAULT_MAX_AGE));
+    let history_store = args.history.then(history::HistoryStore::new);
+    run_tui_mode(
+        scan_options,
+        &args.color_scheme,
+        args.no_color,
+        cache_manager,
+        args.use_cache,
+        history_store.as_ref(),
+    )
 }
 
-fn run_json_mode(options: ScanOptions, top_n: Option<usize>) -> Result<()> {
-    use std::sync::mpsc;
+/// Non-interactive outputs requested on the command line.
+struct HeadlessOutput {
+    /// Format written to stdout (--json/--format)
+    format: Option<OutputFormat>,
+    top_n: Option<usize>,
+    columns: Vec<Column>,
+    /// HTML report destination (--report)
+    report: Option<PathBuf>,
+    /// Treemap image destination and format (--render)
+    render: Option<(PathBuf, ImageFormat)>,
+    render_options: RenderOptions,
+    /// du-style listing written to stdout (--du)
+    du: Option<DuOptions>,
+    /// Export file format and destination (--export)
+    export: Option<(ExportTarget, PathBuf)>,
+    /// Scan progress as JSON lines on stderr (--progress-json)
+    progress_json: bool,
+}
 
-    let (tx, _rx) = mpsc::sync_channel(1000);
-    let scanner = scanner::Scanner::new(options, tx);
+impl HeadlessOutput {
+    fn from_args(args: &Args) -> Result<Self> {
+        // --json and --ndjson are shorthands for --format
+        let format = args.format.or(if args.json {
+            Some(OutputFormat::Json)
+        } else if args.ndjson {
+            Some(OutputFormat::Ndjson)
+        } else {
+            None
+        });
+
+        if format == Some(OutputFormat::Ndjson) && args.top.is_some() {
+            anyhow::bail!("--top is not supported with NDJSON output");
+        }
+        if args.columns.is_some() && !matches!(format, Some(OutputFormat::Csv | OutputFormat::Tsv)) {
+            anyhow::bail!("--columns only applies to --format csv or tsv");
+        }
 
-    // Run scan synchronously for JSON mode
-    let tree = scanner.scan()?;
+        // Validate the image format before scanning
+        let render = match args.render {
+            Some(ref path) => {
+                let image_format = ImageFormat::from_path(path).ok_or_else(|| {
+                    anyhow::anyhow!("Unsupported image format for {} (use .svg or .png)", path.display())
+                })?;
+                Some((path.clone(), image_format))
+            }
+            None => None,
+        };
+
+        let export = match args.export.as_slice() {
+            [target, path] => {
+                let target = ExportTarget::from_str(target, true)
+                    .map_err(|_| anyhow::anyhow!("Unsupported export format '{}' (use sqlite)", target))?;
+                if target == ExportTarget::Sqlite && !cfg!(feature = "sqlite") {
+                    anyhow::bail!("This build of data-x has no SQLite support (enable the sqlite feature)");
+                }
+                Some((target, PathBuf::from(path)))
+            }
+            _ => None,
+        };
+
+        let du = args.du.then(|| DuOptions {
+            units: if args.human_readable {
+                Some(SizeUnits::Iec)
+            } else if args.si {
+                Some(SizeUnits::Si)
+            } else if args.bytes {
+                Some(SizeUnits::Bytes)
+            } else {
+                None
+            },
+            max_depth: if args.summarize { Some(0) } else { args.depth },
+            all: args.all,
+            // Print local paths relative to the argument as typed, like du
+            display_root: (args.load.is_none() && !remote::is_remote_path(&args.path.to_string_lossy()))
+                .then(|| args.path.clone()),
+        });
+
+        Ok(Self {
+            format,
+            top_n: args.top,
+            columns: args.columns.clone().unwrap_or_else(|| Column::ALL.to_vec()),
+            report: args.report.clone(),
+            render,
+            render_options: RenderOptions {
+                width: args.width,
+                height: args.height,
+                max_depth: args.render_depth,
+                labels: !args.no_labels,
+            },
+            du,
+            export,
+            progress_json: args.progress_json,
+        })
+    }
 
-    let export_options = ExportOptions { top_n };
-    let mut stdout = io::stdout();
-    export_json(&tree, &export_options, &mut stdout)?;
-    println!(); // Final newline
+    /// Whether any output was requested (skips the TUI/GUI)
+    fn is_requested(&self) -> bool {
+        self.format.is_some() || self.report.is_some() || self.render.is_some() || self.du.is_some() || self.export.is_some()
+    }
+
+    /// Write every requested output for a scanned tree.
+    ///
+    /// `scan_options` is recorded in the JSON header when known.
+    fn write(&self, tree: &FileTree, scan_options: Option<&ScanOptions>) -> Result<()> {
+        if let Some(ref report_path) = self.report {
+            let mut file = io::BufWriter::new(std::fs::File::create(report_path)?);
+            export_html(tree, &mut file)?;
+            file.flush()?;
+            eprintln!("Report written to {}", report_path.display());
+        }
+
+        if let Some((ref image_path, image_format)) = self.render {
+            let mut file = io::BufWriter::new(std::fs::File::create(image_path)?);
+            render_treemap(tree, image_format, &self.render_options, &mut file)?;
+            file.flush()?;
+            eprintln!("Treemap written to {}", image_path.display());
+        }
+
+        if let Some((target, ref path)) = self.export {
+            match target {
+                ExportTarget::Sqlite => {
+                    #[cfg(feature = "sqlite")]
+                    {
+                        let export_options = ExportOptions {
+                            top_n: None,
+                            scan_options: scan_options.cloned(),
+                        };
+                        export::export_sqlite(tree, &export_options, path)?;
+                        eprintln!("Database written to {}", path.display());
+                    }
+                    #[cfg(not(feature = "sqlite"))]
+                    let _ = path;
+                }
+            }
+        }
+
+        if let Some(format) = self.format {
+            write_export(tree, format, self.top_n, &self.columns, scan_options)?;
+        }
+
+        if let Some(ref du_options) = self.du {
+            let mut stdout = io::BufWriter::new(io::stdout().lock());
+            export_du(tree, du_options, &mut stdout)?;
+            stdout.flush()?;
+        }
+
+        Ok(())
+    }
+}
+
+fn run_export_mode(options: ScanOptions, output: &HeadlessOutput, record_history: bool) -> Result<()> {
+    // Run scan synchronously for export mode
+    let tree = scan_headless(output.progress_json, |tx| Ok(scanner::Scanner::new(options.clone(), tx).scan()?))?;
+
+    if record_history {
+        history::HistoryStore::new().record(&tree, &options, history::Retention::default())?;
+    }
+
+    output.write(&tree, Some(&options))
+}
+
+fn run_remote_export_mode(target: &remote::SshTarget, options: ScanOptions, output: &HeadlessOutput) -> Result<()> {
+    eprintln!("Connecting to {}...", target.describe(&ssh_config::SshConfig::load()));
+    let tree = scan_headless(output.progress_json, |tx| {
+        remote::RemoteScanner::new(target.clone(), tx).with_options(options.clone()).scan()
+    })?;
+
+    let options = ScanOptions {
+        root_path: target.path.clone(),
+        ..options
+    };
+    output.write(&tree, Some(&options))
+}
+
+fn run_history_command(args: HistoryArgs) -> Result<()> {
+    let store = history::HistoryStore::new();
+    let root_path = args.path.canonicalize().unwrap_or(args.path.clone());
+
+    let options = ScanOptions {
+        root_path: root_path.clone(),
+        max_depth: None,
+        exclude_patterns: args.exclude,
+        cross_mount: !args.no_cross_mount,
+        apparent_size: args.apparent_size,
+    };
+
+    if args.record {
+        let retention = history::Retention {
+            keep: args.keep,
+            max_age: Some(args.max_age),
+        };
+        store.record_scan(&options, retention)?;
+    }
+
+    // Only scans that found the same set of files can be compared
+    let snapshots = store.comparable_snapshots(&options);
+    let other_options = store.snapshots(&root_path).len() - snapshots.len();
+    if other_options > 0 {
+        println!("{} snapshots made with other scan options are not shown", other_options);
+    }
+    if snapshots.is_empty() {
+        println!("No snapshots of {} (record one with --record or --history)", root_path.display());
+        return Ok(());
+    }
+
+    println!("{:<17} {:>10} {:>10} {:>11}", "SCANNED", "SIZE", "FILES", "CHANGE");
+    let mut previous: Option<(&history::Snapshot, Option<FileTree>)> = None;
+    for snapshot in &snapshots {
+        let header = &snapshot.header;
+        let scanned = chrono::DateTime::from_timestamp(header.scan_time as i64, 0)
+            .map(|time| time.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_default();
+        let change = previous
+            .as_ref()
+            .map(|(prev, _)| format_change(header.total_size as i128 - prev.header.total_size as i128))
+            .unwrap_or_default();
+        println!(
+            "{:<17} {:>10} {:>10} {:>11}",
+            scanned,
+            format::format_size(header.total_size),
+            header.total_files,
+            change
+        );
+
+        // Largest directory changes since the previous snapshot
+        let tree = if args.changes > 0 { store.load(snapshot).ok() } else { None };
+        if let (Some((_, Some(prev_tree))), Some(tree)) = (&previous, &tree) {
+            let changes = tree::diff_trees(prev_tree, tree);
+            for change in changes
+                .iter()
+                .filter(|change| change.is_dir && change.path != root_path && change.delta() != 0)
+                .take(args.changes)
+            {
+                println!("  {:>11}  {}", format_change(change.delta()), change.path.display());
+            }
+        }
+        previous = Some((snapshot, tree));
+    }
+
+    if let (Some(first), Some(last)) = (snapshots.first(), snapshots.last()) {
+        let days = last.header.scan_time.saturating_sub(first.header.scan_time) as f64 / 86400.0;
+        if days > 0.0 {
+            let growth = (last.header.total_size as f64 - first.header.total_size as f64) / days;
+            println!(
+                "{} snapshots over {:.1} days, average growth {}",
+                snapshots.len(),
+                days,
+                history::format_growth(growth)
+            );
+        }
+    }
 
     Ok(())
 }
 
-fn run_remote_json_mode(target: &remote::SshTarget, top_n: Option<usize>) -> Result<()> {
-    use std::sync::mpsc;
+fn run_fleet_command(args: FleetArgs) -> Result<()> {
+    let hosts = fleet::read_hosts(&args.hosts)?;
+    if hosts.is_empty() {
+        anyhow::bail!("No hosts in {}", args.hosts.display());
+    }
+    let template = remote::SshTarget {
+        path: args.path.clone(),
+        ..Default::default()
+    }
+    .with_jump_hosts(args.jump)
+    .with_ssh_options(args.ssh_options)
+    .with_compression(args.compress);
+    let options = ScanOptions {
+        root_path: args.path.clone(),
+        max_depth: args.depth,
+        exclude_patterns: args.exclude,
+        cross_mount: !args.no_cross_mount,
+        apparent_size: args.apparent_size,
+    };
+    let fleet = fleet::Fleet::new(&hosts, &template)?
+        .with_options(options.clone())
+        .with_jobs(args.jobs)
+        .with_timeout(args.timeout);
+
+    eprintln!("Scanning {} on {} hosts, {} at a time...", args.path.display(), hosts.len(), args.jobs.max(1));
+    let done = std::sync::atomic::AtomicUsize::new(0);
+    let scans = fleet.scan(|scan| {
+        let done = done.fetch_add(1, std::sync::atomic::Ordering::Relaxed) + 1;
+        eprintln!("[{}/{}] {} {}", done, hosts.len(), scan.host, scan.status.describe());
+    })?;
+    let tree = fleet::combine(&scans, &args.path);
 
-    let (tx, _rx) = mpsc::sync_channel(1000);
-    let scanner = remote::RemoteScanner::new(target.clone(), tx);
+    if args.json {
+        let mut stdout = io::BufWriter::new(io::stdout().lock());
+        fleet::write_report(&scans, &tree, &options, &mut stdout)?;
+        writeln!(stdout)?;
+        stdout.flush()?;
+    } else {
+        fleet::write_summary(&scans, &mut io::stdout().lock())?;
+    }
 
-    eprintln!("Connecting to {}...", target.display());
-    let tree = scanner.scan()?;
+    if args.tui {
+        run_loaded_tui_mode(tree, "default", false)?;
+    }
 
-    let export_options = ExportOptions { top_n };
-    let mut stdout = io::stdout();
-    export_json(&tree, &export_options, &mut stdout)?;
-    println!(); // Final newline
+    let failed = scans.iter().filter(|scan| scan.status != fleet::HostStatus::Ok).count();
+    if failed > 0 {
+        anyhow::bail!("{} of {} hosts failed", failed, scans.len());
+    }
+    Ok(())
+}
+
+fn run_agent_command(args: AgentArgs) -> Result<()> {
+    // Every request names its own root
+    let options = ScanOptions {
+        root_path: PathBuf::new(),
+        max_depth: args.depth,
+        exclude_patterns: args.exclude,
+        cross_mount: !args.no_cross_mount,
+        apparent_size: args.apparent_size,
+    };
+    agent::Agent::new(options)
+        .with_progress(true)
+        .serve(io::stdin().lock(), io::stdout().lock())?;
+    Ok(())
+}
+
+/// Signed size difference, e.g. "+1.5 GB"
+fn format_change(delta: i128) -> String {
+    let sign = if delta < 0 { "-" } else { "+" };
+    format!("{}{}", sign, format::format_size(delta.unsigned_abs().min(u64::MAX as u128) as u64))
+}
+
+fn run_cache_command(args: CacheArgs) -> Result<()> {
+    let max_age = args.max_age.unwrap_or(cache::DEFAULT_MAX_AGE);
+    let manager = CacheManager::new().with_max_age(max_age);
+    // Caches are keyed by the canonical path, as scanned, and remote ones
+    // by host too
+    let resolve = |path: &PathBuf| {
+        match remote::SshTarget::parse(&path.to_string_lossy()) {
+            Some(target) => {
+                let host = target.cache_host(&ssh_config::SshConfig::load());
+                (CacheManager::new().with_max_age(max_age).with_host(host), target.path)
+            }
+            None => (CacheManager::new().with_max_age(max_age), path.canonicalize().unwrap_or(path.clone())),
+        }
+    };
+
+    match args.action {
+        CacheAction::List => {
+            let infos = manager.list();
+            if infos.is_empty() {
+                println!("No cached scans in {}", manager.cache_dir().display());
+                return Ok(());
+            }
+
+            println!("{:<10} {:>10} {:>10} {:>10}  ROOT", "AGE", "CACHE", "NODES", "SCANNED");
+            for info in &infos {
+                let stale = if manager.is_stale(info) { "  (stale)" } else { "" };
+                println!(
+                    "{:<10} {:>10} {:>10} {:>10}  {}{}",
+                    info.age_string(),
+                    format::format_size(info.size),
+                    info.node_count,
+                    format::format_size(info.total_size),
+                    info.location(),
+                    stale
+                );
+            }
+            let total: u64 = infos.iter().map(|info| info.size).sum();
+            println!("{} cached scans, {}", infos.len(), format::format_size(total));
+        }
+        CacheAction::Prune { older_than, max_total_size } => {
+            let removed = manager.prune(older_than, max_total_size)?;
+            for info in &removed {
+                println!("Removed {} ({})", info.location(), info.age_string());
+            }
+            let freed: u64 = removed.iter().map(|info| info.size).sum();
+            println!("Removed {} cached scans, freed {}", removed.len(), format::format_size(freed));
+        }
+        CacheAction::Show { path } => {
+            let (manager, root_path) = resolve(&path);
+            let info = manager
+                .get_cache_info(&root_path)
+                .ok_or_else(|| anyhow::anyhow!("No cached scan for {}", path.display()))?;
+
+            println!("Root:        {}", info.location());
+            println!("Cache file:  {}", info.file_path.display());
+            println!("Cache size:  {}", format::format_size(info.size));
+            println!(
+                "Scanned:     {}{}",
+                info.age_string(),
+                if manager.is_stale(&info) { " (stale, will be rescanned)" } else { "" }
+            );
+            println!("Nodes:       {}", info.node_count);
+            println!("Files:       {}", info.total_files);
+            println!("Total size:  {}", format::format_size(info.total_size));
+            println!("Options:     {}", info.options.as_ref().map(describe_options).unwrap_or_else(|| "unknown".to_string()));
+        }
+        CacheAction::Rm { path, all } => {
+            if all {
+                manager.clear_all()?;
+                println!("Removed all cached scans");
+            } else if let Some(path) = path {
+                let (manager, root_path) = resolve(&path);
+                if !manager.clear(&root_path)? {
+                    anyhow::bail!("No cached scan for {}", path.display());
+                }
+                println!("Removed cache for {}", path.display());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn run_metrics_mode(args: MetricsArgs) -> Result<()> {
+    let started = Instant::now();
+    let path_str = args.path.to_string_lossy().to_string();
+
+    let is_remote = remote::is_remote_path(&path_str);
+    let root_path = if is_remote {
+        args.path.clone()
+    } else {
+        args.path.canonicalize().unwrap_or(args.path.clone())
+    };
+    let options = ScanOptions {
+        root_path: root_path.clone(),
+        max_depth: None,
+        exclude_patterns: args.exclude,
+        cross_mount: !args.no_cross_mount,
+        apparent_size: args.apparent_size,
+    };
+
+    let (tree, filesystem) = if is_remote {
+        let target = remote::SshTarget::parse(&path_str)
+            .ok_or_else(|| anyhow::anyhow!("Invalid SSH path format. Use: user@host:/path or ssh://user@host/path"))?
+            .with_jump_hosts(args.jump)
+            .with_ssh_options(args.ssh_options)
+            .with_compression(args.compress);
+
+        // Filesystem usage is only known for local scans
+        let tree = scan_headless(false, |tx| remote::RemoteScanner::new(target, tx).with_options(options).scan())?;
+        (tree, None)
+    } else {
+        let tree = scan_headless(false, |tx| Ok(scanner::Scanner::new(options, tx).scan()?))?;
+        let filesystem = scanner::find_mount_point(&root_path)
+            .and_then(|mount| scanner::get_disk_space(&mount).map(|info| (mount, info)));
+        (tree, filesystem)
+    };
+
+    let options = MetricsOptions {
+        max_depth: args.depth,
+        top_n: args.top,
+        openmetrics: args.openmetrics,
+        filesystem,
+        scan_duration: Some(started.elapsed()),
+    };
+
+    match args.output {
+        Some(path) => {
+            // node_exporter may read the file at any time; never expose a partial one
+            let mut temp_path = path.clone().into_os_string();
+            temp_path.push(".tmp");
+            let temp_path = PathBuf::from(temp_path);
+
+            let mut file = io::BufWriter::new(std::fs::File::create(&temp_path)?);
+            export_prometheus(&tree, &options, &mut file)?;
+            file.flush()?;
+            drop(file);
+            std::fs::rename(&temp_path, &path)?;
+        }
+        None => {
+            let mut stdout = io::BufWriter::new(io::stdout().lock());
+            export_prometheus(&tree, &options, &mut stdout)?;
+            stdout.flush()?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Write a scanned tree to stdout in the requested export format.
+fn write_export(
+    tree: &FileTree,
+    format: OutputFormat,
+    top_n: Option<usize>,
+    columns: &[Column],
+    scan_options: Option<&ScanOptions>,
+) -> Result<()> {
+    let export_options = ExportOptions {
+        top_n,
+        scan_options: scan_options.cloned(),
+    };
+    let mut stdout = io::stdout().lock();
+
+    match format {
+        OutputFormat::Json => {
+            export_json(tree, &export_options, &mut stdout)?;
+            writeln!(stdout)?; // Final newline
+        }
+        OutputFormat::Ndjson => {
+            let mut writer = io::BufWriter::new(stdout);
+            export_ndjson(tree, &export_options, &mut writer)?;
+            writer.flush()?;
+        }
+        OutputFormat::Csv | OutputFormat::Tsv => {
+            let csv_options = CsvOptions {
+                delimiter: if format == OutputFormat::Tsv { Delimiter::Tab } else { Delimiter::Comma },
+                columns: columns.to_vec(),
+            };
+            export_csv(tree, &export_options, &csv_options, &mut stdout)?;
+        }
+    }
 
     Ok(())
 }
 
-fn run_remote_tui_mode(target: remote::SshTarget, color_scheme_name: &str, no_color: bool) -> Result<()> {
+fn run_remote_tui_mode(
+    target: remote::SshTarget,
+    scan_options: ScanOptions,
+    color_scheme_name: &str,
+    no_color: bool,
+    cache_manager: CacheManager,
+    use_cache: bool,
+) -> Result<()> {
     // Set up panic handler to restore terminal on crash
     let original_hook = panic::take_hook();
     panic::set_hook(Box::new(move |panic_info| {
@@ -224,22 +1175,74 @@ fn run_remote_tui_mode(target: remote::SshTarget, color_scheme_name: &str, no_co
     };
 
     // Create app with remote path display
-    let display_path = PathBuf::from(target.display());
+    let display_path = PathBuf::from(target.describe(&ssh_config::SshConfig::load()));
     let mut app = App::new(display_path, color_scheme);
+    app.set_remote(target.clone());
+
+    // Cached under the path on the host, like the tree's nodes
+    let scan_options = ScanOptions {
+        root_path: target.path.clone(),
+        ..scan_options
+    };
+    if !(use_cache && load_from_cache(&mut app, &cache_manager, &scan_options)) {
+        app.start_remote_scan(target, Some(scan_options));
+    }
+
+    let result = run_app(&mut terminal, &mut app, &cache_manager, use_cache, None);
+
+    // Restore terminal
+    let cleanup_result = cleanup_terminal(&mut terminal);
+    result.and(cleanup_result)
+}
+
+fn run_loaded_tui_mode(tree: FileTree, color_scheme_name: &str, no_color: bool) -> Result<()> {
+    // Set up panic handler to restore terminal on crash
+    let original_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |panic_info| {
+        let _ = disable_raw_mode();
+        let _ = execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture);
+        original_hook(panic_info);
+    }));
+
+    // Setup terminal
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    let backend = CrosstermBackend::new(stdout);
+    let mut terminal = Terminal::new(backend)?;
+    terminal.clear()?;
+
+    // Select color scheme
+    let color_scheme = if no_color {
+        ColorScheme::default()
+    } else {
+        match color_scheme_name {
+            "light" => ColorScheme::light(),
+            "colorblind" => ColorScheme::colorblind(),
+            _ => ColorScheme::default(),
+        }
+    };
 
-    // Start remote scan in background
-    app.start_remote_scan(target);
+    let mut app = App::new(PathBuf::new(), color_scheme);
+    app.load_scan(tree);
 
-    // Main loop (no cache for remote)
+    // Main loop (a loaded scan is never written to the cache)
     let cache_manager = CacheManager::new();
-    let result = run_app(&mut terminal, &mut app, &cache_manager, false);
+    let result = run_app(&mut terminal, &mut app, &cache_manager, false, None);
 
     // Restore terminal
     let cleanup_result = cleanup_terminal(&mut terminal);
     result.and(cleanup_result)
 }
 
-fn run_tui_mode(scan_options: ScanOptions, color_scheme_name: &str, no_color: bool, use_cache: bool) -> Result<()> {
+fn run_tui_mode(
+    scan_options: ScanOptions,
+    color_scheme_name: &str,
+    no_color: bool,
+    cache_manager: CacheManager,
+    use_cache: bool,
+    history_store: Option<&history::HistoryStore>,
+) -> Result<()> {
     // Set up panic handler to restore terminal on crash
     let original_hook = panic::take_hook();
     panic::set_hook(Box::new(move |panic_info| {
@@ -273,42 +1276,15 @@ fn run_tui_mode(scan_options: ScanOptions, color_scheme_name: &str, no_color: bo
     };
 
     // Try to load from cache first
-    let cache_manager = CacheManager::new();
-    let root_path = scan_options.root_path.clone();
-    let mut app = App::new(root_path.clone(), color_scheme);
-
-    let mut loaded_from_cache = false;
-    if use_cache && cache_manager.has_valid_cache(&root_path) {
-        if let Some(cache_entry) = cache_manager.load(&root_path) {
-            if let Some(tree) = cache_manager.cache_entry_to_tree(&cache_entry) {
-                // Successfully loaded from cache
-                app.tree = Some(tree);
-                app.scan_progress.total_files = cache_entry.total_files;
-                app.scan_progress.total_size = cache_entry.total_size;
-                app.scan_state = app::ScanState::Complete;
-                app.scan_progress.phase = app::ScanPhase::Complete;
-
-                // Expand root and refresh visible nodes
-                if let Some(ref t) = app.tree {
-                    if let Some(root) = t.root {
-                        app.expanded_nodes.insert(root);
-                    }
-                }
-                app.refresh_visible_nodes();
-                loaded_from_cache = true;
-
-                // Note: User can press 'r' to rescan and check for changes
-            }
-        }
-    }
+    let mut app = App::new(scan_options.root_path.clone(), color_scheme);
 
     // If not loaded from cache, do full scan
-    if !loaded_from_cache {
+    if !(use_cache && load_from_cache(&mut app, &cache_manager, &scan_options)) {
         app.start_scan(scan_options.clone());
     }
 
     // Main loop
-    let result = run_app(&mut terminal, &mut app, &cache_manager, use_cache);
+    let result = run_app(&mut terminal, &mut app, &cache_manager, use_cache, history_store);
 
     // Restore terminal (wrapped in closure to ensure it runs)
     let cleanup_result = cleanup_terminal(&mut terminal);
@@ -317,6 +1293,26 @@ fn run_tui_mode(scan_options: ScanOptions, color_scheme_name: &str, no_color: bo
     result.and(cleanup_result)
 }
 
+/// Show the cached scan matching `scan_options`, if there is a fresh one.
+///
+/// The user can press 'r' to rescan and check for changes.
+fn load_from_cache(app: &mut App, cache_manager: &CacheManager, scan_options: &ScanOptions) -> bool {
+    if cache_manager.has_valid_cache(scan_options) {
+        if let Some(mut reader) = cache_manager.open(&scan_options.root_path) {
+            // Show the top levels right away, the rest streams in behind them
+            if reader.read_block().is_ok() && reader.tree().root.is_some() {
+                app.load_cached_tree(reader);
+                return true;
+            }
+        }
+    } else if let Some((tree, header)) = cache_manager.load(scan_options) {
+        // A cached scan of a parent directory covers this one too
+        app.load_cached_subtree(tree, scan_options, header.scan_time);
+        return true;
+    }
+    false
+}
+
 /// Clean up terminal state.
 fn cleanup_terminal<B: ratatui::backend::Backend + Write>(terminal: &mut Terminal<B>) -> Result<()> {
     disable_raw_mode()?;
@@ -329,11 +1325,28 @@ fn cleanup_terminal<B: ratatui::backend::Backend + Write>(terminal: &mut Termina
     Ok(())
 }
 
+/// Hand the terminal to `shell` until it exits, then take it back.
+///
+/// The outer error is about the terminal, the inner one about the shell.
+fn run_shell<B: ratatui::backend::Backend>(
+    terminal: &mut Terminal<B>,
+    mut shell: std::process::Command,
+) -> Result<io::Result<()>> {
+    disable_raw_mode()?;
+    execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture)?;
+    let status = shell.status().map(|_| ());
+    enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture)?;
+    terminal.clear()?;
+    Ok(status)
+}
+
 fn run_app<B: ratatui::backend::Backend>(
     terminal: &mut Terminal<B>,
     app: &mut App,
     cache_manager: &CacheManager,
     use_cache: bool,
+    history_store: Option<&history::HistoryStore>,
 ) -> Result<()> {
     let mut last_scan_state = app.scan_state.clone();
 
@@ -341,10 +1354,23 @@ fn run_app<B: ratatui::backend::Backend>(
         // Update app state (check scan progress)
         app.update();
 
-        // Check if scan just completed - save to cache
-        if use_cache && app.scan_state == app::ScanState::Complete && last_scan_state != app::ScanState::Complete {
+        // Check if scan just completed - save to cache, unless only its top
+        // levels have been fetched so far
+        let just_completed =
+            app.scan_state == app::ScanState::Complete && last_scan_state != app::ScanState::Complete;
+        if use_cache && just_completed && !app.read_only && !app.partial_tree {
             if let Some(ref tree) = app.tree {
-                let _ = cache_manager.save(tree, &app.root_path);
+                let _ = tree_cache(app, cache_manager).save(tree, &cache_header(app, tree));
+                app.exclusions_changed = false;
+            }
+        }
+
+        // Record it in the history, then show the series including it
+        if let Some(store) = history_store.filter(|_| just_completed && !app.partial_tree) {
+            if let (Some(tree), Some(options)) = (&app.tree, app.history_options()) {
+                if store.record(tree, &options, history::Retention::default()).is_ok() {
+                    app.load_history();
+                }
             }
         }
         last_scan_state = app.scan_state.clone();
@@ -362,6 +1388,11 @@ fn run_app<B: ratatui::backend::Backend>(
                     if key.kind == KeyEventKind::Press {
                         let command = handle_key(key, &app.input_mode);
                         app.handle_command(command);
+                        if let Some(shell) = app.pending_shell.take() {
+                            if let Err(e) = run_shell(terminal, shell)? {
+                                app.message = Some(format!("Shell failed: {}", e));
+                            }
+                        }
                     }
                 }
                 Event::Mouse(mouse_event) => {
@@ -410,5 +1441,55 @@ fn run_app<B: ratatui::backend::Backend>(
         }
     }
 
+    // Keep exclusions made while browsing, without making the scan look newer
+    let complete = app.scan_state == app::ScanState::Complete && !app.loading_cache;
+    if use_cache && complete && app.exclusions_changed && !app.read_only && !app.partial_tree {
+        let cache_manager = tree_cache(app, cache_manager);
+        let tree = app.tree.as_ref();
+        if let Some((tree, reader)) = tree.and_then(|tree| Some((tree, cache_manager.open(&tree_root(tree)?)?))) {
+            let header = cache::CacheHeader {
+                scan_time: reader.header().scan_time,
+                ..cache_header(app, tree)
+            };
+            let _ = cache_manager.save(tree, &header);
+        }
+    }
+
     Ok(())
 }
+
+/// Scan options in words, e.g. "disk usage, one filesystem, depth 3"
+fn describe_options(options: &ScanOptions) -> String {
+    let mut parts = vec![
+        if options.apparent_size { "apparent size" } else { "disk usage" }.to_string(),
+        if options.cross_mount { "cross mounts" } else { "one filesystem" }.to_string(),
+    ];
+    if let Some(depth) = options.max_depth {
+        parts.push(format!("depth {}", depth));
+    }
+    if !options.exclude_patterns.is_empty() {
+        parts.push(format!("excluding {}", options.exclude_patterns.join(", ")));
+    }
+    parts.join(", ")
+}
+
+/// Cache header for the tree shown in the TUI
+fn cache_header(app: &App, tree: &tree::FileTree) -> cache::CacheHeader {
+    let root_path = tree_root(tree).unwrap_or_else(|| app.root_path.clone());
+    cache::header_for(tree, &root_path).with_scan(app.scan_options.as_ref(), app.disk_info)
+}
+
+/// The session's cache, for the host of the tree shown. A scan started
+/// from the path prompt may be on another host than the session's first.
+fn tree_cache(app: &App, cache_manager: &CacheManager) -> CacheManager {
+    let host = match app.actions {
+        actions::Actions::Remote(ref remote) => Some(remote.target().cache_host(&ssh_config::SshConfig::load())),
+        actions::Actions::Local => None,
+    };
+    cache_manager.for_host(host)
+}
+
+/// Path of the tree's root, which a remote scan shows with its host
+fn tree_root(tree: &tree::FileTree) -> Option<PathBuf> {
+    tree.root.and_then(|root| tree.get_node(root)).map(|node| node.path.clone())
+}
@@ -3,23 +3,40 @@
 //! Enables scanning remote servers by connecting via SSH and
 //! executing commands to gather file system information.
 
-use std::io::{BufRead, BufReader};
-use std::path::PathBuf;
-use std::process::{Command, Stdio};
-use std::sync::mpsc::SyncSender;
+use std::ffi::OsString;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Instant, SystemTime};
 
 use anyhow::{anyhow, Result};
 
-use crate::scanner::ScanProgress;
+use crate::agent::{AgentClient, AgentEvent};
+use crate::export::{export_node_to_tree, from_epoch, read_json, NdjsonTreeBuilder, ProgressRecord};
+use crate::scanner::{ScanOptions, ScanProgress};
+use crate::shell::{operand, quote, quote_path};
+use crate::ssh_config::SshConfig;
 use crate::tree::{FileNode, FileTree};
 
 /// Parsed SSH connection info.
-#[derive(Debug, Clone)]
+///
+/// `host` may be an alias from `~/.ssh/config`; ssh applies its settings.
+#[derive(Debug, Clone, Default)]
 pub struct SshTarget {
     pub user: Option<String>,
     pub host: String,
     pub port: Option<u16>,
     pub path: PathBuf,
+    /// Hosts to connect through (`ssh -J`)
+    pub jump_hosts: Vec<String>,
+    /// Extra `ssh -o` options, e.g. `StrictHostKeyChecking=yes`
+    pub ssh_options: Vec<String>,
+    /// How scan output is compressed on its way back
+    pub compression: Compression,
 }
 
 impl SshTarget {
@@ -31,8 +48,8 @@ impl SshTarget {
     /// - ssh://user@host:port/path
     pub fn parse(s: &str) -> Option<Self> {
         // Try ssh:// URL format first
-        if s.starts_with("ssh://") {
-            return Self::parse_ssh_url(&s[6..]);
+        if let Some(rest) = s.strip_prefix("ssh://") {
+            return Self::parse_ssh_url(rest);
         }
 
         // Try user@host:/path or host:/path format
@@ -62,7 +79,13 @@ impl SshTarget {
             (host_port.to_string(), None)
         };
 
-        Some(SshTarget { user, host, port, path })
+        Some(SshTarget {
+            user,
+            host,
+            port,
+            path,
+            ..Default::default()
+        })
     }
 
     fn parse_scp_format(s: &str) -> Option<Self> {
@@ -77,7 +100,30 @@ impl SshTarget {
             (None, host_part.to_string())
         };
 
-        Some(SshTarget { user, host, port: None, path })
+        Some(SshTarget {
+            user,
+            host,
+            path,
+            ..Default::default()
+        })
+    }
+
+    /// Connect through `jump_hosts`.
+    pub fn with_jump_hosts(mut self, jump_hosts: Vec<String>) -> Self {
+        self.jump_hosts = jump_hosts;
+        self
+    }
+
+    /// Pass `options` to ssh with `-o`.
+    pub fn with_ssh_options(mut self, options: Vec<String>) -> Self {
+        self.ssh_options = options;
+        self
+    }
+
+    /// Compress scan output with `compression`.
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
     }
 
     /// Build SSH command arguments.
@@ -89,11 +135,23 @@ impl SshTarget {
             args.push(port.to_string());
         }
 
+        if !self.jump_hosts.is_empty() {
+            args.push("-J".to_string());
+            args.push(self.jump_hosts.join(","));
+        }
+
+        for option in &self.ssh_options {
+            args.push("-o".to_string());
+            args.push(option.clone());
+        }
+
         let target = if let Some(ref user) = self.user {
             format!("{}@{}", user, self.host)
         } else {
             self.host.clone()
         };
+        // A host starting with `-` must not read as an ssh option
+        args.push("--".to_string());
         args.push(target);
 
         args
@@ -105,6 +163,53 @@ impl SshTarget {
         let port_part = self.port.map(|p| format!(":{}", p)).unwrap_or_default();
         format!("{}{}{}:{}", user_part, self.host, port_part, self.path.display())
     }
+
+    /// The machine and account scanned, as `user@host:port`, naming its
+    /// cached scans.
+    ///
+    /// An alias and the host it resolves to through `config` share their
+    /// caches. Without a user anywhere, ssh logs in as the local one.
+    pub fn cache_host(&self, config: &SshConfig) -> String {
+        let resolved = config.resolve(&self.host);
+        let host_name = resolved.host_name().unwrap_or(&self.host);
+        let port = self.port.or(resolved.port()).unwrap_or(22);
+        match self.user.as_deref().or(resolved.user()) {
+            Some(user) => format!("{}@{}:{}", user, host_name, port),
+            None => format!("{}:{}", host_name, port),
+        }
+    }
+
+    /// Display string with what `config` resolves the host to, such as
+    /// `db:/srv (dba@10.0.0.5:2222 via bastion)`.
+    pub fn describe(&self, config: &SshConfig) -> String {
+        let resolved = config.resolve(&self.host);
+        let host_name = resolved.host_name().unwrap_or(&self.host);
+        let user = self.user.as_deref().or(resolved.user());
+        let port = self.port.or(resolved.port()).filter(|&port| port != 22);
+        let jump = if self.jump_hosts.is_empty() {
+            resolved.proxy_jump().map(str::to_string)
+        } else {
+            Some(self.jump_hosts.join(","))
+        };
+
+        if host_name == self.host && user == self.user.as_deref() && port == self.port && jump.is_none() {
+            return self.display();
+        }
+
+        let mut connection = String::new();
+        if let Some(user) = user {
+            connection.push_str(user);
+            connection.push('@');
+        }
+        connection.push_str(host_name);
+        if let Some(port) = port {
+            connection.push_str(&format!(":{}", port));
+        }
+        if let Some(jump) = jump {
+            connection.push_str(&format!(" via {}", jump));
+        }
+        format!("{} ({})", self.display(), connection)
+    }
 }
 
 /// Check if a path string represents a remote target.
@@ -112,147 +217,785 @@ pub fn is_remote_path(s: &str) -> bool {
     SshTarget::parse(s).is_some()
 }
 
+/// Escape the characters `find -name`/`-path` treat as wildcards.
+///
+/// `*` is kept as a wildcard when `keep_star` is set, since data-x
+/// exclude patterns use it the same way.
+fn find_escape(s: &str, keep_star: bool) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        if matches!(c, '\\' | '?' | '[' | ']') || (c == '*' && !keep_star) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Remote data-x flags for the scan options.
+fn datax_flags(options: &ScanOptions) -> Vec<String> {
+    let mut flags = Vec::new();
+    if let Some(depth) = options.max_depth {
+        flags.push(format!("--depth {}", depth));
+    }
+    for pattern in &options.exclude_patterns {
+        // `=` keeps patterns starting with `-` from reading as flags
+        flags.push(format!("--exclude={}", quote(pattern)));
+    }
+    if !options.cross_mount {
+        flags.push("--no-cross-mount".to_string());
+    }
+    if options.apparent_size {
+        flags.push("--apparent-size".to_string());
+    }
+    flags
+}
+
+/// `find` arguments (after the root path, before the action) that
+/// reproduce the scan options.
+///
+/// Only POSIX predicates are used: exclusions and the depth limit both
+/// become `-path`/`-name` tests followed by `-prune`, since `-maxdepth`
+/// is an extension. Like the local scanner, patterns only match below
+/// the root.
+fn find_predicates(root: &Path, options: &ScanOptions) -> Vec<String> {
+    let root = root.to_string_lossy();
+    // "/" would give "//*"
+    let prefix = find_escape(root.trim_end_matches('/'), false);
+
+    let mut tests = Vec::new();
+    for pattern in &options.exclude_patterns {
+        if pattern.contains('*') {
+            // Globs match either the entry name or its path below the root
+            let glob = find_escape(pattern, true);
+            tests.push(format!("-name {} -o -path {}", quote(&glob), quote(&format!("{}/{}", prefix, glob))));
+        } else {
+            // Plain patterns match any part of the relative path
+            let text = find_escape(pattern, false);
+            tests.push(format!("-path {}", quote(&format!("{}/*{}*", prefix, text))));
+        }
+    }
+    if let Some(depth) = options.max_depth {
+        // Entries one level below the limit, and with them everything deeper
+        tests.push(format!("-path {}", quote(&format!("{}{}", prefix, "/*".repeat(depth + 1)))));
+    }
+
+    let mut predicates = Vec::new();
+    if !options.cross_mount {
+        predicates.push("-xdev".to_string());
+    }
+    if !tests.is_empty() {
+        predicates.push(format!("\\( {} \\) -prune -o", tests.join(" -o ")));
+    }
+    predicates
+}
+
+/// Command line options a `find` predicate list was built from, for errors.
+fn describe_find_options(options: &ScanOptions) -> Vec<String> {
+    let mut described = Vec::new();
+    if !options.cross_mount {
+        described.push("--no-cross-mount (-xdev)".to_string());
+    }
+    if !options.exclude_patterns.is_empty() || options.max_depth.is_some() {
+        described.push("--exclude/--depth (-path, -prune)".to_string());
+    }
+    described
+}
+
+/// BSD/macOS `stat` format for a find fallback record
+const BSD_STAT_FORMAT: &str = r#"-f "%Sp %z %b %m""#;
+
+/// busybox/GNU `stat` format for a find fallback record
+const POSIX_STAT_FORMAT: &str = r#"-c "%A %s %b %Y""#;
+
+/// The `mode size blocks mtime` field of a find fallback record.
+#[derive(Debug, PartialEq)]
+struct FindRecord {
+    /// First character of the mode string (`-`, `d`, `l`, ...)
+    kind: u8,
+    size: u64,
+    /// 512-byte blocks
+    blocks: u64,
+    modified: Option<SystemTime>,
+}
+
+impl FindRecord {
+    fn parse(meta: &[u8]) -> Option<Self> {
+        let meta = std::str::from_utf8(meta).ok()?;
+        let mut fields = meta.split_ascii_whitespace();
+        let kind = *fields.next()?.as_bytes().first()?;
+        let size = fields.next()?.parse().ok()?;
+        let blocks = fields.next()?.parse().ok()?;
+        let modified = fields.next().and_then(parse_mtime);
+        Some(Self { kind, size, blocks, modified })
+    }
+}
+
+/// Parse `secs[.fraction]` from `%T@` or `stat`, keeping nanoseconds.
+fn parse_mtime(s: &str) -> Option<SystemTime> {
+    let (negative, s) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s),
+    };
+    let (secs, fraction) = s.split_once('.').unwrap_or((s, ""));
+    let secs: i64 = secs.parse().ok()?;
+    if !fraction.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let digits: String = fraction.chars().chain(std::iter::repeat('0')).take(9).collect();
+    let nanos: u32 = digits.parse().ok()?;
+
+    Some(match (negative, nanos) {
+        (false, _) => from_epoch(secs, nanos),
+        (true, 0) => from_epoch(-secs, 0),
+        (true, _) => from_epoch(-secs - 1, 1_000_000_000 - nanos),
+    })
+}
+
+#[cfg(unix)]
+fn os_from_bytes(bytes: &[u8]) -> OsString {
+    use std::os::unix::ffi::OsStrExt;
+    std::ffi::OsStr::from_bytes(bytes).to_os_string()
+}
+
+#[cfg(not(unix))]
+fn os_from_bytes(bytes: &[u8]) -> OsString {
+    OsString::from(String::from_utf8_lossy(bytes).into_owned())
+}
+
+/// First non-empty line of a remote command's stderr, for errors
+pub fn first_line(errors: &str) -> &str {
+    errors.lines().find(|line| !line.trim().is_empty()).unwrap_or("no output")
+}
+
+/// Compression of remote scan output on its way over ssh
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum Compression {
+    /// zstd or gzip if the host has one, else ssh's own
+    #[default]
+    Auto,
+    Zstd,
+    Gzip,
+    /// ssh -C
+    Ssh,
+    None,
+}
+
+impl Compression {
+    /// What to use given the compressors found on the host. One asked for
+    /// by name but missing gives way to ssh's own.
+    fn negotiate(self, tools: &RemoteTools) -> Self {
+        match self {
+            Self::Auto if tools.zstd => Self::Zstd,
+            Self::Auto if tools.gzip => Self::Gzip,
+            Self::Zstd if tools.zstd => Self::Zstd,
+            Self::Gzip if tools.gzip => Self::Gzip,
+            Self::None => Self::None,
+            _ => Self::Ssh,
+        }
+    }
+
+    /// Remote command compressing stdin to stdout, for those run on the host
+    fn compressor(self) -> Option<&'static str> {
+        match self {
+            Self::Zstd => Some("zstd -q -c"),
+            Self::Gzip => Some("gzip -c"),
+            _ => None,
+        }
+    }
+
+    /// `command` with its output piped through the compressor, if any.
+    ///
+    /// POSIX sh has no `pipefail`, so the command's status takes a detour
+    /// through fd 3 to become the pipeline's, as callers rely on it.
+    fn wrap(self, command: &str) -> String {
+        match self.compressor() {
+            Some(compressor) => format!(
+                "{{ s=$({{ {{ {}; echo $? >&3; }} | {} >&4; }} 3>&1); }} 4>&1; exit ${{s:-1}}",
+                command, compressor
+            ),
+            None => command.to_string(),
+        }
+    }
+
+    /// `reader` decompressed, for output of a command wrapped by `wrap`
+    fn decoder<'a>(self, reader: impl Read + 'a) -> std::io::Result<Box<dyn Read + 'a>> {
+        Ok(match self {
+            Self::Zstd => Box::new(zstd::Decoder::new(reader)?),
+            Self::Gzip => Box::new(flate2::read::MultiGzDecoder::new(reader)),
+            _ => Box::new(reader),
+        })
+    }
+}
+
+/// Programs found on the remote host
+#[derive(Debug, Default, PartialEq)]
+struct RemoteTools {
+    datax: bool,
+    zstd: bool,
+    gzip: bool,
+}
+
+impl RemoteTools {
+    /// Remote command listing the programs' paths, one per line
+    const PROBE: &'static str = "which data-x zstd gzip 2>/dev/null";
+
+    /// Read the output of `PROBE`
+    fn parse(output: &str) -> Self {
+        let mut tools = Self::default();
+        for line in output.lines() {
+            match Path::new(line.trim()).file_name().and_then(|name| name.to_str()) {
+                Some("data-x") => tools.datax = true,
+                Some("zstd") => tools.zstd = true,
+                Some("gzip") => tools.gzip = true,
+                _ => {}
+            }
+        }
+        tools
+    }
+}
+
+/// Decoded bytes between transfer progress reports
+const TRANSFER_REPORT_BYTES: u64 = 256 * 1024;
+
+/// Size of a remote command's output on the wire and once decoded
+#[derive(Default)]
+struct TransferStats {
+    received: AtomicU64,
+    decoded: AtomicU64,
+    /// Why decoding stopped early, if it did
+    error: Mutex<Option<String>>,
+}
+
+impl TransferStats {
+    fn progress(&self) -> ScanProgress {
+        ScanProgress::Transfer {
+            received: self.received.load(Ordering::Relaxed),
+            decoded: self.decoded.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Counts the bytes that come over the connection
+struct ReceivedReader<R> {
+    inner: R,
+    stats: Arc<TransferStats>,
+}
+
+impl<R: Read> Read for ReceivedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.stats.received.fetch_add(read as u64, Ordering::Relaxed);
+        Ok(read)
+    }
+}
+
+/// Counts decoded output and reports the transfer as it goes.
+///
+/// A stream that can't be decoded ends there; the command's status says
+/// why, which beats a decoder error about a cut-off stream.
+struct DecodedReader<'a> {
+    inner: Box<dyn Read + 'a>,
+    stats: Arc<TransferStats>,
+    progress_tx: SyncSender<ScanProgress>,
+    reported: u64,
+}
+
+impl Read for DecodedReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = match self.inner.read(buf) {
+            Ok(read) => read,
+            Err(err) if err.kind() == std::io::ErrorKind::Interrupted => return Err(err),
+            Err(err) => {
+                *self.stats.error.lock().unwrap_or_else(|e| e.into_inner()) = Some(err.to_string());
+                return Ok(0);
+            }
+        };
+        let decoded = self.stats.decoded.fetch_add(read as u64, Ordering::Relaxed) + read as u64;
+        if decoded >= self.reported + TRANSFER_REPORT_BYTES {
+            self.reported = decoded;
+            let _ = self.progress_tx.send(self.stats.progress());
+        }
+        Ok(read)
+    }
+}
+
+/// Error of a scan still running at its deadline
+#[derive(Debug, thiserror::Error)]
+#[error("timed out")]
+pub struct TimedOut;
+
+/// Kills a remote command still running at the scan's deadline
+struct Watchdog {
+    done: mpsc::Sender<()>,
+    thread: thread::JoinHandle<(Child, bool)>,
+}
+
+impl Watchdog {
+    fn start(mut child: Child, deadline: Option<Instant>) -> Self {
+        let (done, done_rx) = mpsc::channel::<()>();
+        let thread = thread::spawn(move || {
+            let timed_out = match deadline {
+                Some(deadline) => {
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    matches!(done_rx.recv_timeout(remaining), Err(mpsc::RecvTimeoutError::Timeout))
+                }
+                None => {
+                    let _ = done_rx.recv();
+                    false
+                }
+            };
+            if timed_out {
+                let _ = child.kill();
+            }
+            (child, timed_out)
+        });
+        Self { done, thread }
+    }
+
+    /// Hand the command back, and whether it had to be killed
+    fn stop(self) -> (Child, bool) {
+        drop(self.done);
+        self.thread.join().expect("watchdog thread panicked")
+    }
+}
+
+/// A command running on the remote host
+struct RemoteProcess {
+    watchdog: Watchdog,
+    /// Its output, decompressed
+    stdout: BufReader<DecodedReader<'static>>,
+    /// Its stderr less progress records, once it exits
+    errors: thread::JoinHandle<String>,
+    stats: Arc<TransferStats>,
+    progress_tx: SyncSender<ScanProgress>,
+}
+
+impl RemoteProcess {
+    /// Wait for the command to exit, returning its status and stderr.
+    fn finish(self) -> Result<(ExitStatus, String)> {
+        let Self { watchdog, stdout, errors, stats, progress_tx } = self;
+        drop(stdout);
+        let (mut child, timed_out) = watchdog.stop();
+        let status = child.wait()?;
+        let errors = errors.join().unwrap_or_default();
+        let _ = progress_tx.send(stats.progress());
+        if timed_out {
+            return Err(TimedOut.into());
+        }
+
+        let decode_error = stats.error.lock().unwrap_or_else(|e| e.into_inner()).take();
+        match decode_error {
+            Some(error) if status.success() => Err(anyhow!("corrupt compressed output: {}", error)),
+            _ => Ok((status, errors)),
+        }
+    }
+}
+
 /// Remote scanner that uses SSH to scan a remote filesystem.
 pub struct RemoteScanner {
     target: SshTarget,
+    options: ScanOptions,
+    ssh_program: PathBuf,
     progress_tx: SyncSender<ScanProgress>,
+    /// When to give up on the host, killing what still runs there
+    deadline: Option<Instant>,
 }
 
 impl RemoteScanner {
     pub fn new(target: SshTarget, progress_tx: SyncSender<ScanProgress>) -> Self {
-        Self { target, progress_tx }
+        let options = ScanOptions {
+            root_path: target.path.clone(),
+            max_depth: None,
+            exclude_patterns: Vec::new(),
+            cross_mount: true,
+            apparent_size: false,
+        };
+        Self {
+            target,
+            options,
+            ssh_program: PathBuf::from("ssh"),
+            progress_tx,
+            deadline: None,
+        }
+    }
+
+    /// Scan with `options` instead of the defaults.
+    ///
+    /// The root path always comes from the target.
+    pub fn with_options(mut self, options: ScanOptions) -> Self {
+        self.options = ScanOptions {
+            root_path: self.target.path.clone(),
+            ..options
+        };
+        self
+    }
+
+    /// Run `program` instead of `ssh`.
+    pub fn with_ssh_program(mut self, program: impl Into<PathBuf>) -> Self {
+        self.ssh_program = program.into();
+        self
+    }
+
+    /// Fail with [`TimedOut`] once `deadline` passes.
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Start `command` on the host, its output sent back with `compression`
+    fn spawn(&self, command: &str, compression: Compression) -> Result<RemoteProcess> {
+        let mut ssh = Command::new(&self.ssh_program);
+        if compression == Compression::Ssh {
+            ssh.arg("-C");
+        }
+        let mut child = ssh
+            .args(self.target.ssh_args())
+            .arg(compression.wrap(command))
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        let stdout = child.stdout.take().ok_or_else(|| anyhow!("Failed to capture stdout"))?;
+        let stderr = child.stderr.take().ok_or_else(|| anyhow!("Failed to capture stderr"))?;
+        let errors = self.forward_progress(stderr);
+
+        let stats = Arc::new(TransferStats::default());
+        let received = ReceivedReader {
+            inner: stdout,
+            stats: Arc::clone(&stats),
+        };
+        let decoded = DecodedReader {
+            inner: compression.decoder(received)?,
+            stats: Arc::clone(&stats),
+            progress_tx: self.progress_tx.clone(),
+            reported: 0,
+        };
+        Ok(RemoteProcess {
+            watchdog: Watchdog::start(child, self.deadline),
+            stdout: BufReader::new(decoded),
+            errors,
+            stats,
+            progress_tx: self.progress_tx.clone(),
+        })
+    }
+
+    /// Remote data-x command line with the scan options
+    fn datax_command(&self, output_flags: &[&str]) -> String {
+        let mut command = vec!["data-x".to_string()];
+        command.extend(output_flags.iter().map(|flag| flag.to_string()));
+        command.extend(datax_flags(&self.options));
+        command.push(self.quoted_root());
+        command.join(" ")
+    }
+
+    /// The root quoted for the scan commands.
+    ///
+    /// They are built as strings, which is exact after `check_root`.
+    fn quoted_root(&self) -> String {
+        quote_path(&self.target.path).to_string_lossy().into_owned()
+    }
+
+    /// Refuse a root that isn't UTF-8: scan commands and agent requests
+    /// are text, so they couldn't name it.
+    fn check_root(&self) -> Result<()> {
+        if self.target.path.to_str().is_none() {
+            return Err(anyhow!("{} is not valid UTF-8 and can't be scanned over ssh", self.target.path.display()));
+        }
+        Ok(())
     }
 
     /// Scan the remote filesystem.
     pub fn scan(&self) -> Result<FileTree> {
         // Send started signal
         let _ = self.progress_tx.send(ScanProgress::Started);
+        self.check_root()?;
 
-        // First, check if data-x is available on remote
-        let has_datax = self.check_remote_datax()?;
+        // First, check if data-x and compressors are available on remote
+        let tools = self.probe_remote()?;
+        let compression = self.target.compression.negotiate(&tools);
 
-        if has_datax {
-            self.scan_with_datax()
+        if tools.datax {
+            self.scan_with_datax(compression)
         } else {
-            self.scan_with_find()
+            self.scan_with_find(compression)
         }
     }
 
-    /// Check if data-x is installed on the remote server.
-    fn check_remote_datax(&self) -> Result<bool> {
-        let mut args = self.target.ssh_args();
-        args.push("which data-x 2>/dev/null || echo ''".to_string());
+    /// Scan the remote filesystem a level at a time through the host's
+    /// `data-x agent`, then list each directory whose path arrives on
+    /// `requests` until the sender hangs up.
+    ///
+    /// Hosts without the agent get a full [`scan`](Self::scan) instead; in
+    /// both cases the tree arrives as `Completed` progress.
+    pub fn browse(&self, requests: Receiver<PathBuf>, events: Sender<AgentEvent>) -> Result<FileTree> {
+        let _ = self.progress_tx.send(ScanProgress::Started);
+        self.check_root()?;
+        let Ok(mut agent) = self.agent() else {
+            return self.scan();
+        };
 
-        let output = Command::new("ssh")
-            .args(&args)
-            .output()?;
+        let _ = events.send(AgentEvent::Connected);
+        let tree = self.complete(export_node_to_tree(&agent.list(&self.target.path)?));
 
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        Ok(!stdout.trim().is_empty())
+        for path in requests {
+            let result = agent.list(&path).map_err(|err| err.to_string());
+            if events.send(AgentEvent::Listed { path, result }).is_err() {
+                break;
+            }
+        }
+        Ok(tree)
     }
 
-    /// Scan using remote data-x installation (preferred, faster).
-    fn scan_with_datax(&self) -> Result<FileTree> {
-        let mut args = self.target.ssh_args();
-        args.push(format!("data-x --json '{}'", self.target.path.display()));
+    /// Start `data-x agent` on the host, scanning with the scan options.
+    ///
+    /// Its scan progress is replayed as local progress.
+    pub fn agent(&self) -> Result<AgentClient> {
+        let mut command = vec!["data-x agent --stdio".to_string()];
+        command.extend(datax_flags(&self.options));
 
-        let mut child = Command::new("ssh")
-            .args(&args)
+        let mut ssh = Command::new(&self.ssh_program);
+        if self.target.compression == Compression::Ssh {
+            ssh.arg("-C");
+        }
+        let mut child = ssh
+            .args(self.target.ssh_args())
+            .arg(command.join(" "))
+            .stdin(Stdio::piped())
             .stdout(Stdio::piped())
-            .stderr(Stdio::null())
+            .stderr(Stdio::piped())
             .spawn()?;
 
-        let stdout = child.stdout.take().ok_or_else(|| anyhow!("Failed to capture stdout"))?;
-        let mut json_output = String::new();
+        let stderr = child.stderr.take().ok_or_else(|| anyhow!("Failed to capture stderr"))?;
+        let errors = self.forward_progress(stderr);
+        AgentClient::start(child, errors)
+    }
+
+    /// Find data-x and the compressors on the remote server.
+    fn probe_remote(&self) -> Result<RemoteTools> {
+        let mut process = self.spawn(RemoteTools::PROBE, Compression::None)?;
+        let mut output = Vec::new();
+        process.stdout.read_to_end(&mut output)?;
+        let (status, errors) = process.finish()?;
+
+        // ssh itself exits with 255 when it can't connect
+        if status.code() == Some(255) {
+            return Err(anyhow!("ssh to {} failed: {}", self.target.host, first_line(&errors)));
+        }
+
+        Ok(RemoteTools::parse(&String::from_utf8_lossy(&output)))
+    }
+
+    /// Scan using remote data-x installation (preferred, faster).
+    ///
+    /// Streams `--ndjson` output into the tree as it arrives, with the
+    /// remote scan's `--progress-json` replayed as local progress; remote
+    /// data-x versions without NDJSON support fall back to `--json`.
+    fn scan_with_datax(&self, compression: Compression) -> Result<FileTree> {
+        if let Some(tree) = self.scan_with_datax_ndjson(compression, true)? {
+            return Ok(tree);
+        }
+
+        let mut process = self.spawn(&self.datax_command(&["--json"]), compression)?;
+        let tree = self.read_json_tree(&mut process.stdout);
+        let (status, errors) = process.finish()?;
 
-        let reader = BufReader::new(stdout);
-        for line in reader.lines() {
-            if let Ok(line) = line {
-                json_output.push_str(&line);
+        tree.map_err(|err| {
+            if status.success() {
+                err
+            } else {
+                anyhow!("remote data-x failed ({}): {}", status, first_line(&errors))
+            }
+        })
+    }
+
+    /// Run `data-x --ndjson` remotely, building the tree line by line.
+    ///
+    /// Returns `Ok(None)` when the remote data-x does not understand
+    /// `--ndjson` (it exits with an error before writing anything). One
+    /// that only rejects `--progress-json` is run again without it.
+    fn scan_with_datax_ndjson(&self, compression: Compression, progress: bool) -> Result<Option<FileTree>> {
+        let flags: &[&str] = if progress { &["--ndjson", "--progress-json"] } else { &["--ndjson"] };
+        let mut process = self.spawn(&self.datax_command(flags), compression)?;
+        let mut builder = NdjsonTreeBuilder::new();
+        let mut files_found = 0u64;
+        let mut bytes_processed = 0u64;
+
+        for line in process.stdout.by_ref().lines() {
+            let line = line?;
+            if let Some(node) = builder.push_line(&line)? {
+                if !node.is_dir {
+                    bytes_processed += node.size;
+                }
+                files_found += 1;
+
+                // Send progress every 100 records
+                if files_found.is_multiple_of(100) {
+                    let _ = self.progress_tx.send(ScanProgress::Scanning {
+                        path: node.path.clone(),
+                        files_found,
+                        estimated_total: files_found + 1000, // Estimate
+                        bytes_processed,
+                    });
+                }
             }
         }
 
-        child.wait()?;
+        let (status, errors) = process.finish()?;
+        let imported = builder.finish();
+
+        if !status.success() {
+            if imported.header.is_none() {
+                if progress && errors.contains("--progress-json") {
+                    return self.scan_with_datax_ndjson(compression, false);
+                }
+                return Ok(None);
+            }
+            return Err(anyhow!("remote data-x failed ({}): {}", status, first_line(&errors)));
+        }
 
-        // Parse JSON and build tree
-        self.parse_json_to_tree(&json_output)
+        Ok(Some(self.complete(imported.tree)))
     }
 
-    /// Scan using find/stat commands (fallback when data-x not installed).
-    fn scan_with_find(&self) -> Result<FileTree> {
-        let mut args = self.target.ssh_args();
-
-        // Use find with stat to get file info
-        // Format: path|type|size|mtime
-        let find_cmd = format!(
-            r#"find '{}' -printf '%p|%y|%s|%T@\n' 2>/dev/null || find '{}' -exec stat -f '%N|%HT|%z|%m' {{}} \; 2>/dev/null"#,
-            self.target.path.display(),
-            self.target.path.display()
-        );
-        args.push(find_cmd);
+    /// Replay `--progress-json` lines from remote stderr as scan progress
+    /// while the command runs. The thread returns every other line, for
+    /// error messages.
+    fn forward_progress(&self, stderr: impl Read + Send + 'static) -> thread::JoinHandle<String> {
+        let progress_tx = self.progress_tx.clone();
+        thread::spawn(move || {
+            let mut errors = String::new();
+            for line in BufReader::new(stderr).split(b'\n').map_while(|line| line.ok()) {
+                let line = String::from_utf8_lossy(&line);
+                match ProgressRecord::parse(&line) {
+                    Some(record) => {
+                        if let Some(progress) = record.into_progress() {
+                            let _ = progress_tx.send(progress);
+                        }
+                    }
+                    None => {
+                        errors.push_str(&line);
+                        errors.push('\n');
+                    }
+                }
+            }
+            errors
+        })
+    }
 
-        let mut child = Command::new("ssh")
-            .args(&args)
-            .stdout(Stdio::piped())
-            .stderr(Stdio::null())
-            .spawn()?;
+    /// Shell command for the find fallback.
+    ///
+    /// Every variant prints one NUL-terminated `mode size blocks mtime`,
+    /// path and symlink target per entry, so any byte may appear in names:
+    /// GNU find formats them itself, otherwise a `sh` loop asks BSD or
+    /// busybox `stat` and `readlink`.
+    fn find_command(&self) -> String {
+        let root = self.quoted_root();
+        format!(
+            "if find {root} -maxdepth 0 -printf '' >/dev/null 2>&1; then {}; elif stat -f %z {root} >/dev/null 2>&1; then {}; else {}; fi",
+            self.find_printf_command(),
+            self.find_stat_command(BSD_STAT_FORMAT),
+            self.find_stat_command(POSIX_STAT_FORMAT),
+        )
+    }
 
-        let stdout = child.stdout.take().ok_or_else(|| anyhow!("Failed to capture stdout"))?;
-        let reader = BufReader::new(stdout);
+    /// `find` with the root and scan option predicates
+    fn find_prefix(&self) -> String {
+        let mut find = vec!["find".to_string(), self.quoted_root()];
+        find.extend(find_predicates(&operand(&self.target.path), &self.options));
+        find.join(" ")
+    }
+
+    /// Records from GNU find's `-printf`
+    fn find_printf_command(&self) -> String {
+        format!(r"{} -printf '%M %s %b %T@\0%p\0%l\0'", self.find_prefix())
+    }
+
+    /// Records from `stat` and `readlink`, for finds without `-printf`
+    ///
+    /// The `.` keeps command substitution from eating trailing newlines
+    /// of the link target.
+    fn find_stat_command(&self, stat_format: &str) -> String {
+        format!(
+            r#"{} -exec sh -c 'for f do m=$(stat {stat_format} "$f" 2>/dev/null); t=$(readlink "$f" 2>/dev/null; echo .); t=${{t%.}}; t=${{t%?}}; printf "%s\0%s\0%s\0" "$m" "$f" "$t"; done' sh {{}} +"#,
+            self.find_prefix()
+        )
+    }
+
+    /// Scan using find/stat commands (fallback when data-x not installed).
+    fn scan_with_find(&self, compression: Compression) -> Result<FileTree> {
+        let mut process = self.spawn(&self.find_command(), compression)?;
+        let tree = self.read_find_output(&mut process.stdout);
+        let (status, errors) = process.finish()?;
+
+        // find stops before printing anything when it rejects a predicate
+        if tree.root.is_none() && !status.success() {
+            let message = first_line(&errors);
+            let options = describe_find_options(&self.options);
+            if options.is_empty() {
+                return Err(anyhow!("remote find failed: {}", message));
+            }
+            return Err(anyhow!(
+                "remote find failed ({}); it may not support {}. Install data-x on {} to scan with these options",
+                message,
+                options.join(", "),
+                self.target.host
+            ));
+        }
+
+        Ok(tree)
+    }
 
+    /// Build the tree from find fallback records.
+    fn read_find_output(&self, reader: impl BufRead) -> FileTree {
         let mut tree = FileTree::new();
         let mut files_found = 0u64;
         let mut total_size = 0u64;
         let mut path_to_id = std::collections::HashMap::new();
 
-        for line in reader.lines() {
-            let line = match line {
-                Ok(l) => l,
-                Err(_) => continue,
-            };
-
-            let parts: Vec<&str> = line.split('|').collect();
-            if parts.len() < 3 {
+        let mut fields = reader.split(0).map_while(|field| field.ok());
+        while let (Some(meta), Some(path), Some(target)) = (fields.next(), fields.next(), fields.next()) {
+            // stat prints nothing for entries that vanished mid-scan
+            let Some(record) = FindRecord::parse(&meta) else {
                 continue;
-            }
+            };
+            let path = PathBuf::from(os_from_bytes(&path));
 
-            let path = PathBuf::from(parts[0]);
-            let file_type = parts[1];
-            let size: u64 = parts[2].parse().unwrap_or(0);
+            let size = match record.kind {
+                b'd' | b'l' => 0,
+                _ if self.options.apparent_size => record.size,
+                _ => record.blocks * 512,
+            };
 
-            let is_dir = file_type == "d" || file_type == "Directory";
+            let mut node = FileNode::new(path.clone(), record.kind == b'd');
+            node.size = size;
+            if let Some(modified) = record.modified {
+                node = node.with_modified(modified);
+            }
+            if record.kind == b'l' {
+                node = node.with_symlink(PathBuf::from(os_from_bytes(&target)));
+            }
 
-            // Create or update tree
             if tree.root.is_none() {
-                // First entry should be root
+                // First entry is the root
                 tree = FileTree::with_root(path.clone());
                 if let Some(root_id) = tree.root {
                     path_to_id.insert(path.clone(), root_id);
-                    if let Some(node) = tree.get_node_mut(root_id) {
-                        node.size = size;
-                    }
-                }
-            } else {
-                // Add as child of parent
-                let parent_path = path.parent().map(|p| p.to_path_buf());
-
-                if let Some(parent_path) = parent_path {
-                    if let Some(&parent_id) = path_to_id.get(&parent_path) {
-                        let mut node = FileNode::new(path.clone(), is_dir);
-                        node.size = size;
-                        let node_id = tree.add_child(parent_id, node);
-                        path_to_id.insert(path, node_id);
+                    if let Some(root) = tree.get_node_mut(root_id) {
+                        root.modified = node.modified;
                     }
                 }
+            } else if let Some(&parent_id) = path.parent().and_then(|parent| path_to_id.get(parent)) {
+                let node_id = tree.add_child(parent_id, node);
+                path_to_id.insert(path.clone(), node_id);
             }
 
             files_found += 1;
             total_size += size;
 
             // Send progress every 100 files
-            if files_found % 100 == 0 {
+            if files_found.is_multiple_of(100) {
                 let _ = self.progress_tx.send(ScanProgress::Scanning {
-                    path: PathBuf::from(parts[0]),
+                    path,
                     files_found,
                     estimated_total: files_found + 1000, // Estimate
                     bytes_processed: total_size,
@@ -260,8 +1003,6 @@ impl RemoteScanner {
             }
         }
 
-        child.wait()?;
-
         // Calculate sizes
         tree.calculate_sizes();
 
@@ -272,91 +1013,28 @@ impl RemoteScanner {
             tree: tree.clone(),
         });
 
-        Ok(tree)
+        tree
     }
 
-    /// Parse JSON output from remote data-x into a FileTree.
-    fn parse_json_to_tree(&self, json: &str) -> Result<FileTree> {
-        // Parse the JSON structure
-        let value: serde_json::Value = serde_json::from_str(json)?;
-
-        let mut tree = FileTree::new();
-
-        if let Some(obj) = value.as_object() {
-            let path = obj.get("path")
-                .and_then(|v| v.as_str())
-                .map(PathBuf::from)
-                .unwrap_or_else(|| self.target.path.clone());
-
-            let size = obj.get("size")
-                .and_then(|v| v.as_u64())
-                .unwrap_or(0);
-
-            let file_count = obj.get("file_count")
-                .and_then(|v| v.as_u64())
-                .unwrap_or(0);
-
-            tree = FileTree::with_root(path);
-
-            if let Some(root_id) = tree.root {
-                if let Some(node) = tree.get_node_mut(root_id) {
-                    node.size = size;
-                    node.file_count = file_count;
-                }
-
-                // Parse children recursively
-                if let Some(children) = obj.get("children").and_then(|v| v.as_array()) {
-                    self.parse_children(&mut tree, root_id, children);
-                }
-            }
+    /// Parse JSON output from remote data-x into a FileTree as it arrives.
+    ///
+    /// Understands both the versioned export document and the bare tree
+    /// written by older data-x versions.
+    fn read_json_tree(&self, reader: impl Read) -> Result<FileTree> {
+        Ok(self.complete(read_json(reader)?.tree))
+    }
 
-            // Send completed
+    /// Report a finished tree to the progress channel.
+    fn complete(&self, tree: FileTree) -> FileTree {
+        if let Some(root) = tree.root.and_then(|id| tree.get_node(id)) {
             let _ = self.progress_tx.send(ScanProgress::Completed {
-                total_files: file_count,
-                total_size: size,
+                total_files: root.file_count,
+                total_size: root.size,
                 tree: tree.clone(),
             });
         }
 
-        Ok(tree)
-    }
-
-    fn parse_children(&self, tree: &mut FileTree, parent_id: indextree::NodeId, children: &[serde_json::Value]) {
-        for child_val in children {
-            if let Some(obj) = child_val.as_object() {
-                let path = obj.get("path")
-                    .and_then(|v| v.as_str())
-                    .map(PathBuf::from);
-
-                let path = match path {
-                    Some(p) => p,
-                    None => continue,
-                };
-
-                let is_dir = obj.get("is_dir")
-                    .and_then(|v| v.as_bool())
-                    .unwrap_or(false);
-
-                let size = obj.get("size")
-                    .and_then(|v| v.as_u64())
-                    .unwrap_or(0);
-
-                let file_count = obj.get("file_count")
-                    .and_then(|v| v.as_u64())
-                    .unwrap_or(0);
-
-                let mut node = FileNode::new(path, is_dir);
-                node.size = size;
-                node.file_count = file_count;
-
-                let node_id = tree.add_child(parent_id, node);
-
-                // Recurse for children
-                if let Some(grandchildren) = obj.get("children").and_then(|v| v.as_array()) {
-                    self.parse_children(tree, node_id, grandchildren);
-                }
-            }
-        }
+        tree
     }
 }
 
@@ -413,9 +1091,776 @@ mod tests {
             host: "server.com".to_string(),
             port: Some(2222),
             path: PathBuf::from("/data"),
+            ..Default::default()
         };
 
         let args = target.ssh_args();
-        assert_eq!(args, vec!["-p", "2222", "admin@server.com"]);
+        assert_eq!(args, vec!["-p", "2222", "--", "admin@server.com"]);
+
+        let target = target
+            .with_jump_hosts(vec!["bastion".to_string(), "ops@gw:2200".to_string()])
+            .with_ssh_options(vec!["StrictHostKeyChecking=yes".to_string()]);
+        assert_eq!(
+            target.ssh_args(),
+            vec![
+                "-p",
+                "2222",
+                "-J",
+                "bastion,ops@gw:2200",
+                "-o",
+                "StrictHostKeyChecking=yes",
+                "--",
+                "admin@server.com"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_describe_resolves_aliases() {
+        let config = SshConfig::parse(
+            "Host db\n  HostName 10.0.0.5\n  User dba\n  Port 2222\n  ProxyJump bastion\nHost plain\n  Port 22\n",
+            Path::new("/nonexistent"),
+        );
+
+        let target = SshTarget::parse("db:/srv").unwrap();
+        assert_eq!(target.describe(&config), "db:/srv (dba@10.0.0.5:2222 via bastion)");
+
+        // Settings on the command line win over the config
+        let target = SshTarget::parse("ssh://root@db:2200/srv").unwrap().with_jump_hosts(vec!["gw".to_string()]);
+        assert_eq!(target.describe(&config), "root@db:2200:/srv (root@10.0.0.5:2200 via gw)");
+
+        let target = SshTarget::parse("plain:/srv").unwrap();
+        assert_eq!(target.describe(&config), "plain:/srv");
+    }
+
+    #[test]
+    fn test_cache_host() {
+        let config = SshConfig::parse("Host db\n  HostName 10.0.0.5\n  User dba\n  Port 2222\n", Path::new("/nonexistent"));
+
+        // The alias and what it stands for share caches
+        let alias = SshTarget::parse("db:/srv").unwrap();
+        let direct = SshTarget::parse("ssh://dba@10.0.0.5:2222/srv").unwrap();
+        assert_eq!(alias.cache_host(&config), "dba@10.0.0.5:2222");
+        assert_eq!(direct.cache_host(&config), alias.cache_host(&config));
+
+        // Other users and ports don't
+        assert_eq!(SshTarget::parse("root@db:/srv").unwrap().cache_host(&config), "root@10.0.0.5:2222");
+        assert_eq!(SshTarget::parse("files:/srv").unwrap().cache_host(&config), "files:22");
+    }
+
+    #[test]
+    fn test_compression_negotiation() {
+        let tools = RemoteTools::parse("/usr/local/bin/data-x\n/usr/bin/gzip\n");
+        assert_eq!(tools, RemoteTools { datax: true, zstd: false, gzip: true });
+        assert_eq!(RemoteTools::parse(""), RemoteTools::default());
+
+        assert_eq!(Compression::Auto.negotiate(&tools), Compression::Gzip);
+        assert_eq!(Compression::Zstd.negotiate(&tools), Compression::Ssh);
+        assert_eq!(Compression::None.negotiate(&tools), Compression::None);
+        assert_eq!(Compression::Auto.negotiate(&RemoteTools { zstd: true, ..tools }), Compression::Zstd);
+        assert_eq!(Compression::Auto.negotiate(&RemoteTools::default()), Compression::Ssh);
+
+        // Only compressors run on the host change the command
+        assert_eq!(Compression::Ssh.wrap("find ."), "find .");
+        assert!(Compression::Gzip.wrap("find .").contains("find .; echo $? >&3; } | gzip -c"));
+    }
+
+    #[test]
+    fn test_parse_json_round_trip() {
+        use crate::export::{export_json, tree_to_export_node, ExportOptions};
+        use std::time::{Duration, UNIX_EPOCH};
+
+        let mut tree = FileTree::with_root(PathBuf::from("/srv"));
+        let root = tree.root.unwrap();
+        let logs = tree.add_child(root, FileNode::new(PathBuf::from("/srv/logs"), true));
+        tree.add_child(
+            logs,
+            FileNode::new(PathBuf::from("/srv/logs/app.log"), false)
+                .with_size(4096)
+                .with_modified(UNIX_EPOCH + Duration::new(1_600_000_000, 5)),
+        );
+        tree.add_child(root, FileNode::new(PathBuf::from("/srv/current"), false).with_symlink(PathBuf::from("logs")));
+        tree.calculate_sizes();
+
+        let mut json = Vec::new();
+        export_json(&tree, &ExportOptions::default(), &mut json).unwrap();
+
+        let (tx, rx) = std::sync::mpsc::sync_channel(10);
+        let scanner = RemoteScanner::new(SshTarget::parse("host:/srv").unwrap(), tx);
+        let parsed = scanner.read_json_tree(json.as_slice()).unwrap();
+
+        assert_eq!(
+            tree_to_export_node(&tree, root),
+            tree_to_export_node(&parsed, parsed.root.unwrap())
+        );
+        assert!(matches!(rx.try_recv(), Ok(ScanProgress::Completed { total_size: 4096, .. })));
+    }
+
+    #[test]
+    fn test_datax_command_forwards_options() {
+        let (tx, _rx) = std::sync::mpsc::sync_channel(10);
+        let target = SshTarget::parse("host:/srv/it's").unwrap();
+        let scanner = RemoteScanner::new(target, tx);
+        assert_eq!(scanner.datax_command(&["--ndjson"]), r#"data-x --ndjson '/srv/it'\''s'"#);
+
+        let options = ScanOptions {
+            root_path: PathBuf::from("/ignored"),
+            max_depth: Some(3),
+            exclude_patterns: vec!["*.log".to_string(), "node_modules".to_string()],
+            cross_mount: false,
+            apparent_size: true,
+        };
+        let scanner = scanner.with_options(options);
+        assert_eq!(scanner.options.root_path, PathBuf::from("/srv/it's"));
+        assert_eq!(
+            scanner.datax_command(&["--json"]),
+            r#"data-x --json --depth 3 --exclude='*.log' --exclude='node_modules' --no-cross-mount --apparent-size '/srv/it'\''s'"#
+        );
+    }
+
+    #[test]
+    fn test_find_predicates() {
+        let options = ScanOptions {
+            max_depth: Some(1),
+            exclude_patterns: vec!["*.tmp".to_string(), "cache?".to_string()],
+            cross_mount: false,
+            ..ScanOptions::new(PathBuf::from("/srv/[a]"))
+        };
+        assert_eq!(
+            find_predicates(Path::new("/srv/[a]"), &options),
+            [
+                "-xdev",
+                r"\( -name '*.tmp' -o -path '/srv/\[a\]/*.tmp' -o -path '/srv/\[a\]/*cache\?*' -o -path '/srv/\[a\]/*/*' \) -prune -o",
+            ]
+        );
+        assert!(find_predicates(Path::new("/"), &ScanOptions::new(PathBuf::from("/"))).len() == 1);
+        assert_eq!(
+            find_predicates(Path::new("/"), &ScanOptions::new(PathBuf::from("/")).with_cross_mount(true)),
+            Vec::<String>::new()
+        );
+    }
+
+    /// Paths, file sizes, link targets and mtimes of a tree, sorted
+    ///
+    /// The root's mtime is left out as the local scanner doesn't read it.
+    #[allow(clippy::type_complexity)]
+    fn listing(tree: &FileTree) -> Vec<(PathBuf, Option<u64>, Option<PathBuf>, Option<SystemTime>)> {
+        let root = tree.root.unwrap();
+        let mut nodes: Vec<_> = root
+            .descendants(&tree.arena)
+            .map(|id| (id, tree.get_node(id).unwrap()))
+            .map(|(id, node)| {
+                (
+                    node.path.clone(),
+                    (!node.is_dir).then_some(node.size),
+                    node.symlink_target.clone(),
+                    node.modified.filter(|_| id != root),
+                )
+            })
+            .collect();
+        nodes.sort();
+        nodes
+    }
+
+    /// Drop sub-second precision from listed mtimes
+    #[allow(clippy::type_complexity)]
+    fn whole_seconds(
+        mut nodes: Vec<(PathBuf, Option<u64>, Option<PathBuf>, Option<SystemTime>)>,
+    ) -> Vec<(PathBuf, Option<u64>, Option<PathBuf>, Option<SystemTime>)> {
+        for node in &mut nodes {
+            node.3 = node.3.map(|t| {
+                let (secs, _) = crate::export::to_epoch(t);
+                from_epoch(secs, 0)
+            });
+        }
+        nodes
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_find_fallback_matches_local_scan() {
+        use crate::scanner::Scanner;
+        use std::os::unix::ffi::OsStrExt;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        // Wildcard characters in the root must not act as wildcards
+        let root = dir.path().join("scan [x]");
+        for path in ["keep/a.txt", "keep/deep/er/b.txt", "node_modules/pkg/index.js", "x.tmp", "sub/y.tmp", "c?d/e"] {
+            let path = root.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, path.to_string_lossy().as_bytes()).unwrap();
+        }
+
+        // Names that broke the old `path|type|size` lines
+        let odd = root.join("new\nline");
+        std::fs::create_dir(&odd).unwrap();
+        std::fs::write(odd.join("a|b c.txt"), b"pipes").unwrap();
+        std::fs::write(odd.join(std::ffi::OsStr::from_bytes(b"\xff\xfe.bin")), b"bytes").unwrap();
+        std::os::unix::fs::symlink("a|b c.txt", odd.join("link")).unwrap();
+        std::os::unix::fs::symlink("missing\n", odd.join("broken")).unwrap();
+
+        let cases = [
+            ScanOptions::new(root.clone()),
+            ScanOptions::new(root.clone()).with_exclude_patterns(vec!["node_modules".to_string(), "*.tmp".to_string()]),
+            ScanOptions::new(root.clone()).with_exclude_patterns(vec!["c?d".to_string(), "keep/deep".to_string()]),
+            ScanOptions::new(root.clone()).with_max_depth(Some(2)),
+            ScanOptions::new(root.clone()).with_max_depth(Some(0)),
+            ScanOptions {
+                apparent_size: false,
+                ..ScanOptions::new(root.clone())
+            },
+        ];
+        for options in cases {
+            let (tx, _rx) = std::sync::mpsc::sync_channel(10_000);
+            let local = listing(&Scanner::new(options.clone(), tx.clone()).scan().unwrap());
+
+            let target = SshTarget {
+                host: "localhost".to_string(),
+                path: root.clone(),
+                ..Default::default()
+            };
+            let scanner = RemoteScanner::new(target, tx).with_options(options.clone());
+
+            // GNU find here, and the stat loop busybox would take
+            let output = Command::new("sh").arg("-c").arg(scanner.find_command()).output().unwrap();
+            let remote = scanner.read_find_output(output.stdout.as_slice());
+            assert_eq!(listing(&remote), local, "{:?}", options);
+
+            let stat_command = scanner.find_stat_command(POSIX_STAT_FORMAT);
+            let output = Command::new("sh").arg("-c").arg(stat_command).output().unwrap();
+            let remote = scanner.read_find_output(output.stdout.as_slice());
+            assert_eq!(listing(&remote), whole_seconds(local), "{:?}", options);
+        }
+    }
+
+    /// GNU find 4.9 `-printf` records
+    const GNU_OUTPUT: &[u8] = b"drwxr-xr-x 4096 8 1700000000.2500000000\0/srv\0\0\
+        -rw-r--r-- 5000 16 1700000100.1234567890\0/srv/a|b.log\0\0\
+        drwxr-xr-x 4096 8 1699999999.0000000000\0/srv/new\nline\0\0\
+        -rw-r--r-- 10 8 1700000200.5000000000\0/srv/new\nline/\xff\xfe.bin\0\0\
+        -rw-r--r-- 1073741824 0 1700000300.0000000000\0/srv/sparse.img\0\0\
+        lrwxrwxrwx 7 0 1700000400.0000000000\0/srv/current\0a|b.log\0";
+
+    /// macOS `stat -f "%Sp %z %b %m"` records, with APFS directory sizes
+    const BSD_OUTPUT: &[u8] = b"drwxr-xr-x 224 0 1700000000\0/srv\0\0\
+        -rw-r--r-- 5000 16 1700000100\0/srv/a|b.log\0\0\
+        drwxr-xr-x 96 0 1699999999\0/srv/new\nline\0\0\
+        -rw-r--r-- 10 8 1700000200\0/srv/new\nline/\xff\xfe.bin\0\0\
+        -rw-r--r-- 1073741824 0 1700000300\0/srv/sparse.img\0\0\
+        lrwxr-xr-x 7 0 1700000400\0/srv/current\0a|b.log\0";
+
+    /// busybox `stat -c "%A %s %b %Y"` records, one for a file deleted
+    /// between find and stat
+    const BUSYBOX_OUTPUT: &[u8] = b"drwxr-xr-x 4096 8 1700000000\0/srv\0\0\
+        -rw-r--r-- 5000 16 1700000100\0/srv/a|b.log\0\0\
+        \0/srv/gone.tmp\0\0\
+        drwxr-xr-x 4096 8 1699999999\0/srv/new\nline\0\0\
+        -rw-r--r-- 10 8 1700000200\0/srv/new\nline/\xff\xfe.bin\0\0\
+        -rw-r--r-- 1073741824 0 1700000300\0/srv/sparse.img\0\0\
+        lrwxrwxrwx 7 0 1700000400\0/srv/current\0a|b.log\0";
+
+    /// Executable `ssh` stand-in in `dir` running `script` with the
+    /// remote command in `$command`
+    #[cfg(unix)]
+    fn fake_ssh(dir: &Path, script: &str) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+
+        let ssh = dir.join("ssh");
+        std::fs::write(&ssh, format!("#!/bin/sh\nfor command; do :; done\n{}", script)).unwrap();
+        std::fs::set_permissions(&ssh, std::fs::Permissions::from_mode(0o755)).unwrap();
+        ssh
+    }
+
+    /// Scan `/srv` through an `ssh` stand-in that prints `output` for any
+    /// command but `which data-x`. Returns the tree and the remote command.
+    #[cfg(unix)]
+    fn scan_recorded(output: &[u8], options: ScanOptions) -> (FileTree, String) {
+        let dir = tempfile::TempDir::new().unwrap();
+        let ssh = fake_ssh(
+            dir.path(),
+            "case \"$command\" in\n    which\\ data-x*) exit 0 ;;\nesac\nprintf '%s' \"$command\" > \"$0.command\"\ncat \"$0.output\"\n",
+        );
+        std::fs::write(dir.path().join("ssh.output"), output).unwrap();
+
+        let (tx, _rx) = std::sync::mpsc::sync_channel(10_000);
+        let target = SshTarget::parse("admin@files:/srv").unwrap();
+        let tree = RemoteScanner::new(target, tx)
+            .with_options(options)
+            .with_ssh_program(&ssh)
+            .scan()
+            .unwrap();
+        let command = std::fs::read_to_string(dir.path().join("ssh.command")).unwrap();
+        (tree, command)
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_recorded_find_outputs() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let binary = PathBuf::from(std::ffi::OsStr::from_bytes(b"/srv/new\nline/\xff\xfe.bin"));
+        for (name, output, fraction) in [
+            ("GNU", GNU_OUTPUT, 123_456_789),
+            ("BSD", BSD_OUTPUT, 0),
+            ("busybox", BUSYBOX_OUTPUT, 0),
+        ] {
+            let options = ScanOptions::new(PathBuf::from("/srv")).with_apparent_size(false);
+            let (tree, command) = scan_recorded(output, options);
+            assert!(command.contains("-printf") && command.contains("stat -f"), "{}", command);
+
+            // Directories and links count nothing, like a local scan
+            assert_eq!(
+                listing(&tree)
+                    .into_iter()
+                    .map(|(path, size, target, _)| (path, size, target))
+                    .collect::<Vec<_>>(),
+                [
+                    (PathBuf::from("/srv"), None, None),
+                    (PathBuf::from("/srv/a|b.log"), Some(8192), None),
+                    (PathBuf::from("/srv/current"), Some(0), Some(PathBuf::from("a|b.log"))),
+                    (PathBuf::from("/srv/new\nline"), None, None),
+                    (binary.clone(), Some(4096), None),
+                    (PathBuf::from("/srv/sparse.img"), Some(0), None),
+                ],
+                "{}",
+                name
+            );
+            let root = tree.get_node(tree.root.unwrap()).unwrap();
+            assert_eq!((root.size, root.file_count), (12288, 4), "{}", name);
+
+            let log = tree.find_by_path(&PathBuf::from("/srv/a|b.log")).unwrap();
+            assert_eq!(tree.get_node(log).unwrap().modified, Some(from_epoch(1700000100, fraction)), "{}", name);
+            let link = tree.get_node(tree.find_by_path(&PathBuf::from("/srv/current")).unwrap()).unwrap();
+            assert!(link.is_symlink, "{}", name);
+
+            let (tree, _) = scan_recorded(output, ScanOptions::new(PathBuf::from("/srv")));
+            let root = tree.get_node(tree.root.unwrap()).unwrap();
+            assert_eq!(root.size, 5000 + 10 + 1073741824, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_parse_find_record() {
+        assert_eq!(
+            FindRecord::parse(b"-rw-r--r-- 12 8 1700000000.1234567890"),
+            Some(FindRecord {
+                kind: b'-',
+                size: 12,
+                blocks: 8,
+                modified: Some(from_epoch(1700000000, 123_456_789)),
+            })
+        );
+        assert_eq!(FindRecord::parse(b"lrwxrwxrwx 3 0 -1.25").unwrap().modified, Some(from_epoch(-2, 750_000_000)));
+        assert_eq!(FindRecord::parse(b"drwxr-xr-x 64 0 1700000000").unwrap().modified, Some(from_epoch(1700000000, 0)));
+        assert_eq!(FindRecord::parse(b"drwxr-xr-x 64 0 soon").unwrap().modified, None);
+        assert_eq!(FindRecord::parse(b""), None);
+        assert_eq!(FindRecord::parse(b"-rw-r--r-- 12"), None);
+    }
+
+    /// Root names that break or escape naively quoted commands
+    const HOSTILE_NAMES: &[&str] = &[
+        "it's",
+        "'; touch pwned; '",
+        "$(touch pwned)",
+        "`touch pwned`",
+        "a\"b\\c;touch pwned",
+        "new\nline & touch pwned",
+        "-rf",
+    ];
+
+    #[test]
+    #[cfg(unix)]
+    fn test_hostile_paths_run_through_ssh() {
+        let home = tempfile::TempDir::new().unwrap();
+        let bin = tempfile::TempDir::new().unwrap();
+        // Runs the command like sshd would, in `home` and without data-x
+        let ssh = fake_ssh(
+            bin.path(),
+            &format!("cd {} && PATH=/usr/bin:/bin exec sh -c \"$command\"\n", quote(&home.path().to_string_lossy())),
+        );
+
+        for name in HOSTILE_NAMES {
+            let root = home.path().join(name);
+            std::fs::create_dir(&root).unwrap();
+            std::fs::write(root.join("kept.txt"), b"12345").unwrap();
+            std::fs::write(root.join("skipped '$(touch pwned)'.txt"), b"1").unwrap();
+
+            for path in [root.clone(), PathBuf::from(name)] {
+                let (tx, _rx) = std::sync::mpsc::sync_channel(10_000);
+                let target = SshTarget {
+                    host: "-oProxyCommand=touch pwned".to_string(),
+                    path: path.clone(),
+                    ..Default::default()
+                };
+                let options = ScanOptions::new(path.clone())
+                    .with_exclude_patterns(vec!["skipped '$(touch pwned)'*".to_string()]);
+                let tree = RemoteScanner::new(target, tx)
+                    .with_options(options)
+                    .with_ssh_program(&ssh)
+                    .scan()
+                    .unwrap();
+
+                let root_node = tree.get_node(tree.root.unwrap()).unwrap();
+                assert_eq!(root_node.name, *name, "{:?}", path);
+                assert_eq!((root_node.size, root_node.file_count), (5, 1), "{:?}", path);
+            }
+            std::fs::remove_dir_all(&root).unwrap();
+        }
+        assert_eq!(std::fs::read_dir(home.path()).unwrap().count(), 0);
+        assert!(!bin.path().join("pwned").exists());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_hostile_datax_arguments() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let bin = tempfile::TempDir::new().unwrap();
+        let datax = bin.path().join("data-x");
+        std::fs::write(&datax, "#!/bin/sh\nprintf '%s\\0' \"$@\"\n").unwrap();
+        std::fs::set_permissions(&datax, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        for name in HOSTILE_NAMES {
+            let (tx, _rx) = std::sync::mpsc::sync_channel(10);
+            let target = SshTarget::parse(&format!("host:{}", name)).unwrap();
+            let pattern = format!("-{}", name);
+            let scanner = RemoteScanner::new(target, tx).with_options(ScanOptions {
+                root_path: PathBuf::new(),
+                max_depth: None,
+                exclude_patterns: vec![pattern.clone()],
+                cross_mount: true,
+                apparent_size: false,
+            });
+
+            let output = Command::new("sh")
+                .current_dir(bin.path())
+                .env("PATH", format!("{}:/usr/bin:/bin", bin.path().display()))
+                .arg("-c")
+                .arg(scanner.datax_command(&["--json"]))
+                .output()
+                .unwrap();
+            let args: Vec<_> = output.stdout.split(|&b| b == 0).map(String::from_utf8_lossy).collect();
+            let path = operand(Path::new(name));
+            assert_eq!(
+                args,
+                ["--json", &format!("--exclude={}", pattern), &path.to_string_lossy(), ""],
+                "{}",
+                name
+            );
+        }
+        assert!(!bin.path().join("pwned").exists());
+    }
+
+    /// Stand-in for `ssh` to a host with data-x installed. `script` sees
+    /// the remote command in `$command` and may print `$0.json` or
+    /// `$0.ndjson`, a scan of a small `/srv` tree.
+    #[cfg(unix)]
+    fn datax_host(dir: &Path, script: &str) -> PathBuf {
+        use crate::export::{export_json, export_ndjson, ExportOptions};
+
+        let mut tree = FileTree::with_root(PathBuf::from("/srv"));
+        let root = tree.root.unwrap();
+        let logs = tree.add_child(root, FileNode::new(PathBuf::from("/srv/logs"), true));
+        tree.add_child(logs, FileNode::new(PathBuf::from("/srv/logs/app.log"), false).with_size(4096));
+        tree.calculate_sizes();
+
+        let mut json = Vec::new();
+        export_json(&tree, &ExportOptions::default(), &mut json).unwrap();
+        std::fs::write(dir.join("ssh.json"), json).unwrap();
+        let mut ndjson = Vec::new();
+        export_ndjson(&tree, &ExportOptions::default(), &mut ndjson).unwrap();
+        std::fs::write(dir.join("ssh.ndjson"), ndjson).unwrap();
+
+        fake_ssh(
+            dir,
+            &format!(
+                "case \"$command\" in\n    which\\ data-x*) echo /usr/local/bin/data-x; exit 0 ;;\nesac\nprintf '%s\\n' \"$command\" >> \"$0.log\"\n{}",
+                script
+            ),
+        )
+    }
+
+    /// Scan `/srv` through `ssh`, returning the tree, the progress events
+    /// and the remote commands run
+    #[cfg(unix)]
+    fn scan_datax_host(dir: &Path, ssh: &Path) -> (FileTree, Vec<ScanProgress>, Vec<String>) {
+        let (tx, rx) = std::sync::mpsc::sync_channel(10_000);
+        let target = SshTarget::parse("admin@files:/srv").unwrap();
+        let tree = RemoteScanner::new(target, tx).with_ssh_program(ssh).scan().unwrap();
+        let log = std::fs::read_to_string(dir.join("ssh.log")).unwrap();
+        (tree, rx.try_iter().collect(), log.lines().map(String::from).collect())
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_datax_progress_is_forwarded() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let ssh = datax_host(
+            dir.path(),
+            concat!(
+                r#"echo '{"event":"scanning","path":"/srv/logs","files_found":1500,"estimated_total":3000,"bytes_processed":65536}' >&2"#,
+                "\n",
+                r#"echo 'warning: something unrelated' >&2"#,
+                "\n",
+                r#"echo '{"event":"completed","total_files":3000,"total_size":131072}' >&2"#,
+                "\n",
+                r#"cat "$0.ndjson""#,
+                "\n",
+            ),
+        );
+
+        let (tree, progress, commands) = scan_datax_host(dir.path(), &ssh);
+        assert_eq!(commands, ["data-x --ndjson --progress-json '/srv'"]);
+        assert_eq!(tree.get_node(tree.root.unwrap()).unwrap().size, 4096);
+
+        // Remote progress arrives as local progress, before completion
+        let scanning = progress
+            .iter()
+            .position(|p| {
+                matches!(p, ScanProgress::Scanning { files_found: 1500, estimated_total: 3000, bytes_processed: 65536, path }
+                    if path == Path::new("/srv/logs"))
+            })
+            .expect("remote progress forwarded");
+        let completed = progress.iter().position(|p| matches!(p, ScanProgress::Completed { .. })).unwrap();
+        assert!(scanning < completed);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_datax_without_progress_json() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let ssh = datax_host(
+            dir.path(),
+            concat!(
+                "case \"$command\" in\n",
+                "    *--progress-json*) echo \"error: unexpected argument '--progress-json' found\" >&2; exit 2 ;;\n",
+                "esac\n",
+                "cat \"$0.ndjson\"\n",
+            ),
+        );
+
+        let (tree, _, commands) = scan_datax_host(dir.path(), &ssh);
+        assert_eq!(commands, ["data-x --ndjson --progress-json '/srv'", "data-x --ndjson '/srv'"]);
+        assert_eq!(tree.get_node(tree.root.unwrap()).unwrap().size, 4096);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_datax_without_ndjson() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let ssh = datax_host(
+            dir.path(),
+            concat!(
+                "case \"$command\" in\n",
+                "    *--ndjson*) echo \"error: unexpected argument '--ndjson' found\" >&2; exit 2 ;;\n",
+                "esac\n",
+                "cat \"$0.json\"\n",
+            ),
+        );
+
+        let (tree, progress, commands) = scan_datax_host(dir.path(), &ssh);
+        assert_eq!(commands, ["data-x --ndjson --progress-json '/srv'", "data-x --json '/srv'"]);
+        assert_eq!(tree.get_node(tree.root.unwrap()).unwrap().size, 4096);
+        assert!(progress.iter().any(|p| matches!(p, ScanProgress::Completed { total_size: 4096, .. })));
+
+        // A failing --json scan reports the remote error
+        let dir = tempfile::TempDir::new().unwrap();
+        let ssh = datax_host(dir.path(), "echo 'Error: /srv: permission denied' >&2\nexit 1\n");
+        let (tx, _rx) = std::sync::mpsc::sync_channel(10_000);
+        let target = SshTarget::parse("admin@files:/srv").unwrap();
+        let err = RemoteScanner::new(target, tx).with_ssh_program(&ssh).scan().unwrap_err();
+        assert!(err.to_string().contains("permission denied"), "{}", err);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_compressed_find_fallback() {
+        use crate::scanner::Scanner;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = dir.path().join("srv");
+        for i in 0..200 {
+            let path = root.join(format!("logs/{}/app.log", i % 20));
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path.with_file_name(format!("app-{}.log", i)), b"entry").unwrap();
+        }
+        let (tx, _rx) = std::sync::mpsc::sync_channel(10_000);
+        let local = listing(&Scanner::new(ScanOptions::new(root.clone()), tx).scan().unwrap());
+
+        // A host with both compressors but no data-x, running commands here
+        let ssh = fake_ssh(
+            dir.path(),
+            "case \"$command\" in\n    which\\ *) echo /usr/bin/zstd; echo /usr/bin/gzip; exit 0 ;;\nesac\nprintf '%s\\n' \"$1\" > \"$0.first\"\nexec sh -c \"$command\"\n",
+        );
+
+        let mut modes = vec![Compression::Gzip, Compression::Ssh, Compression::None];
+        if Command::new("zstd").arg("--version").output().is_ok() {
+            modes.push(Compression::Zstd);
+        }
+        for compression in modes {
+            let (tx, rx) = std::sync::mpsc::sync_channel(10_000);
+            let target = SshTarget {
+                host: "localhost".to_string(),
+                path: root.clone(),
+                ..Default::default()
+            }
+            .with_compression(compression);
+            let scanner = RemoteScanner::new(target, tx)
+                .with_options(ScanOptions::new(root.clone()))
+                .with_ssh_program(&ssh);
+            let plain = Command::new("sh").arg("-c").arg(scanner.find_command()).output().unwrap().stdout;
+
+            let tree = scanner.scan().unwrap();
+            assert_eq!(listing(&tree), local, "{:?}", compression);
+
+            let first = std::fs::read_to_string(dir.path().join("ssh.first")).unwrap();
+            assert_eq!(first.trim() == "-C", compression == Compression::Ssh, "{:?}", compression);
+
+            let (received, decoded) = rx
+                .try_iter()
+                .filter_map(|p| match p {
+                    ScanProgress::Transfer { received, decoded } => Some((received, decoded)),
+                    _ => None,
+                })
+                .last()
+                .expect("transfer reported");
+            assert_eq!(decoded, plain.len() as u64, "{:?}", compression);
+            match compression {
+                Compression::Gzip | Compression::Zstd => assert!(received < decoded / 2, "{:?}", compression),
+                _ => assert_eq!(received, decoded, "{:?}", compression),
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_compressed_datax_keeps_exit_status() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        datax_host(dir.path(), "");
+        let datax = dir.path().join("data-x");
+        std::fs::write(
+            &datax,
+            format!(
+                "#!/bin/sh\ncase \"$*\" in\n    *--progress-json*) echo \"error: unexpected argument '--progress-json' found\" >&2; exit 2 ;;\nesac\ncat '{}'\n",
+                dir.path().join("ssh.ndjson").display()
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&datax, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        // data-x and gzip on the host, the old data-x run from `dir`
+        let ssh = fake_ssh(
+            dir.path(),
+            "case \"$command\" in\n    which\\ *) echo /usr/local/bin/data-x; echo /bin/gzip; exit 0 ;;\nesac\nprintf '%s\\n' \"$command\" >> \"$0.log\"\nPATH=\"${0%/*}:$PATH\" exec sh -c \"$command\"\n",
+        );
+
+        // The rejected flag is still seen through the compressor
+        let (tree, _, commands) = scan_datax_host(dir.path(), &ssh);
+        assert_eq!(commands.len(), 2, "{:?}", commands);
+        assert!(commands[0].contains("data-x --ndjson --progress-json '/srv'; echo $? >&3; } | gzip -c"));
+        assert!(commands[1].contains("data-x --ndjson '/srv'; echo $? >&3; } | gzip -c"));
+        assert_eq!(tree.get_node(tree.root.unwrap()).unwrap().size, 4096);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_browse_with_agent() {
+        use crate::agent::Agent;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = dir.path().canonicalize().unwrap().join("srv");
+        std::fs::create_dir_all(root.join("logs/old")).unwrap();
+        std::fs::write(root.join("logs/old/app.log"), b"entry").unwrap();
+        std::fs::write(root.join("notes.txt"), b"notes").unwrap();
+
+        // The host's agent, answered ahead of time by one running here
+        let request = |id: u64, path: &Path| serde_json::json!({ "id": id, "op": "list", "path": path }).to_string();
+        let requests = [r#"{"id":1,"op":"hello"}"#.to_string(), request(2, &root), request(3, &root.join("logs"))];
+        let mut replies = Vec::new();
+        Agent::new(ScanOptions::new(PathBuf::new()))
+            .serve(std::io::Cursor::new(requests.join("\n")), &mut replies)
+            .unwrap();
+        std::fs::write(dir.path().join("ssh.replies"), replies).unwrap();
+        let ssh = fake_ssh(
+            dir.path(),
+            "printf '%s' \"$command\" > \"$0.command\"\nn=0\nwhile IFS= read -r request; do\n    n=$((n + 1))\n    printf '%s\\n' \"$request\" >> \"$0.requests\"\n    sed -n \"${n}p\" \"$0.replies\"\ndone\n",
+        );
+
+        let (tx, rx) = std::sync::mpsc::sync_channel(10_000);
+        let target = SshTarget {
+            host: "localhost".to_string(),
+            path: root.clone(),
+            ..Default::default()
+        };
+        let scanner = RemoteScanner::new(target, tx)
+            .with_options(ScanOptions::new(root.clone()))
+            .with_ssh_program(&ssh);
+        let (request_tx, request_rx) = mpsc::channel();
+        let (event_tx, event_rx) = mpsc::channel();
+        request_tx.send(root.join("logs")).unwrap();
+        drop(request_tx);
+        let tree = scanner.browse(request_rx, event_tx).unwrap();
+
+        // Only the top level at first, sized by what is below it
+        let command = std::fs::read_to_string(dir.path().join("ssh.command")).unwrap();
+        assert_eq!(command, "data-x agent --stdio --no-cross-mount --apparent-size");
+        let paths: Vec<PathBuf> = listing(&tree).into_iter().map(|(path, ..)| path).collect();
+        assert_eq!(paths, [root.clone(), root.join("logs"), root.join("notes.txt")]);
+        let logs = tree.find_by_path(&root.join("logs")).unwrap();
+        assert_eq!(tree.get_node(logs).unwrap().size, 5);
+        assert!(rx.try_iter().any(|p| matches!(p, ScanProgress::Completed { total_size: 10, .. })));
+
+        let events: Vec<AgentEvent> = event_rx.try_iter().collect();
+        assert!(matches!(events[0], AgentEvent::Connected));
+        match &events[1..] {
+            [AgentEvent::Listed { path, result: Ok(node) }] => {
+                assert_eq!(path, &root.join("logs"));
+                assert_eq!(node.children.len(), 1);
+                assert_eq!(node.children[0].name, "old");
+            }
+            other => panic!("expected one listing, got {:?}", other),
+        }
+        let sent = std::fs::read_to_string(dir.path().join("ssh.requests")).unwrap();
+        let sent: Vec<serde_json::Value> = sent.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(sent.len(), 3);
+        assert_eq!(sent[2]["path"], root.join("logs").to_str().unwrap());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_browse_without_agent() {
+        use crate::scanner::Scanner;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = dir.path().join("srv");
+        std::fs::create_dir_all(root.join("logs/old")).unwrap();
+        std::fs::write(root.join("logs/old/app.log"), b"entry").unwrap();
+        let (tx, _rx) = std::sync::mpsc::sync_channel(10_000);
+        let local = listing(&Scanner::new(ScanOptions::new(root.clone()), tx).scan().unwrap());
+
+        // No data-x on the host: the whole tree comes from find
+        let ssh = fake_ssh(
+            dir.path(),
+            "case \"$command\" in\n    data-x\\ *) echo 'sh: data-x: not found' >&2; exit 127 ;;\n    which\\ *) exit 0 ;;\nesac\nexec sh -c \"$command\"\n",
+        );
+        let (tx, _rx) = std::sync::mpsc::sync_channel(10_000);
+        let target = SshTarget {
+            host: "localhost".to_string(),
+            path: root.clone(),
+            ..Default::default()
+        };
+        let scanner = RemoteScanner::new(target, tx)
+            .with_options(ScanOptions::new(root.clone()))
+            .with_ssh_program(&ssh);
+        let err = scanner.agent().err().unwrap();
+        assert_eq!(err.to_string(), "no agent: sh: data-x: not found");
+
+        let (_request_tx, request_rx) = mpsc::channel();
+        let (event_tx, event_rx) = mpsc::channel();
+        let tree = scanner.browse(request_rx, event_tx).unwrap();
+        assert_eq!(listing(&tree), local);
+        assert!(event_rx.try_recv().is_err());
     }
 }
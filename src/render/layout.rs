@@ -0,0 +1,224 @@
+//! Renderer-independent squarified treemap layout.
+
+use ratatui::style::Color;
+
+use crate::tree::{FileTree, NodeId};
+use crate::ui::colors::get_file_type_color;
+
+/// Height reserved for a directory's label above its children
+const HEADER_HEIGHT: f64 = 16.0;
+/// Inner padding between a directory and its children
+const PADDING: f64 = 2.0;
+/// Smallest box (in pixels) that is still subdivided into children
+const MIN_NEST_SIZE: f64 = 24.0;
+
+/// Options for laying out a treemap image
+#[derive(Debug, Clone, Copy)]
+pub struct LayoutOptions {
+    pub width: f64,
+    pub height: f64,
+    /// How many directory levels are nested inside each other
+    pub max_depth: usize,
+}
+
+/// A positioned box in the treemap, in paint order (parents first)
+#[derive(Debug, Clone)]
+pub struct LayoutRect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    /// Nesting level (0 for children of the layout root)
+    pub depth: usize,
+    pub name: String,
+    pub size: u64,
+    pub is_dir: bool,
+    pub color: (u8, u8, u8),
+}
+
+/// Lay out `sizes` (sorted descending) in the given area with the squarified
+/// algorithm. Returns one `(x, y, width, height)` per input size.
+pub fn squarify(sizes: &[u64], x: f64, y: f64, width: f64, height: f64) -> Vec<(f64, f64, f64, f64)> {
+    let mut result = Vec::with_capacity(sizes.len());
+    let total: u64 = sizes.iter().sum();
+
+    if total == 0 || width <= 0.0 || height <= 0.0 {
+        return result;
+    }
+
+    let scale = width * height / total as f64;
+    let (mut x, mut y, mut width, mut height) = (x, y, width, height);
+    let mut start = 0;
+
+    while start < sizes.len() {
+        // Lay the row along the shorter side
+        let side = width.min(height);
+        let mut end = start + 1;
+        let mut row_area = sizes[start] as f64 * scale;
+
+        while end < sizes.len() {
+            let next_area = sizes[end] as f64 * scale;
+            let current = worst_ratio(&sizes[start..end], row_area, side, scale);
+            let extended = worst_ratio(&sizes[start..=end], row_area + next_area, side, scale);
+            if extended > current {
+                break;
+            }
+            row_area += next_area;
+            end += 1;
+        }
+
+        let thickness = row_area / side;
+        let mut offset = 0.0;
+
+        for &size in &sizes[start..end] {
+            let length = size as f64 * scale / thickness;
+            if width >= height {
+                result.push((x, y + offset, thickness, length));
+            } else {
+                result.push((x + offset, y, length, thickness));
+            }
+            offset += length;
+        }
+
+        if width >= height {
+            x += thickness;
+            width -= thickness;
+        } else {
+            y += thickness;
+            height -= thickness;
+        }
+
+        start = end;
+    }
+
+    result
+}
+
+/// Worst aspect ratio of a row of items laid along `side`.
+fn worst_ratio(row: &[u64], row_area: f64, side: f64, scale: f64) -> f64 {
+    let max = row.iter().max().copied().unwrap_or(0) as f64 * scale;
+    let min = row.iter().min().copied().unwrap_or(0) as f64 * scale;
+    let side_sq = side * side;
+    let area_sq = row_area * row_area;
+
+    if min <= 0.0 || area_sq <= 0.0 {
+        return f64::INFINITY;
+    }
+
+    (side_sq * max / area_sq).max(area_sq / (side_sq * min))
+}
+
+/// Convert a terminal color to an RGB triple
+fn rgb(color: Color) -> (u8, u8, u8) {
+    match color {
+        Color::Rgb(r, g, b) => (r, g, b),
+        _ => (128, 128, 128),
+    }
+}
+
+/// Lay out the children of `root` (recursively, up to `max_depth` levels).
+pub fn layout_treemap(tree: &FileTree, root: NodeId, options: &LayoutOptions) -> Vec<LayoutRect> {
+    let mut result = Vec::new();
+    let mut stack = vec![(root, 0usize, 0.0, 0.0, options.width, options.height)];
+
+    while let Some((parent_id, depth, x, y, width, height)) = stack.pop() {
+        let mut children: Vec<(NodeId, u64)> = tree
+            .get_children(parent_id)
+            .into_iter()
+            .filter_map(|id| tree.get_node(id).map(|n| (id, n)))
+            .filter(|(_, n)| !n.excluded && n.size > 0)
+            .map(|(id, n)| (id, n.size))
+            .collect();
+        children.sort_by_key(|&(_, size)| std::cmp::Reverse(size));
+
+        let sizes: Vec<u64> = children.iter().map(|&(_, size)| size).collect();
+        let rects = squarify(&sizes, x, y, width, height);
+
+        for (&(node_id, size), &(rx, ry, rw, rh)) in children.iter().zip(rects.iter()) {
+            let node = tree.get_node(node_id).expect("Node must exist");
+            let nest = node.is_dir
+                && depth + 1 < options.max_depth
+                && rw >= MIN_NEST_SIZE
+                && rh >= MIN_NEST_SIZE + HEADER_HEIGHT
+                && !tree.get_children(node_id).is_empty();
+
+            result.push(LayoutRect {
+                x: rx,
+                y: ry,
+                width: rw,
+                height: rh,
+                depth,
+                name: node.name.clone(),
+                size,
+                is_dir: node.is_dir,
+                color: rgb(get_file_type_color(node.extension.as_deref(), node.is_dir)),
+            });
+
+            if nest {
+                stack.push((
+                    node_id,
+                    depth + 1,
+                    rx + PADDING,
+                    ry + HEADER_HEIGHT,
+                    rw - 2.0 * PADDING,
+                    rh - HEADER_HEIGHT - PADDING,
+                ));
+            }
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::FileNode;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_squarify_fills_area() {
+        let sizes = [600, 300, 100, 50, 25, 25];
+        let rects = squarify(&sizes, 0.0, 0.0, 200.0, 100.0);
+
+        assert_eq!(rects.len(), sizes.len());
+
+        let total_area: f64 = rects.iter().map(|&(_, _, w, h)| w * h).sum();
+        assert!((total_area - 20000.0).abs() < 1e-6);
+
+        let scale = 20000.0 / 1100.0;
+        for (&(x, y, w, h), &size) in rects.iter().zip(sizes.iter()) {
+            assert!(x >= -1e-9 && y >= -1e-9);
+            assert!(x + w <= 200.0 + 1e-9 && y + h <= 100.0 + 1e-9);
+            assert!((w * h - size as f64 * scale).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_squarify_empty() {
+        assert!(squarify(&[], 0.0, 0.0, 10.0, 10.0).is_empty());
+        assert!(squarify(&[0, 0], 0.0, 0.0, 10.0, 10.0).is_empty());
+        assert!(squarify(&[5], 0.0, 0.0, 0.0, 10.0).is_empty());
+    }
+
+    #[test]
+    fn test_layout_depth_control() {
+        let mut tree = FileTree::with_root(PathBuf::from("/test"));
+        let root = tree.root.unwrap();
+        let sub = tree.add_child(root, FileNode::new(PathBuf::from("/test/sub"), true));
+        tree.add_child(sub, FileNode::new(PathBuf::from("/test/sub/a.rs"), false).with_size(800));
+        tree.add_child(root, FileNode::new(PathBuf::from("/test/b.mp4"), false).with_size(200));
+        tree.calculate_sizes();
+
+        let flat = layout_treemap(&tree, root, &LayoutOptions { width: 400.0, height: 300.0, max_depth: 1 });
+        assert_eq!(flat.len(), 2);
+        assert!(flat.iter().all(|r| r.depth == 0));
+
+        let nested = layout_treemap(&tree, root, &LayoutOptions { width: 400.0, height: 300.0, max_depth: 2 });
+        assert_eq!(nested.len(), 3);
+        let sub_rect = nested.iter().find(|r| r.name == "sub").unwrap();
+        let file_rect = nested.iter().find(|r| r.name == "a.rs").unwrap();
+        assert_eq!(file_rect.depth, 1);
+        assert!(file_rect.x >= sub_rect.x && file_rect.y >= sub_rect.y + HEADER_HEIGHT);
+    }
+}
@@ -0,0 +1,206 @@
+//! Headless treemap rendering.
+//!
+//! Lays out a scanned tree with the squarified algorithm and writes it as an
+//! SVG document or (with the `gui` feature, which pulls in `image`) a PNG,
+//! without needing a terminal or a display.
+
+mod layout;
+#[cfg(feature = "gui")]
+mod font;
+#[cfg(feature = "gui")]
+mod png;
+mod svg;
+
+use std::io::Write;
+use std::path::Path;
+
+use crate::tree::FileTree;
+
+pub use layout::{layout_treemap, squarify, LayoutOptions, LayoutRect};
+
+/// Image format of a rendered treemap
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Svg,
+    Png,
+}
+
+impl ImageFormat {
+    /// Guess the format from a file extension (`.svg` or `.png`)
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_string_lossy().to_lowercase().as_str() {
+            "svg" => Some(ImageFormat::Svg),
+            "png" => Some(ImageFormat::Png),
+            _ => None,
+        }
+    }
+}
+
+/// Options for rendering a treemap image
+#[derive(Debug, Clone, Copy)]
+pub struct RenderOptions {
+    /// Image width in pixels
+    pub width: u32,
+    /// Image height in pixels
+    pub height: u32,
+    /// Number of directory levels nested inside each other
+    pub max_depth: usize,
+    /// Draw names and sizes on boxes that are large enough
+    pub labels: bool,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            width: 1600,
+            height: 1000,
+            max_depth: 3,
+            labels: true,
+        }
+    }
+}
+
+/// Label for a box, truncated to `max_chars` characters.
+///
+/// Prefers "name size", falling back to the bare (possibly truncated) name.
+fn label_text(rect: &LayoutRect, max_chars: usize) -> Option<String> {
+    let size = crate::format::format_size(rect.size);
+    let full = format!("{} {}", rect.name, size);
+
+    if full.chars().count() <= max_chars {
+        return Some(full);
+    }
+
+    let name_len = rect.name.chars().count();
+    if name_len <= max_chars {
+        return Some(rect.name.clone());
+    }
+
+    if max_chars < 3 {
+        return None;
+    }
+
+    let truncated: String = rect.name.chars().take(max_chars - 2).collect();
+    Some(format!("{}..", truncated))
+}
+
+/// Black or white, whichever reads better on the given background
+fn label_color((r, g, b): (u8, u8, u8)) -> (u8, u8, u8) {
+    let luminance = 0.299 * r as f64 + 0.587 * g as f64 + 0.114 * b as f64;
+    if luminance > 150.0 {
+        (0, 0, 0)
+    } else {
+        (255, 255, 255)
+    }
+}
+
+/// Render the treemap of a tree's root into `writer`.
+///
+/// # Returns
+/// * `Ok(())` on success
+/// * `Err(std::io::Error)` on write failure, empty tree, or PNG requested
+///   without the `gui` feature
+pub fn render_treemap(
+    tree: &FileTree,
+    format: ImageFormat,
+    options: &RenderOptions,
+    writer: &mut impl Write,
+) -> Result<(), std::io::Error> {
+    let root = tree
+        .root
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidInput, "empty tree"))?;
+
+    let layout_options = LayoutOptions {
+        width: options.width as f64,
+        height: options.height as f64,
+        max_depth: options.max_depth.max(1),
+    };
+    let rects = layout_treemap(tree, root, &layout_options);
+
+    match format {
+        ImageFormat::Svg => svg::write_svg(&rects, options, writer),
+        #[cfg(feature = "gui")]
+        ImageFormat::Png => png::write_png(&rects, options, writer),
+        #[cfg(not(feature = "gui"))]
+        ImageFormat::Png => Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "PNG output requires the gui feature (use .svg instead)",
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::FileNode;
+    use std::path::PathBuf;
+
+    fn create_test_tree() -> FileTree {
+        let mut tree = FileTree::with_root(PathBuf::from("/test"));
+        let root = tree.root.unwrap();
+        let sub = tree.add_child(root, FileNode::new(PathBuf::from("/test/src"), true));
+        tree.add_child(sub, FileNode::new(PathBuf::from("/test/src/main.rs"), false).with_size(4000));
+        tree.add_child(sub, FileNode::new(PathBuf::from("/test/src/<lib>.rs"), false).with_size(2000));
+        tree.add_child(root, FileNode::new(PathBuf::from("/test/movie.mp4"), false).with_size(6000));
+        tree.calculate_sizes();
+        tree
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(ImageFormat::from_path(Path::new("a.SVG")), Some(ImageFormat::Svg));
+        assert_eq!(ImageFormat::from_path(Path::new("a.png")), Some(ImageFormat::Png));
+        assert_eq!(ImageFormat::from_path(Path::new("a.jpg")), None);
+    }
+
+    #[test]
+    fn test_label_text_truncation() {
+        let rect = LayoutRect {
+            x: 0.0,
+            y: 0.0,
+            width: 100.0,
+            height: 20.0,
+            depth: 0,
+            name: "averylongname.txt".to_string(),
+            size: 2048,
+            is_dir: false,
+            color: (0, 0, 0),
+        };
+
+        assert_eq!(label_text(&rect, 40).unwrap(), "averylongname.txt 2.0 KB");
+        assert_eq!(label_text(&rect, 17).unwrap(), "averylongname.txt");
+        assert_eq!(label_text(&rect, 8).unwrap(), "averyl..");
+        assert!(label_text(&rect, 2).is_none());
+    }
+
+    #[test]
+    fn test_render_svg() {
+        let tree = create_test_tree();
+        let options = RenderOptions { width: 800, height: 500, ..Default::default() };
+
+        let mut buffer = Vec::new();
+        render_treemap(&tree, ImageFormat::Svg, &options, &mut buffer).unwrap();
+        let output = String::from_utf8(buffer).unwrap();
+
+        assert!(output.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"800\" height=\"500\""));
+        assert!(output.trim_end().ends_with("</svg>"));
+        // Background + src + main.rs + <lib>.rs + movie.mp4
+        assert_eq!(output.matches("<rect ").count(), 5);
+        assert!(output.contains("&lt;lib&gt;.rs"));
+        assert!(output.contains("movie.mp4"));
+    }
+
+    #[cfg(feature = "gui")]
+    #[test]
+    fn test_render_png() {
+        let tree = create_test_tree();
+        let options = RenderOptions { width: 320, height: 200, ..Default::default() };
+
+        let mut buffer = Vec::new();
+        render_treemap(&tree, ImageFormat::Png, &options, &mut buffer).unwrap();
+
+        let image = image::load_from_memory(&buffer).unwrap();
+        assert_eq!(image.width(), 320);
+        assert_eq!(image.height(), 200);
+    }
+}
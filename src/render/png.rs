@@ -0,0 +1,132 @@
+//! PNG treemap output, rasterized with the built-in bitmap font.
+
+use std::io::Write;
+
+use image::codecs::png::PngEncoder;
+use image::{ImageEncoder, Rgb, RgbImage};
+
+use super::font::{self, ADVANCE, GLYPH_HEIGHT};
+use super::layout::LayoutRect;
+use super::{label_color, label_text, RenderOptions};
+
+const BACKGROUND: Rgb<u8> = Rgb([0x11, 0x11, 0x11]);
+
+/// Clamp a floating-point rectangle to integer pixel bounds of the image
+fn pixel_bounds(rect: &LayoutRect, width: u32, height: u32) -> Option<(u32, u32, u32, u32)> {
+    let x0 = rect.x.round().max(0.0) as u32;
+    let y0 = rect.y.round().max(0.0) as u32;
+    let x1 = ((rect.x + rect.width).round().max(0.0) as u32).min(width);
+    let y1 = ((rect.y + rect.height).round().max(0.0) as u32).min(height);
+
+    if x1 > x0 && y1 > y0 {
+        Some((x0, y0, x1, y1))
+    } else {
+        None
+    }
+}
+
+/// Fill a box and draw a 1px darker border around it
+fn fill_rect(image: &mut RgbImage, (x0, y0, x1, y1): (u32, u32, u32, u32), color: (u8, u8, u8)) {
+    let (r, g, b) = color;
+    let fill = Rgb([r, g, b]);
+    let border = Rgb([r / 2, g / 2, b / 2]);
+
+    for y in y0..y1 {
+        for x in x0..x1 {
+            let on_edge = x == x0 || y == y0 || x == x1 - 1 || y == y1 - 1;
+            image.put_pixel(x, y, if on_edge { border } else { fill });
+        }
+    }
+}
+
+/// Draw text with the 5x7 bitmap font, clipped to `max_x`
+fn draw_text(image: &mut RgbImage, text: &str, x: u32, y: u32, max_x: u32, color: Rgb<u8>) {
+    for (i, c) in text.chars().enumerate() {
+        let gx = x + i as u32 * ADVANCE;
+        for (col, bits) in font::glyph(c).iter().enumerate() {
+            let px = gx + col as u32;
+            if px >= max_x || px >= image.width() {
+                return;
+            }
+            for row in 0..GLYPH_HEIGHT {
+                let py = y + row;
+                if bits >> row & 1 == 1 && py < image.height() {
+                    image.put_pixel(px, py, color);
+                }
+            }
+        }
+    }
+}
+
+/// Write laid-out treemap rectangles as a PNG image
+pub fn write_png(
+    rects: &[LayoutRect],
+    options: &RenderOptions,
+    writer: &mut impl Write,
+) -> Result<(), std::io::Error> {
+    let (width, height) = (options.width, options.height);
+    let mut image = RgbImage::from_pixel(width, height, BACKGROUND);
+
+    for rect in rects {
+        let Some(bounds) = pixel_bounds(rect, width, height) else {
+            continue;
+        };
+        fill_rect(&mut image, bounds, rect.color);
+
+        if !options.labels {
+            continue;
+        }
+
+        let (x0, y0, x1, y1) = bounds;
+        if x1 - x0 < 3 * ADVANCE + 6 || y1 - y0 < GLYPH_HEIGHT + 6 {
+            continue;
+        }
+
+        let max_chars = ((x1 - x0 - 6) / ADVANCE) as usize;
+        if let Some(text) = label_text(rect, max_chars) {
+            let (r, g, b) = label_color(rect.color);
+            draw_text(&mut image, &text, x0 + 3, y0 + 4, x1 - 2, Rgb([r, g, b]));
+        }
+    }
+
+    PngEncoder::new(writer)
+        .write_image(image.as_raw(), width, height, image::ExtendedColorType::Rgb8)
+        .map_err(std::io::Error::other)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: f64, y: f64, width: f64, height: f64) -> LayoutRect {
+        LayoutRect {
+            x,
+            y,
+            width,
+            height,
+            depth: 0,
+            name: "file.txt".to_string(),
+            size: 100,
+            is_dir: false,
+            color: (200, 100, 50),
+        }
+    }
+
+    #[test]
+    fn test_pixel_bounds_clamped() {
+        assert_eq!(pixel_bounds(&rect(-5.0, 2.4, 20.0, 200.0), 100, 100), Some((0, 2, 15, 100)));
+        assert_eq!(pixel_bounds(&rect(10.0, 10.0, 0.2, 5.0), 100, 100), None);
+    }
+
+    #[test]
+    fn test_fill_and_border() {
+        let options = RenderOptions { width: 40, height: 30, max_depth: 1, labels: false };
+        let mut buffer = Vec::new();
+        write_png(&[rect(10.0, 10.0, 20.0, 10.0)], &options, &mut buffer).unwrap();
+
+        let image = image::load_from_memory(&buffer).unwrap().to_rgb8();
+        assert_eq!(*image.get_pixel(0, 0), BACKGROUND);
+        assert_eq!(*image.get_pixel(10, 10), Rgb([100, 50, 25]));
+        assert_eq!(*image.get_pixel(15, 15), Rgb([200, 100, 50]));
+    }
+}
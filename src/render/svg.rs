@@ -0,0 +1,98 @@
+//! SVG treemap output.
+
+use std::io::Write;
+
+use super::layout::LayoutRect;
+use super::{label_color, label_text, RenderOptions};
+
+/// Approximate advance of one label character at the SVG font size
+const CHAR_WIDTH: f64 = 7.0;
+const FONT_SIZE: f64 = 12.0;
+
+/// Escape text for XML content and attribute values
+fn escape_xml(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Control characters are not allowed in XML 1.0
+            c if c.is_control() => escaped.push('?'),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Write laid-out treemap rectangles as an SVG document
+pub fn write_svg(
+    rects: &[LayoutRect],
+    options: &RenderOptions,
+    writer: &mut impl Write,
+) -> Result<(), std::io::Error> {
+    writeln!(
+        writer,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" font-family="Helvetica, Arial, sans-serif" font-size="{fs}">"#,
+        w = options.width,
+        h = options.height,
+        fs = FONT_SIZE,
+    )?;
+    writeln!(writer, r##"<rect width="100%" height="100%" fill="#111111"/>"##)?;
+
+    for rect in rects {
+        let (r, g, b) = rect.color;
+        writeln!(
+            writer,
+            r##"<rect class="{}" data-depth="{}" x="{:.2}" y="{:.2}" width="{:.2}" height="{:.2}" fill="#{:02x}{:02x}{:02x}" stroke="#111111" stroke-width="1"><title>{}</title></rect>"##,
+            if rect.is_dir { "dir" } else { "file" },
+            rect.depth,
+            rect.x,
+            rect.y,
+            rect.width,
+            rect.height,
+            r,
+            g,
+            b,
+            escape_xml(&format!("{} ({})", rect.name, crate::format::format_size(rect.size))),
+        )?;
+
+        if !options.labels {
+            continue;
+        }
+
+        let max_chars = ((rect.width - 6.0) / CHAR_WIDTH).floor();
+        if max_chars < 3.0 || rect.height < FONT_SIZE + 4.0 {
+            continue;
+        }
+
+        if let Some(text) = label_text(rect, max_chars as usize) {
+            let (tr, tg, tb) = label_color(rect.color);
+            writeln!(
+                writer,
+                r##"<text x="{:.2}" y="{:.2}" fill="#{:02x}{:02x}{:02x}">{}</text>"##,
+                rect.x + 3.0,
+                rect.y + FONT_SIZE + 1.0,
+                tr,
+                tg,
+                tb,
+                escape_xml(&text),
+            )?;
+        }
+    }
+
+    writeln!(writer, "</svg>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_xml() {
+        assert_eq!(escape_xml("a<b>&\"c'"), "a&lt;b&gt;&amp;&quot;c&apos;");
+        assert_eq!(escape_xml("line\nbreak"), "line?break");
+    }
+}
@@ -2,11 +2,11 @@
 //!
 //! Provides cross-platform disk space retrieval using the fs2 crate.
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use fs2::statvfs;
 
 /// Information about disk space for a mount point.
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub struct DiskSpaceInfo {
     /// Total disk capacity in bytes
     pub total: u64,
@@ -35,7 +35,7 @@ impl DiskSpaceInfo {
 ///
 /// # Arguments
 /// * `path` - Any path on the filesystem. The function will get info for the
-///            mount point containing this path.
+///   mount point containing this path.
 ///
 /// # Returns
 /// * `Some(DiskSpaceInfo)` - Disk space information if successful
@@ -66,6 +66,45 @@ pub fn get_disk_space<P: AsRef<Path>>(path: P) -> Option<DiskSpaceInfo> {
     }
 }
 
+/// Find the mount point of the filesystem containing `path`.
+///
+/// Walks up the (canonicalized) path until the parent lives on a different
+/// device. On non-Unix platforms this returns the path's root component.
+///
+/// # Returns
+/// * `Some(PathBuf)` - The mount point directory
+/// * `None` - If the path doesn't exist
+pub fn find_mount_point<P: AsRef<Path>>(path: P) -> Option<PathBuf> {
+    let path = path.as_ref().canonicalize().ok()?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+
+        let mut current = path.as_path();
+        let mut device = std::fs::metadata(current).ok()?.dev();
+
+        while let Some(parent) = current.parent() {
+            let parent_device = match std::fs::metadata(parent) {
+                Ok(metadata) => metadata.dev(),
+                Err(_) => break,
+            };
+            if parent_device != device {
+                break;
+            }
+            current = parent;
+            device = parent_device;
+        }
+
+        Some(current.to_path_buf())
+    }
+
+    #[cfg(not(unix))]
+    {
+        path.ancestors().last().map(Path::to_path_buf)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -90,6 +129,16 @@ mod tests {
         assert!(info.is_none(), "Should return None for nonexistent path");
     }
 
+    #[test]
+    fn test_find_mount_point() {
+        let current_dir = env::current_dir().expect("Failed to get current directory");
+        let mount = find_mount_point(&current_dir).expect("Current directory has a mount point");
+
+        assert!(current_dir.canonicalize().unwrap().starts_with(&mount));
+        assert_eq!(find_mount_point(&mount), Some(mount.clone()));
+        assert!(find_mount_point("/this/path/definitely/does/not/exist/xyz123").is_none());
+    }
+
     #[test]
     fn test_usage_percent() {
         let info = DiskSpaceInfo {
@@ -7,7 +7,7 @@ mod disk_space;
 mod progress;
 mod walker;
 
-pub use disk_space::{get_disk_space, DiskSpaceInfo};
+pub use disk_space::{find_mount_point, get_disk_space, DiskSpaceInfo};
 pub use progress::ScanProgress;
 pub use walker::{ScanOptions, Scanner};
 
@@ -59,6 +59,14 @@ pub enum ScanProgress {
         parent_path: PathBuf,
     },
 
+    /// Scan output received from a remote host so far
+    Transfer {
+        /// Bytes that came over the connection
+        received: u64,
+        /// The same bytes once decompressed
+        decoded: u64,
+    },
+
     /// Building the tree structure (after analysis)
     Building {
         /// Number of items to process
@@ -0,0 +1,143 @@
+//! POSIX shell quoting for commands run on remote hosts.
+//!
+//! ssh hands its command to the remote user's shell as a single string,
+//! so every path or pattern spliced into one must be quoted here first.
+
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+/// Quote `s` as one shell word.
+///
+/// Single quotes keep every character literal; an embedded `'` closes
+/// the quote, is escaped, and reopens it.
+pub fn quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+/// Quote each word and join them with spaces.
+pub fn join<I, S>(words: I) -> String
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    words.into_iter().map(|word| quote(word.as_ref())).collect::<Vec<_>>().join(" ")
+}
+
+/// `path` as a command operand that can't be mistaken for an option.
+///
+/// Relative paths starting with `-` get a `./` prefix.
+pub fn operand(path: &Path) -> PathBuf {
+    if path.as_os_str().as_encoded_bytes().first() == Some(&b'-') {
+        Path::new(".").join(path)
+    } else {
+        path.to_path_buf()
+    }
+}
+
+/// Quote `path` as a command operand.
+///
+/// The word keeps the path's bytes, so a name that isn't UTF-8 still names
+/// the same file.
+#[cfg(unix)]
+pub fn quote_path(path: &Path) -> OsString {
+    use std::os::unix::ffi::{OsStrExt, OsStringExt};
+
+    let mut word = vec![b'\''];
+    for &byte in operand(path).as_os_str().as_bytes() {
+        match byte {
+            b'\'' => word.extend_from_slice(br"'\''"),
+            _ => word.push(byte),
+        }
+    }
+    word.push(b'\'');
+    OsString::from_vec(word)
+}
+
+/// Quote `path` as a command operand.
+#[cfg(not(unix))]
+pub fn quote_path(path: &Path) -> OsString {
+    quote(&operand(path).to_string_lossy()).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Hostile strings that break or escape naive quoting
+    const HOSTILE: &[&str] = &[
+        "it's",
+        "'; touch pwned; '",
+        "$(touch pwned)",
+        "`touch pwned`",
+        "a\"b\\c",
+        "$HOME ${IFS}*?[a]",
+        "line\nbreak",
+        "'",
+        "''",
+        "",
+        "-rf",
+    ];
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("/srv/data"), "'/srv/data'");
+        assert_eq!(quote("it's"), r"'it'\''s'");
+        assert_eq!(join(["printf", "%s", "a b"]), "'printf' '%s' 'a b'");
+    }
+
+    #[test]
+    fn test_operand() {
+        assert_eq!(operand(Path::new("-rf")), PathBuf::from("./-rf"));
+        assert_eq!(operand(Path::new("/srv/-rf")), PathBuf::from("/srv/-rf"));
+        assert_eq!(operand(Path::new("logs")), PathBuf::from("logs"));
+        assert_eq!(quote_path(Path::new("-it's")), r"'./-it'\''s'");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_quote_path_keeps_bytes() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let name = OsStr::from_bytes(b"it's \xff\xfe.log");
+        std::fs::write(dir.path().join(name), b"").unwrap();
+        assert_eq!(quote_path(Path::new(name)).as_bytes(), b"'it'\\''s \xff\xfe.log'");
+
+        let mut command = OsString::from("rm ");
+        command.push(quote_path(Path::new(name)));
+        let status = std::process::Command::new("sh")
+            .current_dir(dir.path())
+            .arg("-c")
+            .arg(command)
+            .status()
+            .unwrap();
+        assert!(status.success());
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_shell_reads_quoted_words_back() {
+        let dir = tempfile::TempDir::new().unwrap();
+        for word in HOSTILE {
+            let output = std::process::Command::new("sh")
+                .current_dir(dir.path())
+                .arg("-c")
+                .arg(format!("printf '%s' {}", quote(word)))
+                .output()
+                .unwrap();
+            assert_eq!(String::from_utf8(output.stdout).unwrap(), *word);
+        }
+
+        let output = std::process::Command::new("sh")
+            .current_dir(dir.path())
+            .arg("-c")
+            .arg(format!(r"printf '%s\0' {}", join(HOSTILE)))
+            .output()
+            .unwrap();
+        let words: Vec<_> = output.stdout.split(|&b| b == 0).map(|w| String::from_utf8_lossy(w)).collect();
+        assert_eq!(words[..HOSTILE.len()], *HOSTILE);
+        assert!(!dir.path().join("pwned").exists());
+    }
+}
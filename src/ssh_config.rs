@@ -0,0 +1,388 @@
+//! Reader for OpenSSH client configuration (`~/.ssh/config`).
+//!
+//! ssh applies the file itself when it connects; this is for showing what
+//! an alias resolves to and for deciding which defaults data-x may add
+//! without overriding the user's own settings.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Nesting limit for `Include`, as in OpenSSH
+const MAX_INCLUDE_DEPTH: usize = 16;
+
+/// Parsed client configuration.
+#[derive(Debug, Clone, Default)]
+pub struct SshConfig {
+    blocks: Vec<Block>,
+}
+
+/// Settings under one `Host` (or `Match`) line
+#[derive(Debug, Clone)]
+struct Block {
+    /// Host patterns; None for a `Match` block that never applies
+    patterns: Option<Vec<String>>,
+    /// Lowercase keyword and value, in file order
+    settings: Vec<(String, String)>,
+}
+
+/// Effective settings for one host.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HostConfig {
+    values: HashMap<String, String>,
+    identity_files: Vec<PathBuf>,
+}
+
+impl SshConfig {
+    /// Read `~/.ssh/config`; a missing or unreadable file is empty.
+    pub fn load() -> Self {
+        match std::env::var_os("HOME") {
+            Some(home) => Self::load_from(&Path::new(&home).join(".ssh").join("config")),
+            None => Self::default(),
+        }
+    }
+
+    /// Read the configuration at `path`, resolving relative `Include`s
+    /// against its directory.
+    pub fn load_from(path: &Path) -> Self {
+        let mut config = Self::default();
+        if let Ok(text) = std::fs::read_to_string(path) {
+            let dir = path.parent().unwrap_or(Path::new("."));
+            config.read(&text, dir, 0);
+        }
+        config
+    }
+
+    /// Parse configuration text; `Include` paths are relative to `dir`.
+    pub fn parse(text: &str, dir: &Path) -> Self {
+        let mut config = Self::default();
+        config.read(text, dir, 0);
+        config
+    }
+
+    fn read(&mut self, text: &str, dir: &Path, depth: usize) {
+        for line in text.lines() {
+            let Some((keyword, value)) = split_line(line) else {
+                continue;
+            };
+            match keyword.as_str() {
+                "host" => self.blocks.push(Block {
+                    patterns: Some(split_words(&value)),
+                    settings: Vec::new(),
+                }),
+                "match" => {
+                    let all = value.trim().eq_ignore_ascii_case("all");
+                    self.blocks.push(Block {
+                        patterns: all.then(|| vec!["*".to_string()]),
+                        settings: Vec::new(),
+                    });
+                }
+                "include" if depth < MAX_INCLUDE_DEPTH => {
+                    for pattern in split_words(&value) {
+                        for path in expand_include(&pattern, dir) {
+                            if let Ok(text) = std::fs::read_to_string(&path) {
+                                self.read(&text, dir, depth + 1);
+                            }
+                        }
+                    }
+                }
+                _ => {
+                    if self.blocks.is_empty() {
+                        // Settings before the first Host apply to every host
+                        self.blocks.push(Block {
+                            patterns: Some(vec!["*".to_string()]),
+                            settings: Vec::new(),
+                        });
+                    }
+                    if let Some(block) = self.blocks.last_mut() {
+                        block.settings.push((keyword, value));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Effective settings for `host`: the first value of each keyword from
+    /// every matching block wins, identity files accumulate.
+    pub fn resolve(&self, host: &str) -> HostConfig {
+        let mut config = HostConfig::default();
+        for block in &self.blocks {
+            if !block.patterns.as_ref().is_some_and(|patterns| host_matches(patterns, host)) {
+                continue;
+            }
+            for (keyword, value) in &block.settings {
+                if keyword == "identityfile" {
+                    config.identity_files.push(expand_home(value));
+                } else {
+                    config.values.entry(keyword.clone()).or_insert_with(|| value.clone());
+                }
+            }
+        }
+        if let Some(host_name) = config.values.get_mut("hostname") {
+            *host_name = host_name.replace("%h", host);
+        }
+        config
+    }
+
+    /// Hosts named without wildcards, in file order
+    pub fn aliases(&self) -> Vec<String> {
+        let mut aliases: Vec<String> = Vec::new();
+        for patterns in self.blocks.iter().filter_map(|block| block.patterns.as_ref()) {
+            for pattern in patterns {
+                if !pattern.contains(['*', '?', '!']) && !aliases.contains(pattern) {
+                    aliases.push(pattern.clone());
+                }
+            }
+        }
+        aliases
+    }
+}
+
+impl HostConfig {
+    /// Value of any keyword (case-insensitive)
+    pub fn get(&self, keyword: &str) -> Option<&str> {
+        self.values.get(&keyword.to_ascii_lowercase()).map(String::as_str)
+    }
+
+    pub fn host_name(&self) -> Option<&str> {
+        self.get("hostname")
+    }
+
+    pub fn user(&self) -> Option<&str> {
+        self.get("user")
+    }
+
+    pub fn port(&self) -> Option<u16> {
+        self.get("port").and_then(|port| port.parse().ok())
+    }
+
+    pub fn identity_files(&self) -> &[PathBuf] {
+        &self.identity_files
+    }
+
+    /// Jump hosts, None when unset or `none`
+    pub fn proxy_jump(&self) -> Option<&str> {
+        self.get("proxyjump").filter(|jump| !jump.eq_ignore_ascii_case("none"))
+    }
+}
+
+/// Whether `options` (as given to `-o`) set `keyword`.
+pub fn sets_option(options: &[String], keyword: &str) -> bool {
+    options
+        .iter()
+        .filter_map(|option| split_line(option))
+        .any(|(key, _)| key.eq_ignore_ascii_case(keyword))
+}
+
+/// Keyword (lowercased) and value of a config line, skipping comments
+fn split_line(line: &str) -> Option<(String, String)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let end = line.find(|c: char| c.is_whitespace() || c == '=').unwrap_or(line.len());
+    let (keyword, rest) = line.split_at(end);
+    let rest = rest.trim_start();
+    let rest = rest.strip_prefix('=').unwrap_or(rest).trim();
+    Some((keyword.to_ascii_lowercase(), unquote(rest)))
+}
+
+/// Strip surrounding double quotes from a single-word value
+fn unquote(value: &str) -> String {
+    match value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+        Some(inner) if !inner.contains('"') => inner.to_string(),
+        _ => value.to_string(),
+    }
+}
+
+/// Whitespace-separated words, honoring double quotes
+fn split_words(value: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut quoted = false;
+    let mut in_word = false;
+    for c in value.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                in_word = true;
+            }
+            c if c.is_whitespace() && !quoted => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            c => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+    words
+}
+
+/// Whether a `Host` line matches: any pattern matches and no negated one
+fn host_matches(patterns: &[String], host: &str) -> bool {
+    let mut matched = false;
+    for pattern in patterns {
+        if let Some(negated) = pattern.strip_prefix('!') {
+            if wildcard_match(negated, host) {
+                return false;
+            }
+        } else if wildcard_match(pattern, host) {
+            matched = true;
+        }
+    }
+    matched
+}
+
+/// `*` and `?` wildcard match over the whole string
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// `~/` prefix to the home directory
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), std::env::var_os("HOME")) {
+        (Some(rest), Some(home)) => Path::new(&home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Files an `Include` pattern names, sorted; wildcards are allowed in the
+/// file name
+fn expand_include(pattern: &str, dir: &Path) -> Vec<PathBuf> {
+    let path = expand_home(pattern);
+    let path = if path.is_absolute() { path } else { dir.join(path) };
+    let name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    if !name.contains(['*', '?']) {
+        return vec![path];
+    }
+
+    let parent = path.parent().unwrap_or(dir);
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(parent)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| wildcard_match(&name, &entry.file_name().to_string_lossy()))
+        .map(|entry| entry.path())
+        .collect();
+    paths.sort();
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+# Global defaults go first
+ServerAliveInterval 30
+
+Host bastion
+    HostName bastion.example.com
+    User jump
+
+Host db-* !db-legacy
+    ProxyJump bastion
+    User dba
+    IdentityFile "~/keys/db key"
+
+Host db-prod
+    HostName=10.0.0.5
+    Port = 2222
+    User ignored
+
+Host *.internal
+    HostName %h.example.net
+    StrictHostKeyChecking yes
+
+Match exec "false"
+    User never
+
+Host *
+    User fallback
+    IdentityFile ~/.ssh/id_ed25519
+"#;
+
+    #[test]
+    fn test_resolve_first_value_wins() {
+        let config = SshConfig::parse(CONFIG, Path::new("/nonexistent"));
+
+        let db = config.resolve("db-prod");
+        assert_eq!(db.host_name(), Some("10.0.0.5"));
+        assert_eq!(db.user(), Some("dba"));
+        assert_eq!(db.port(), Some(2222));
+        assert_eq!(db.proxy_jump(), Some("bastion"));
+        assert_eq!(db.get("ServerAliveInterval"), Some("30"));
+        assert_eq!(db.identity_files().len(), 2);
+        assert!(db.identity_files()[0].ends_with("keys/db key"));
+
+        let legacy = config.resolve("db-legacy");
+        assert_eq!(legacy.user(), Some("fallback"));
+        assert_eq!(legacy.proxy_jump(), None);
+
+        let internal = config.resolve("cache.internal");
+        assert_eq!(internal.host_name(), Some("cache.internal.example.net"));
+        assert_eq!(internal.get("stricthostkeychecking"), Some("yes"));
+
+        assert_eq!(config.resolve("other").host_name(), None);
+        assert_eq!(config.aliases(), ["bastion", "db-prod"]);
+    }
+
+    #[test]
+    fn test_include() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join("conf.d")).unwrap();
+        std::fs::write(dir.path().join("conf.d/10-web.conf"), "Host web\n  HostName web.example.com\n").unwrap();
+        std::fs::write(dir.path().join("conf.d/20-db.conf"), "Host db\n  Port 2200\n").unwrap();
+        std::fs::write(dir.path().join("config"), "Include conf.d/*.conf\nHost *\n  Port 22\n").unwrap();
+
+        let config = SshConfig::load_from(&dir.path().join("config"));
+        assert_eq!(config.resolve("web").host_name(), Some("web.example.com"));
+        assert_eq!(config.resolve("db").port(), Some(2200));
+        assert_eq!(config.resolve("web").port(), Some(22));
+        assert_eq!(config.aliases(), ["web", "db"]);
+        assert!(SshConfig::load_from(&dir.path().join("missing")).aliases().is_empty());
+    }
+
+    #[test]
+    fn test_sets_option() {
+        let options = vec!["StrictHostKeyChecking=yes".to_string(), "Compression yes".to_string()];
+        assert!(sets_option(&options, "stricthostkeychecking"));
+        assert!(sets_option(&options, "Compression"));
+        assert!(!sets_option(&options, "ProxyJump"));
+    }
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("db-*", "db-prod"));
+        assert!(wildcard_match("*", ""));
+        assert!(wildcard_match("web?", "web1"));
+        assert!(!wildcard_match("web?", "web12"));
+        assert!(wildcard_match("*.a*b", "x.aab"));
+        assert!(!wildcard_match("db-*", "xdb-prod"));
+    }
+}
@@ -0,0 +1,147 @@
+//! Size differences between two scans of the same directory.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use super::FileTree;
+
+/// A node whose size differs between two trees
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SizeChange {
+    pub path: PathBuf,
+    pub is_dir: bool,
+    /// Size in the old tree, None if the node was added
+    pub old_size: Option<u64>,
+    /// Size in the new tree, None if the node was removed
+    pub new_size: Option<u64>,
+}
+
+impl SizeChange {
+    /// Growth in bytes (negative if the node shrank or was removed)
+    pub fn delta(&self) -> i128 {
+        i128::from(self.new_size.unwrap_or(0)) - i128::from(self.old_size.unwrap_or(0))
+    }
+}
+
+/// Compare two trees by path.
+///
+/// Returns every node that was added, removed or changed size, largest
+/// change first. Only the top of a removed or added subtree is reported,
+/// not each node below it.
+pub fn diff_trees(old: &FileTree, new: &FileTree) -> Vec<SizeChange> {
+    let old_nodes = sizes_by_path(old);
+    let new_nodes = sizes_by_path(new);
+    let mut changes = Vec::new();
+
+    for (path, &(new_size, is_dir)) in &new_nodes {
+        match old_nodes.get(path) {
+            Some(&(old_size, _)) if old_size == new_size => {}
+            Some(&(old_size, _)) => changes.push(SizeChange {
+                path: path.to_path_buf(),
+                is_dir,
+                old_size: Some(old_size),
+                new_size: Some(new_size),
+            }),
+            None if has_parent_in(path, &old_nodes) || path_is_root(new, path) => changes.push(SizeChange {
+                path: path.to_path_buf(),
+                is_dir,
+                old_size: None,
+                new_size: Some(new_size),
+            }),
+            None => {}
+        }
+    }
+
+    for (path, &(old_size, is_dir)) in &old_nodes {
+        if !new_nodes.contains_key(path) && (has_parent_in(path, &new_nodes) || path_is_root(old, path)) {
+            changes.push(SizeChange {
+                path: path.to_path_buf(),
+                is_dir,
+                old_size: Some(old_size),
+                new_size: None,
+            });
+        }
+    }
+
+    changes.sort_by(|a, b| b.delta().abs().cmp(&a.delta().abs()).then_with(|| a.path.cmp(&b.path)));
+    changes
+}
+
+fn sizes_by_path(tree: &FileTree) -> HashMap<&Path, (u64, bool)> {
+    let Some(root) = tree.root else { return HashMap::new() };
+    root.descendants(&tree.arena)
+        .filter_map(|id| tree.get_node(id))
+        .map(|node| (node.path.as_path(), (node.size, node.is_dir)))
+        .collect()
+}
+
+fn has_parent_in(path: &Path, nodes: &HashMap<&Path, (u64, bool)>) -> bool {
+    path.parent().is_some_and(|parent| nodes.contains_key(parent))
+}
+
+fn path_is_root(tree: &FileTree, path: &Path) -> bool {
+    tree.root
+        .and_then(|root| tree.get_node(root))
+        .is_some_and(|node| node.path == path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::FileNode;
+
+    fn create_tree(files: &[(&str, u64)]) -> FileTree {
+        let mut tree = FileTree::with_root(PathBuf::from("/srv"));
+        let root = tree.root.unwrap();
+        let mut dirs: HashMap<PathBuf, crate::tree::NodeId> = HashMap::new();
+        for &(path, size) in files {
+            let path = PathBuf::from(path);
+            let dir_path = path.parent().unwrap().to_path_buf();
+            let dir = match dirs.get(&dir_path) {
+                Some(&dir) => dir,
+                None if dir_path == Path::new("/srv") => root,
+                None => {
+                    let dir = tree.add_child(root, FileNode::new(dir_path.clone(), true));
+                    dirs.insert(dir_path, dir);
+                    dir
+                }
+            };
+            tree.add_child(dir, FileNode::new(path, false).with_size(size));
+        }
+        tree.calculate_sizes();
+        tree
+    }
+
+    #[test]
+    fn test_changes_largest_first() {
+        let old = create_tree(&[("/srv/logs/a.log", 100), ("/srv/logs/b.log", 50), ("/srv/tmp/x", 10)]);
+        let new = create_tree(&[("/srv/logs/a.log", 400), ("/srv/logs/c.log", 5), ("/srv/tmp/x", 10)]);
+
+        let changes = diff_trees(&old, &new);
+        let summary: Vec<(&str, i128)> = changes.iter().map(|c| (c.path.to_str().unwrap(), c.delta())).collect();
+        assert_eq!(
+            summary,
+            [
+                ("/srv/logs/a.log", 300),
+                ("/srv", 255),
+                ("/srv/logs", 255),
+                ("/srv/logs/b.log", -50),
+                ("/srv/logs/c.log", 5),
+            ]
+        );
+        assert_eq!(changes[3].new_size, None);
+        assert_eq!(changes[4].old_size, None);
+    }
+
+    #[test]
+    fn test_new_subtree_reported_once() {
+        let old = create_tree(&[("/srv/a", 1)]);
+        let new = create_tree(&[("/srv/a", 1), ("/srv/cache/blob", 700)]);
+
+        let changes = diff_trees(&old, &new);
+        let paths: Vec<&str> = changes.iter().map(|c| c.path.to_str().unwrap()).collect();
+        assert_eq!(paths, ["/srv", "/srv/cache"]);
+        assert!(changes[1].is_dir);
+        assert!(diff_trees(&new, &new).is_empty());
+    }
+}
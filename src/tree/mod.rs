@@ -1,5 +1,7 @@
+mod diff;
 mod node;
 
+pub use diff::{diff_trees, SizeChange};
 pub use indextree::NodeId;
 pub use node::{FileNode, FileTree};
 // Re-exported for potential future use
@@ -102,7 +102,7 @@ impl Clone for FileTree {
                 }
 
                 // Clone children recursively
-                Self::clone_children(&self, root_id, new_root, &mut new_tree);
+                Self::clone_children(self, root_id, new_root, &mut new_tree);
             }
         }
 
@@ -257,12 +257,48 @@ impl FileTree {
         }
     }
 
-    /// Remove a node and its subtree
-    #[allow(dead_code)]
+    /// Remove a node and its subtree, taking its size and file count off
+    /// its ancestors up to the first excluded one.
     pub fn remove_node(&mut self, node_id: NodeId) {
+        if let Some(node) = self.get_node(node_id).filter(|node| !node.excluded) {
+            let (size, file_count) = (node.size, node.file_count);
+            let mut current = self.get_parent(node_id);
+            while let Some(node) = current.and_then(|id| self.get_node_mut(id)).filter(|node| !node.excluded) {
+                node.size = node.size.saturating_sub(size);
+                node.file_count = node.file_count.saturating_sub(file_count);
+                current = current.and_then(|id| self.get_parent(id));
+            }
+        }
         node_id.remove_subtree(&mut self.arena);
     }
 
+    /// Copy the subtree below `node_id` into a tree of its own
+    pub fn subtree(&self, node_id: NodeId) -> Option<FileTree> {
+        let node = self.get_node(node_id)?;
+        let mut tree = FileTree::new();
+        let root = tree.arena.new_node(node.clone());
+        tree.root = Some(root);
+        Self::clone_children(self, node_id, root, &mut tree);
+        Some(tree)
+    }
+
+    /// Replace the subtree at `node_id` with the contents of `other`, such as
+    /// a fresh scan of that directory, and recalculate the sizes above it.
+    /// `node_id` stays valid and refers to the new root.
+    pub fn replace_subtree(&mut self, node_id: NodeId, other: &FileTree) {
+        let Some(other_root) = other.root else { return };
+        let Some(new_node) = other.get_node(other_root).cloned() else { return };
+
+        for child_id in self.get_children(node_id) {
+            child_id.remove_subtree(&mut self.arena);
+        }
+        if let Some(node) = self.get_node_mut(node_id) {
+            *node = new_node;
+        }
+        Self::clone_children(other, other_root, node_id, self);
+        self.calculate_sizes();
+    }
+
     /// Find a node by path
     pub fn find_by_path(&self, path: &PathBuf) -> Option<NodeId> {
         self.root.and_then(|root| {
@@ -350,4 +386,67 @@ mod tests {
         let dir = FileNode::new(PathBuf::from("/test/dir"), true);
         assert_eq!(dir.extension, None);
     }
+
+    fn create_nested_tree() -> FileTree {
+        let mut tree = FileTree::with_root(PathBuf::from("/home"));
+        let root = tree.root.unwrap();
+        let alice = tree.add_child(root, FileNode::new(PathBuf::from("/home/alice"), true));
+        tree.add_child(alice, FileNode::new(PathBuf::from("/home/alice/old.txt"), false).with_size(100));
+        let bob = tree.add_child(root, FileNode::new(PathBuf::from("/home/bob"), true));
+        tree.add_child(bob, FileNode::new(PathBuf::from("/home/bob/notes.txt"), false).with_size(50));
+        tree.calculate_sizes();
+        tree
+    }
+
+    #[test]
+    fn test_subtree() {
+        let tree = create_nested_tree();
+        let alice = tree.find_by_path(&PathBuf::from("/home/alice")).unwrap();
+
+        let subtree = tree.subtree(alice).unwrap();
+        let root = subtree.root.unwrap();
+        assert_eq!(subtree.get_node(root).unwrap().path, PathBuf::from("/home/alice"));
+        assert_eq!(subtree.total_size(), 100);
+        assert_eq!(subtree.node_count(), 2);
+    }
+
+    #[test]
+    fn test_replace_subtree() {
+        let mut tree = create_nested_tree();
+        let alice = tree.find_by_path(&PathBuf::from("/home/alice")).unwrap();
+
+        let mut fresh = FileTree::with_root(PathBuf::from("/home/alice"));
+        let fresh_root = fresh.root.unwrap();
+        fresh.add_child(fresh_root, FileNode::new(PathBuf::from("/home/alice/a.bin"), false).with_size(1000));
+        fresh.add_child(fresh_root, FileNode::new(PathBuf::from("/home/alice/b.bin"), false).with_size(2000));
+        fresh.calculate_sizes();
+
+        tree.replace_subtree(alice, &fresh);
+
+        assert_eq!(tree.get_node(alice).unwrap().size, 3000);
+        assert_eq!(tree.get_children(alice).len(), 2);
+        assert!(tree.find_by_path(&PathBuf::from("/home/alice/old.txt")).is_none());
+        assert_eq!(tree.total_size(), 3050);
+        assert_eq!(tree.total_file_count(), 3);
+    }
+
+    #[test]
+    fn test_remove_node() {
+        let mut tree = create_nested_tree();
+        let alice = tree.find_by_path(&PathBuf::from("/home/alice")).unwrap();
+        let old = tree.find_by_path(&PathBuf::from("/home/alice/old.txt")).unwrap();
+
+        tree.remove_node(old);
+        assert!(tree.find_by_path(&PathBuf::from("/home/alice/old.txt")).is_none());
+        assert_eq!(tree.get_node(alice).unwrap().size, 0);
+        assert_eq!((tree.total_size(), tree.total_file_count()), (50, 1));
+
+        // An excluded node counts nothing above it
+        let bob = tree.find_by_path(&PathBuf::from("/home/bob")).unwrap();
+        tree.get_node_mut(bob).unwrap().excluded = true;
+        tree.calculate_sizes();
+        tree.remove_node(bob);
+        assert_eq!((tree.total_size(), tree.total_file_count()), (0, 0));
+        assert_eq!(tree.get_children(tree.root.unwrap()), vec![alice]);
+    }
 }
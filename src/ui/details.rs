@@ -13,6 +13,7 @@ use ratatui::{
 };
 use std::time::SystemTime;
 
+use crate::format::format_size;
 use crate::tree::{FileTree, NodeId};
 use crate::ui::colors::ColorScheme;
 
@@ -213,31 +214,6 @@ fn create_detail_line<'a>(
     ])
 }
 
-/// Format a size in bytes to a human-readable string.
-///
-/// Examples:
-/// - 1024 -> "1.0 KB"
-/// - 1048576 -> "1.0 MB"
-/// - 1073741824 -> "1.0 GB"
-fn format_size(bytes: u64) -> String {
-    const KB: u64 = 1024;
-    const MB: u64 = 1024 * KB;
-    const GB: u64 = 1024 * MB;
-    const TB: u64 = 1024 * GB;
-
-    if bytes >= TB {
-        format!("{:.1} TB", bytes as f64 / TB as f64)
-    } else if bytes >= GB {
-        format!("{:.1} GB", bytes as f64 / GB as f64)
-    } else if bytes >= MB {
-        format!("{:.1} MB", bytes as f64 / MB as f64)
-    } else if bytes >= KB {
-        format!("{:.1} KB", bytes as f64 / KB as f64)
-    } else {
-        format!("{} B", bytes)
-    }
-}
-
 /// Format a number with comma separators for readability.
 ///
 /// Example: 1234567 -> "1,234,567"
@@ -292,18 +268,6 @@ fn calculate_total_percentage(tree: &FileTree, node: &crate::tree::FileNode) ->
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_format_size() {
-        assert_eq!(format_size(0), "0 B");
-        assert_eq!(format_size(512), "512 B");
-        assert_eq!(format_size(1024), "1.0 KB");
-        assert_eq!(format_size(1536), "1.5 KB");
-        assert_eq!(format_size(1024 * 1024), "1.0 MB");
-        assert_eq!(format_size(1024 * 1024 * 1024), "1.0 GB");
-        assert_eq!(format_size(1024 * 1024 * 1024 * 1024), "1.0 TB");
-        assert_eq!(format_size(1536 * 1024 * 1024), "1.5 GB");
-    }
-
     #[test]
     fn test_format_number_with_commas() {
         assert_eq!(format_number_with_commas(0), "0");
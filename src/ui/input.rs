@@ -205,6 +205,8 @@ pub enum Command {
     CopyPath,
     /// Open the selected item in the system file manager.
     OpenInFM,
+    /// Open a shell in the selected directory, over ssh for remote scans.
+    OpenShell,
     /// Export the current view to a file.
     Export,
     /// Show detailed information about the selected item.
@@ -323,6 +325,9 @@ fn handle_normal_mode(key: KeyEvent) -> Command {
         // Open in file manager
         KeyCode::Char('o') => Command::OpenInFM,
 
+        // Open shell
+        KeyCode::Char('S') => Command::OpenShell,
+
         // Export
         KeyCode::Char('e') => Command::Export,
 
@@ -507,6 +512,7 @@ mod tests {
         assert_eq!(handle_key(key_event(KeyCode::Char('r')), &mode), Command::Rescan);
         assert_eq!(handle_key(key_event(KeyCode::Char('c')), &mode), Command::CopyPath);
         assert_eq!(handle_key(key_event(KeyCode::Char('o')), &mode), Command::OpenInFM);
+        assert_eq!(handle_key(key_event(KeyCode::Char('S')), &mode), Command::OpenShell);
         assert_eq!(handle_key(key_event(KeyCode::Char('e')), &mode), Command::Export);
         assert_eq!(handle_key(key_event(KeyCode::Char('i')), &mode), Command::ShowDetails);
         assert_eq!(handle_key(key_event(KeyCode::Char('x')), &mode), Command::Exclude);
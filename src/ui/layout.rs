@@ -11,7 +11,9 @@ use ratatui::{
     Frame,
 };
 
+use crate::actions::Actions;
 use crate::app::{App, ScanPhase, ScanState};
+use crate::format::format_size;
 use crate::scanner::DiskSpaceInfo;
 use crate::tree::{FileTree, NodeId};
 use crate::ui::colors::ColorScheme;
@@ -101,6 +103,7 @@ pub fn render_ui(frame: &mut Frame, app: &mut App) {
                     &app.visible_node_ids,
                     &tree_state,
                     &app.color_scheme,
+                    app.history.as_ref(),
                 );
 
                 treemap_result = Some(render_treemap_with_breadcrumb(
@@ -150,6 +153,7 @@ pub fn render_ui(frame: &mut Frame, app: &mut App) {
                     &app.visible_node_ids,
                     &tree_state,
                     &app.color_scheme,
+                    app.history.as_ref(),
                 );
 
                 // Render stats panel or details panel based on toggle
@@ -492,7 +496,7 @@ fn render_header(frame: &mut Frame, area: Rect, app: &App) {
         path_str.to_string()
     };
 
-    let header_line = Line::from(vec![
+    let mut spans = vec![
         Span::raw(" "),
         Span::styled(format!("{} v{}", APP_NAME, VERSION), title_style),
         Span::raw(" "),
@@ -502,8 +506,21 @@ fn render_header(frame: &mut Frame, area: Rect, app: &App) {
         Span::raw(" "),
         Span::styled("\u{2502}", header_style), // Vertical separator
         Span::raw(" "),
-        Span::styled("Press ? for help", hint_style),
-    ]);
+    ];
+    if app.read_only {
+        spans.push(Span::styled("read-only", title_style));
+        spans.push(Span::raw(" "));
+        spans.push(Span::styled("\u{2502}", header_style));
+        spans.push(Span::raw(" "));
+    }
+    if let Some(scan_time) = app.cached_at {
+        spans.push(Span::styled(format!("cached {}", crate::cache::age_string(scan_time)), hint_style));
+        spans.push(Span::raw(" "));
+        spans.push(Span::styled("\u{2502}", header_style));
+        spans.push(Span::raw(" "));
+    }
+    spans.push(Span::styled("Press ? for help", hint_style));
+    let header_line = Line::from(spans);
 
     let header_block = Block::default()
         .borders(Borders::ALL)
@@ -809,6 +826,16 @@ fn render_status_bar(frame: &mut Frame, area: Rect, app: &App) {
                             ));
                         }
                     }
+
+                    // Remote output received so far
+                    if app.scan_progress.bytes_received > 0 {
+                        spans.push(Span::styled(" │ ", status_style));
+                        let mut transfer = format!("{} received", format_size(app.scan_progress.bytes_received));
+                        if let Some(ratio) = app.scan_progress.compression_ratio() {
+                            transfer.push_str(&format!(" ({:.1}x)", ratio));
+                        }
+                        spans.push(Span::styled(transfer, hint_style));
+                    }
                 }
                 ScanPhase::Complete | ScanPhase::Idle => {
                     spans.push(Span::styled("Ready", count_style));
@@ -843,9 +870,10 @@ fn render_status_bar(frame: &mut Frame, area: Rect, app: &App) {
             spans.push(Span::styled("(ESC to cancel)", hint_style));
         }
         InputMode::Confirm(action) => {
-            let action_text = match action {
-                ConfirmAction::Delete => "Delete? (y/n)",
-                ConfirmAction::Quit => "Quit? (y/n)",
+            let action_text = match (action, &app.actions) {
+                (ConfirmAction::Delete, Actions::Remote(remote)) => format!("Delete on {}? (y/n)", remote.target().host),
+                (ConfirmAction::Delete, Actions::Local) => "Delete? (y/n)".to_string(),
+                (ConfirmAction::Quit, _) => "Quit? (y/n)".to_string(),
             };
             spans.push(Span::styled(
                 action_text,
@@ -854,6 +882,9 @@ fn render_status_bar(frame: &mut Frame, area: Rect, app: &App) {
                     .add_modifier(Modifier::BOLD),
             ));
         }
+        InputMode::Normal if app.message.is_some() => {
+            spans.push(Span::styled(app.message.as_deref().unwrap_or_default(), count_style));
+        }
         InputMode::Normal => {
             // Key shortcuts
             spans.push(Span::styled("?", key_style));
@@ -933,26 +964,6 @@ fn format_compact_number(n: u64) -> String {
     }
 }
 
-/// Format a byte size into human-readable format.
-pub fn format_size(bytes: u64) -> String {
-    const KB: u64 = 1024;
-    const MB: u64 = KB * 1024;
-    const GB: u64 = MB * 1024;
-    const TB: u64 = GB * 1024;
-
-    if bytes >= TB {
-        format!("{:.1} TB", bytes as f64 / TB as f64)
-    } else if bytes >= GB {
-        format!("{:.1} GB", bytes as f64 / GB as f64)
-    } else if bytes >= MB {
-        format!("{:.1} MB", bytes as f64 / MB as f64)
-    } else if bytes >= KB {
-        format!("{:.1} KB", bytes as f64 / KB as f64)
-    } else {
-        format!("{} B", bytes)
-    }
-}
-
 /// Truncate a string to a maximum length, adding ellipsis if needed.
 /// Respects Unicode character boundaries.
 fn truncate_str(s: &str, max_chars: usize) -> String {
@@ -971,7 +982,7 @@ fn truncate_str(s: &str, max_chars: usize) -> String {
 fn render_help_overlay(frame: &mut Frame, area: Rect, color_scheme: &ColorScheme) {
     // Calculate centered overlay dimensions
     let overlay_width = 60.min(area.width.saturating_sub(4));
-    let overlay_height = 29.min(area.height.saturating_sub(2));
+    let overlay_height = 31.min(area.height.saturating_sub(2));
     let overlay_x = (area.width.saturating_sub(overlay_width)) / 2;
     let overlay_y = (area.height.saturating_sub(overlay_height)) / 2;
 
@@ -1074,6 +1085,10 @@ fn render_help_overlay(frame: &mut Frame, area: Rect, color_scheme: &ColorScheme
             Span::styled("  o        ", Style::default().fg(color_scheme.key_fg)),
             Span::styled("Open in file manager", Style::default().fg(color_scheme.text)),
         ]),
+        Line::from(vec![
+            Span::styled("  S        ", Style::default().fg(color_scheme.key_fg)),
+            Span::styled("Open shell here (over ssh for remote scans)", Style::default().fg(color_scheme.text)),
+        ]),
         Line::from(vec![
             Span::styled("  x        ", Style::default().fg(color_scheme.key_fg)),
             Span::styled("Exclude from analysis", Style::default().fg(color_scheme.text)),
@@ -1157,17 +1172,6 @@ mod tests {
         assert_eq!(format_file_count(1234567), "1,234,567 files");
     }
 
-    #[test]
-    fn test_format_size() {
-        assert_eq!(format_size(0), "0 B");
-        assert_eq!(format_size(512), "512 B");
-        assert_eq!(format_size(1024), "1.0 KB");
-        assert_eq!(format_size(1536), "1.5 KB");
-        assert_eq!(format_size(1048576), "1.0 MB");
-        assert_eq!(format_size(1073741824), "1.0 GB");
-        assert_eq!(format_size(1099511627776), "1.0 TB");
-    }
-
     #[test]
     fn test_truncate_str() {
         assert_eq!(truncate_str("hello", 10), "hello");
@@ -17,8 +17,6 @@ pub use treemap::TreemapRect;
 #[allow(unused_imports)]
 pub use details::render_details_panel;
 #[allow(unused_imports)]
-pub use layout::format_size;
-#[allow(unused_imports)]
 pub use stats::{render_stats_panel, FileTypeStats, StatsCategory};
 #[allow(unused_imports)]
 pub use tooltip::render_tooltip;
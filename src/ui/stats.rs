@@ -13,6 +13,7 @@ use ratatui::{
     Frame,
 };
 
+use crate::format::format_size;
 use crate::tree::{FileTree, NodeId};
 use crate::ui::colors::ColorScheme;
 use crate::ui::input::FileCategory;
@@ -187,7 +188,6 @@ impl AggregatedStats {
     }
 
     /// Get stats sorted by size (descending)
-    #[allow(dead_code)]
     pub fn sorted_by_size(&self) -> Vec<(StatsCategory, &FileTypeStats)> {
         let mut sorted: Vec<_> = self.by_category.iter().map(|(&k, v)| (k, v)).collect();
         sorted.sort_by(|a, b| b.1.total_size.cmp(&a.1.total_size));
@@ -319,26 +319,6 @@ fn get_category_color(category: StatsCategory, color_scheme: &ColorScheme) -> ra
     }
 }
 
-/// Format a size in bytes to a human-readable string.
-fn format_size(bytes: u64) -> String {
-    const KB: u64 = 1024;
-    const MB: u64 = 1024 * KB;
-    const GB: u64 = 1024 * MB;
-    const TB: u64 = 1024 * GB;
-
-    if bytes >= TB {
-        format!("{:.1}TB", bytes as f64 / TB as f64)
-    } else if bytes >= GB {
-        format!("{:.1}GB", bytes as f64 / GB as f64)
-    } else if bytes >= MB {
-        format!("{:.1}MB", bytes as f64 / MB as f64)
-    } else if bytes >= KB {
-        format!("{:.1}KB", bytes as f64 / KB as f64)
-    } else {
-        format!("{}B", bytes)
-    }
-}
-
 /// Format a count with K/M suffixes for large numbers
 fn format_count(count: u64) -> String {
     if count >= 1_000_000 {
@@ -381,16 +361,6 @@ mod tests {
         assert_eq!(StatsCategory::from_extension(""), StatsCategory::Other);
     }
 
-    #[test]
-    fn test_format_size() {
-        assert_eq!(format_size(0), "0B");
-        assert_eq!(format_size(512), "512B");
-        assert_eq!(format_size(1024), "1.0KB");
-        assert_eq!(format_size(1024 * 1024), "1.0MB");
-        assert_eq!(format_size(1024 * 1024 * 1024), "1.0GB");
-        assert_eq!(format_size(1024 * 1024 * 1024 * 1024), "1.0TB");
-    }
-
     #[test]
     fn test_format_count() {
         assert_eq!(format_count(0), "0");
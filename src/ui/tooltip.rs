@@ -11,9 +11,9 @@ use ratatui::{
     Frame,
 };
 
+use crate::format::format_size;
 use crate::tree::{FileTree, NodeId};
 use crate::ui::colors::ColorScheme;
-use crate::ui::layout::format_size;
 
 /// Render a tooltip overlay for a hovered treemap node.
 ///
@@ -13,6 +13,8 @@ use ratatui::{
     Frame,
 };
 
+use crate::format::format_size;
+use crate::history::{format_growth, SizeHistory};
 use crate::tree::{FileNode, FileTree, NodeId};
 use crate::ui::colors::ColorScheme;
 
@@ -154,32 +156,6 @@ impl TreeViewState {
 // Note: get_visible_nodes() removed - sorting now handled by App.collect_visible_nodes()
 // VisibleNode struct kept for test compatibility but no longer used in main render path
 
-/// Format a byte size into a human-readable string.
-///
-/// # Arguments
-/// * `bytes` - The size in bytes
-///
-/// # Returns
-/// A formatted string like "1.5 GB", "256 KB", etc.
-pub fn format_size(bytes: u64) -> String {
-    const KB: u64 = 1024;
-    const MB: u64 = KB * 1024;
-    const GB: u64 = MB * 1024;
-    const TB: u64 = GB * 1024;
-
-    if bytes >= TB {
-        format!("{:.1} TB", bytes as f64 / TB as f64)
-    } else if bytes >= GB {
-        format!("{:.1} GB", bytes as f64 / GB as f64)
-    } else if bytes >= MB {
-        format!("{:.1} MB", bytes as f64 / MB as f64)
-    } else if bytes >= KB {
-        format!("{:.1} KB", bytes as f64 / KB as f64)
-    } else {
-        format!("{} B", bytes)
-    }
-}
-
 /// Get the appropriate icon for a file node.
 ///
 /// # Arguments
@@ -296,6 +272,7 @@ fn compute_node_depth(tree: &FileTree, node_id: NodeId) -> usize {
 /// * `visible_node_ids` - Pre-sorted list of visible node IDs from App
 /// * `state` - The tree view state (for selection, scroll, expansion)
 /// * `color_scheme` - The color scheme to use
+/// * `history` - Earlier scans of the root, shown as sparkline and growth columns
 pub fn render_tree_view(
     frame: &mut Frame,
     area: Rect,
@@ -303,6 +280,7 @@ pub fn render_tree_view(
     visible_node_ids: &[NodeId],
     state: &TreeViewState,
     color_scheme: &ColorScheme,
+    history: Option<&SizeHistory>,
 ) {
     if visible_node_ids.is_empty() {
         // Render empty state
@@ -322,6 +300,9 @@ pub fn render_tree_view(
     // Approximate layout: indent + icon(2) + name(variable) + bar(10-20) + size(10) + percent(6)
     let bar_width = 12.min(inner_width.saturating_sub(30)).max(6);
 
+    // Trend columns need at least two snapshots and room next to the names
+    let history = history.filter(|h| h.times.len() >= 2 && inner_width >= 60 + TREND_WIDTH);
+
     // Build list items
     let mut items: Vec<ListItem> = Vec::new();
 
@@ -364,6 +345,7 @@ pub fn render_tree_view(
             color_scheme,
             is_selected,
             state.search_query.as_deref(),
+            history,
         );
 
         items.push(ListItem::new(line));
@@ -380,6 +362,12 @@ pub fn render_tree_view(
     frame.render_widget(list, area);
 }
 
+/// Width of the sparkline column
+const SPARKLINE_WIDTH: usize = 8;
+
+/// Width of the sparkline and growth columns, including separators
+const TREND_WIDTH: usize = SPARKLINE_WIDTH + 12;
+
 /// Build a single line for the tree view.
 fn build_tree_line(
     node: &FileNode,
@@ -391,6 +379,7 @@ fn build_tree_line(
     color_scheme: &ColorScheme,
     is_selected: bool,
     search_query: Option<&str>,
+    history: Option<&SizeHistory>,
 ) -> Line<'static> {
     let mut spans: Vec<Span> = Vec::new();
 
@@ -422,7 +411,8 @@ fn build_tree_line(
         .saturating_sub(3) // icon + space
         .saturating_sub(bar_width + 1) // bar + space
         .saturating_sub(10) // size text
-        .saturating_sub(7); // percentage + spaces
+        .saturating_sub(7) // percentage + spaces
+        .saturating_sub(if history.is_some() { TREND_WIDTH } else { 0 });
 
     let name = truncate_unicode(&node.name, name_max_len);
 
@@ -513,6 +503,24 @@ fn build_tree_line(
         Style::default().fg(color_scheme.text_dim),
     ));
 
+    // Size over time (directories only)
+    if let Some(history) = history {
+        let sparkline = history.sparkline(&node.path, SPARKLINE_WIDTH).unwrap_or_default();
+        let growth = history
+            .growth_per_day(&node.path)
+            .map(format_growth)
+            .unwrap_or_default();
+        spans.push(Span::raw(" "));
+        spans.push(Span::styled(
+            format!("{:<width$}", sparkline, width = SPARKLINE_WIDTH),
+            Style::default().fg(color_scheme.dirs),
+        ));
+        spans.push(Span::styled(
+            format!(" {:>10}", growth),
+            Style::default().fg(color_scheme.text_dim),
+        ));
+    }
+
     Line::from(spans)
 }
 
@@ -521,38 +529,6 @@ mod tests {
     use super::*;
     use std::path::PathBuf;
 
-    #[test]
-    fn test_format_size_bytes() {
-        assert_eq!(format_size(0), "0 B");
-        assert_eq!(format_size(512), "512 B");
-        assert_eq!(format_size(1023), "1023 B");
-    }
-
-    #[test]
-    fn test_format_size_kilobytes() {
-        assert_eq!(format_size(1024), "1.0 KB");
-        assert_eq!(format_size(1536), "1.5 KB");
-        assert_eq!(format_size(1024 * 512), "512.0 KB");
-    }
-
-    #[test]
-    fn test_format_size_megabytes() {
-        assert_eq!(format_size(1024 * 1024), "1.0 MB");
-        assert_eq!(format_size(1024 * 1024 * 100), "100.0 MB");
-    }
-
-    #[test]
-    fn test_format_size_gigabytes() {
-        assert_eq!(format_size(1024 * 1024 * 1024), "1.0 GB");
-        assert_eq!(format_size(1024 * 1024 * 1024 * 2), "2.0 GB");
-    }
-
-    #[test]
-    fn test_format_size_terabytes() {
-        assert_eq!(format_size(1024u64 * 1024 * 1024 * 1024), "1.0 TB");
-        assert_eq!(format_size(1024u64 * 1024 * 1024 * 1024 * 5), "5.0 TB");
-    }
-
     #[test]
     fn test_tree_view_state_new() {
         let state = TreeViewState::new(None);
@@ -11,10 +11,11 @@ use ratatui::{
     Frame,
 };
 
+use crate::format::format_size;
+use crate::render::squarify;
 use crate::tree::{FileTree, NodeId};
 use crate::ui::colors::{get_file_type_color, get_file_type_selection_color, ColorScheme};
 use crate::ui::input::FileCategory;
-use crate::ui::tree_view::format_size;
 
 /// Safely truncate a name string respecting Unicode character boundaries.
 fn truncate_name_unicode(s: &str, max_chars: usize) -> String {
@@ -164,19 +165,19 @@ pub fn render_treemap(
 
     // Calculate treemap layout using squarified algorithm
     let total_size: u64 = items.iter().map(|(_, s, _, _, _)| *s).sum();
+    let sizes: Vec<u64> = items.iter().map(|(_, s, _, _, _)| *s).collect();
     let layout_rects = squarify(
-        &items,
+        &sizes,
         inner_area.x as f64,
         inner_area.y as f64,
         inner_area.width as f64,
         inner_area.height as f64,
-        total_size,
     );
 
     let mut result_rects = Vec::new();
 
     // Render each rectangle
-    for (node_id, size, name, is_dir, extension, lx, ly, lw, lh) in layout_rects {
+    for ((node_id, size, name, is_dir, extension), (lx, ly, lw, lh)) in items.into_iter().zip(layout_rects) {
         let rx = lx.round() as u16;
         let ry = ly.round() as u16;
         let rw = lw.round().max(1.0) as u16;
@@ -224,76 +225,6 @@ pub fn render_treemap(
     result_rects
 }
 
-/// Squarified treemap algorithm.
-/// Attempts to create rectangles with aspect ratios close to 1 (squares).
-/// Returns: Vec<(NodeId, size, name, is_dir, extension, x, y, width, height)>
-fn squarify(
-    items: &[(NodeId, u64, String, bool, Option<String>)],
-    x: f64,
-    y: f64,
-    width: f64,
-    height: f64,
-    total_size: u64,
-) -> Vec<(NodeId, u64, String, bool, Option<String>, f64, f64, f64, f64)> {
-    let mut result = Vec::new();
-
-    if items.is_empty() || total_size == 0 || width <= 0.0 || height <= 0.0 {
-        return result;
-    }
-
-    // Normalize sizes to area
-    let area = width * height;
-    let scale = area / total_size as f64;
-
-    // Use slice-and-dice for simplicity (alternating horizontal/vertical splits)
-    let mut remaining_items = items.to_vec();
-    let mut curr_x = x;
-    let mut curr_y = y;
-    let mut curr_width = width;
-    let mut curr_height = height;
-    let mut horizontal = width >= height;
-
-    while !remaining_items.is_empty() {
-        let (node_id, size, name, is_dir, extension) = remaining_items.remove(0);
-        let item_area = size as f64 * scale;
-
-        let (rect_width, rect_height) = if horizontal {
-            let h = curr_height;
-            let w = if h > 0.0 { item_area / h } else { 0.0 };
-            (w.min(curr_width), h)
-        } else {
-            let w = curr_width;
-            let h = if w > 0.0 { item_area / w } else { 0.0 };
-            (w, h.min(curr_height))
-        };
-
-        result.push((node_id, size, name, is_dir, extension, curr_x, curr_y, rect_width, rect_height));
-
-        // Update remaining area
-        if horizontal {
-            curr_x += rect_width;
-            curr_width -= rect_width;
-            if curr_width <= 1.0 {
-                // Switch to vertical
-                horizontal = false;
-                curr_x = x;
-                curr_width = width;
-            }
-        } else {
-            curr_y += rect_height;
-            curr_height -= rect_height;
-            if curr_height <= 1.0 {
-                // Switch to horizontal
-                horizontal = true;
-                curr_y = y;
-                curr_height = height;
-            }
-        }
-    }
-
-    result
-}
-
 /// Render a single block filling the entire area.
 fn render_single_block(frame: &mut Frame, area: Rect, name: &str, size: u64, percentage: u8, color: Color, selection_color: Color, is_selected: bool) {
     let bg_color = if is_selected { selection_color } else { color };
@@ -40,6 +40,32 @@ const visualizationLabels: Record<VisualizationType, string> = {
   circles: 'Circles',
 }
 
+// Copy of `node` without the descendant `removed`, with the sizes above it reduced
+function withoutNode(node: FileNode, removed: FileNode): FileNode {
+  const children = node.children
+    .filter((child) => child.path !== removed.path)
+    .map((child) => withoutNode(child, removed))
+  if (children.length === node.children.length && children.every((child, i) => child === node.children[i])) {
+    return node
+  }
+  return {
+    ...node,
+    children,
+    size: node.size - removed.size,
+    file_count: node.file_count - (removed.is_dir ? removed.file_count : 1),
+  }
+}
+
+// The node at `path` in `root`, if it is still there
+function findByPath(root: FileNode, path: string): FileNode | null {
+  if (root.path === path) return root
+  for (const child of root.children) {
+    const found = findByPath(child, path)
+    if (found) return found
+  }
+  return null
+}
+
 function App() {
   const [scanResult, setScanResult] = useState<ScanResult | null>(null)
   const [diskInfo, setDiskInfo] = useState<DiskInfo | null>(null)
@@ -134,7 +160,7 @@ function App() {
     setActiveConnection(null)
 
     try {
-      const result = await invoke<ScanResult>('scan_directory', { path, maxDepth: 8 })
+      const result = await invoke<ScanResult>('scan_directory', { path })
       setScanResult(result)
       setCurrentNode(result.root)
       setHistory([result.root])
@@ -220,16 +246,54 @@ function App() {
   }, [scanResult])
 
   // Handle file actions
+  // Paths of a remote scan are on its host: act there, never on this machine
   const handleOpenInFinder = useCallback(async (node: FileNode) => {
     try {
-      await invoke('open_in_finder', { path: node.path })
+      if (isRemote && activeConnection) {
+        const dir = node.is_dir ? node.path : node.path.replace(/\/[^/]*$/, '') || '/'
+        await invoke('open_ssh_shell', { connectionId: activeConnection.id, path: dir })
+      } else {
+        await invoke('open_in_finder', { path: node.path })
+      }
     } catch (e) {
       setError(String(e))
     }
-  }, [])
+  }, [isRemote, activeConnection])
+
+  const deleteRemote = useCallback(async (connection: SSHConnection, node: FileNode) => {
+    if (!scanResult || !confirm(`Permanently delete ${node.path} on ${connection.name}? There is no trash over SSH.`)) {
+      return
+    }
+    await invoke('delete_remote', {
+      connectionId: connection.id,
+      root: scanResult.root.path,
+      path: node.path,
+      isDir: node.is_dir,
+    })
+
+    // Rescanning the host takes a while and nothing else changed
+    const root = withoutNode(scanResult.root, node)
+    setScanResult({
+      ...scanResult,
+      root,
+      total_size: root.size,
+      total_files: root.file_count,
+    })
+    const newHistory = history
+      .map((entry) => findByPath(root, entry.path))
+      .filter((entry): entry is FileNode => entry !== null)
+    setHistory(newHistory.length > 0 ? newHistory : [root])
+    setCurrentNode(newHistory[newHistory.length - 1] || root)
+    setSelectedNode(null)
+  }, [scanResult, history])
 
   const handleMoveToTrash = useCallback(async (node: FileNode) => {
     try {
+      // There is no trash over SSH; deleting for good is a separate action
+      if (isRemote) {
+        setError(`${node.path} is on a server, which has no trash. Use Delete on Server to remove it for good.`)
+        return
+      }
       await invoke('move_to_trash', { path: node.path })
       // Rescan after deletion
       if (currentPath) {
@@ -238,7 +302,17 @@ function App() {
     } catch (e) {
       setError(String(e))
     }
-  }, [currentPath, scanDirectory])
+  }, [isRemote, currentPath, scanDirectory])
+
+  const handleDeleteOnServer = useCallback(async (node: FileNode) => {
+    try {
+      if (isRemote && activeConnection) {
+        await deleteRemote(activeConnection, node)
+      }
+    } catch (e) {
+      setError(String(e))
+    }
+  }, [isRemote, activeConnection, deleteRemote])
 
   // Initial scan on mount - wait for user to select folder
   useEffect(() => {
@@ -485,6 +559,8 @@ function App() {
               onDrillDown={navigateTo}
               onOpenInFinder={handleOpenInFinder}
               onMoveToTrash={handleMoveToTrash}
+              onDeleteOnServer={handleDeleteOnServer}
+              isRemote={isRemote}
             />
           </aside>
         )}
@@ -1,5 +1,5 @@
 import { useState, useCallback } from 'react'
-import { ChevronRight, ChevronDown, Folder, FolderOpen, File, Trash2, ExternalLink } from 'lucide-react'
+import { ChevronRight, ChevronDown, Folder, FolderOpen, File, Trash2, ExternalLink, Terminal } from 'lucide-react'
 import { FileNode, formatSize, getFileCategory, categoryColors, directoryColor } from '../types'
 
 interface FileTreeProps {
@@ -9,6 +9,8 @@ interface FileTreeProps {
   onDrillDown: (node: FileNode) => void
   onOpenInFinder: (node: FileNode) => void
   onMoveToTrash: (node: FileNode) => void
+  onDeleteOnServer?: (node: FileNode) => void
+  isRemote?: boolean
 }
 
 interface TreeNodeProps {
@@ -148,6 +150,8 @@ export function FileTree({
   onDrillDown,
   onOpenInFinder,
   onMoveToTrash,
+  onDeleteOnServer,
+  isRemote = false,
 }: FileTreeProps) {
   const [expandedIds, setExpandedIds] = useState<Set<string>>(new Set([root.id.toString()]))
   const [contextMenu, setContextMenu] = useState<{ x: number; y: number; node: FileNode } | null>(null)
@@ -216,18 +220,22 @@ export function FileTree({
             }}
             className="w-full flex items-center gap-2 px-4 py-2 text-sm hover:bg-dark-accent transition-colors"
           >
-            <ExternalLink className="w-4 h-4" />
-            Show in Finder
+            {isRemote ? <Terminal className="w-4 h-4" /> : <ExternalLink className="w-4 h-4" />}
+            {isRemote ? 'Open SSH Shell Here' : 'Show in Finder'}
           </button>
           <button
             onClick={() => {
-              onMoveToTrash(contextMenu.node)
+              if (isRemote) {
+                onDeleteOnServer?.(contextMenu.node)
+              } else {
+                onMoveToTrash(contextMenu.node)
+              }
               closeContextMenu()
             }}
             className="w-full flex items-center gap-2 px-4 py-2 text-sm text-red-400 hover:bg-dark-accent transition-colors"
           >
             <Trash2 className="w-4 h-4" />
-            Move to Trash
+            {isRemote ? 'Delete on Server...' : 'Move to Trash'}
           </button>
         </div>
       )}
@@ -1,6 +1,7 @@
 import { useState, useEffect } from 'react'
+import { invoke } from '@tauri-apps/api/core'
 import { X, Key, Lock, Server, CheckCircle, XCircle, Loader2 } from 'lucide-react'
-import { SSHConnection, SSHConnectionInput, SSHTestResult, AuthMethod } from '../types'
+import { SSHConnection, SSHConnectionInput, SSHTestResult, SSHConfigHost, AuthMethod } from '../types'
 
 interface SSHConnectionModalProps {
   isOpen: boolean
@@ -26,6 +27,9 @@ export function SSHConnectionModal({
   const [password, setPassword] = useState('')
   const [defaultPath, setDefaultPath] = useState('')
   const [timeoutSecs, setTimeoutSecs] = useState(30)
+  const [jumpHosts, setJumpHosts] = useState('')
+  const [sshOptions, setSshOptions] = useState('')
+  const [configHosts, setConfigHosts] = useState<SSHConfigHost[]>([])
 
   const [isTesting, setIsTesting] = useState(false)
   const [testResult, setTestResult] = useState<SSHTestResult | null>(null)
@@ -49,6 +53,8 @@ export function SSHConnectionModal({
         setPassword('')
         setDefaultPath(connection.default_path || '')
         setTimeoutSecs(connection.timeout_secs)
+        setJumpHosts((connection.jump_hosts || []).join(', '))
+        setSshOptions((connection.ssh_options || []).join('\n'))
       } else {
         // Reset to defaults for new connection
         setName('')
@@ -60,12 +66,23 @@ export function SSHConnectionModal({
         setPassword('')
         setDefaultPath('')
         setTimeoutSecs(30)
+        setJumpHosts('')
+        setSshOptions('')
       }
       setTestResult(null)
       setError(null)
     }
   }, [isOpen, connection])
 
+  // Offer the Host aliases from ~/.ssh/config
+  useEffect(() => {
+    if (isOpen) {
+      invoke<SSHConfigHost[]>('get_ssh_config_hosts')
+        .then(setConfigHosts)
+        .catch(() => setConfigHosts([]))
+    }
+  }, [isOpen])
+
   // Handle escape key
   useEffect(() => {
     const handleEsc = (e: KeyboardEvent) => {
@@ -77,6 +94,15 @@ export function SSHConnectionModal({
 
   if (!isOpen) return null
 
+  const configHost = configHosts.find((h) => h.alias === host.trim())
+
+  const describeConfigHost = (h: SSHConfigHost): string => {
+    let target = h.user ? `${h.user}@${h.host_name}` : h.host_name
+    if (h.port) target += `:${h.port}`
+    if (h.proxy_jump) target += ` via ${h.proxy_jump}`
+    return target
+  }
+
   const buildAuthMethod = (): AuthMethod => {
     switch (authType) {
       case 'key':
@@ -98,6 +124,8 @@ export function SSHConnectionModal({
     password: authType === 'password' ? password : undefined,
     default_path: defaultPath || undefined,
     timeout_secs: timeoutSecs,
+    jump_hosts: jumpHosts.split(',').map((h) => h.trim()).filter(Boolean),
+    ssh_options: sshOptions.split('\n').map((o) => o.trim()).filter(Boolean),
   })
 
   const handleTest = async () => {
@@ -127,7 +155,8 @@ export function SSHConnectionModal({
     }
   }
 
-  const isValid = name.trim() && host.trim() && username.trim()
+  // A username may come from the alias in ~/.ssh/config
+  const isValid = name.trim() && host.trim() && (username.trim() || configHost?.user)
 
   return (
     <div className="fixed inset-0 z-50 flex items-center justify-center">
@@ -172,8 +201,21 @@ export function SSHConnectionModal({
                 value={host}
                 onChange={(e) => setHost(e.target.value)}
                 placeholder="192.168.1.100 or server.example.com"
+                list="ssh-config-hosts"
                 className="w-full px-3 py-2 bg-dark-bg border border-dark-accent rounded-md focus:outline-none focus:border-accent"
               />
+              <datalist id="ssh-config-hosts">
+                {configHosts.map((h) => (
+                  <option key={h.alias} value={h.alias}>
+                    {describeConfigHost(h)}
+                  </option>
+                ))}
+              </datalist>
+              {configHost && (
+                <p className="text-xs text-gray-500 mt-1">
+                  ~/.ssh/config: {describeConfigHost(configHost)}
+                </p>
+              )}
             </div>
             <div className="w-24">
               <label className="block text-sm text-gray-400 mb-1">Port</label>
@@ -193,7 +235,7 @@ export function SSHConnectionModal({
               type="text"
               value={username}
               onChange={(e) => setUsername(e.target.value)}
-              placeholder="root"
+              placeholder={configHost?.user || 'root'}
               className="w-full px-3 py-2 bg-dark-bg border border-dark-accent rounded-md focus:outline-none focus:border-accent"
             />
           </div>
@@ -288,6 +330,34 @@ export function SSHConnectionModal({
             />
           </div>
 
+          {/* Jump Hosts */}
+          <div>
+            <label className="block text-sm text-gray-400 mb-1">
+              Jump Hosts <span className="text-gray-600">(optional)</span>
+            </label>
+            <input
+              type="text"
+              value={jumpHosts}
+              onChange={(e) => setJumpHosts(e.target.value)}
+              placeholder={configHost?.proxy_jump || 'bastion, user@gateway:2222'}
+              className="w-full px-3 py-2 bg-dark-bg border border-dark-accent rounded-md focus:outline-none focus:border-accent"
+            />
+          </div>
+
+          {/* Extra SSH Options */}
+          <div>
+            <label className="block text-sm text-gray-400 mb-1">
+              SSH Options <span className="text-gray-600">(optional, one per line)</span>
+            </label>
+            <textarea
+              value={sshOptions}
+              onChange={(e) => setSshOptions(e.target.value)}
+              placeholder="StrictHostKeyChecking=yes"
+              rows={2}
+              className="w-full px-3 py-2 bg-dark-bg border border-dark-accent rounded-md focus:outline-none focus:border-accent font-mono text-sm"
+            />
+          </div>
+
           {/* Timeout */}
           <div>
             <label className="block text-sm text-gray-400 mb-1">
@@ -97,6 +97,8 @@ export interface SSHConnection {
   auth_method: AuthMethod
   default_path: string | null
   timeout_secs: number
+  jump_hosts: string[]
+  ssh_options: string[]
   created_at: number
   last_used_at: number | null
 }
@@ -111,6 +113,17 @@ export interface SSHConnectionInput {
   password?: string
   default_path?: string
   timeout_secs?: number
+  jump_hosts?: string[]
+  ssh_options?: string[]
+}
+
+export interface SSHConfigHost {
+  alias: string
+  host_name: string
+  user: string | null
+  port: number | null
+  identity_file: string | null
+  proxy_jump: string | null
 }
 
 export interface SSHTestResult {